@@ -332,6 +332,36 @@ pub mod tests {
         (privs, group)
     }
 
+    #[test]
+    fn commitments_of_the_wrong_degree_are_rejected() {
+        type C = threshold_bls::curve::bls12381::Curve;
+
+        let (n, thr) = (5, 3);
+        let (privs, group) = setup_group::<C>(n, thr);
+
+        // the dealer 0 deals to the holder 1 with a polynomial of the given degree
+        let process = |degree: usize| {
+            let secret = PrivatePoly::<C>::new(degree);
+            let public = secret.commit::<<C as Curve>::Point>();
+            let bundle =
+                create_share_bundle(0, &secret, &public, &group, &mut thread_rng()).unwrap();
+
+            process_shares_get_all(&group, &group, 1, &privs[1], &[bundle]).unwrap()
+        };
+
+        for &degree in [thr, thr - 2].iter() {
+            let (shares, publics, statuses) = process(degree);
+            assert!(shares.is_empty());
+            assert!(publics.is_empty());
+            assert_eq!(statuses.get(0, 1), Status::Complaint);
+        }
+
+        let (shares, publics, statuses) = process(thr - 1);
+        assert_eq!(shares.len(), 1);
+        assert_eq!(publics[&0].degree(), thr - 1);
+        assert_eq!(statuses.get(0, 1), Status::Success);
+    }
+
     pub fn invalid2<C: Curve>(mut s: Vec<BundledShares<C>>) -> Vec<BundledShares<C>> {
         // modify a share
         s[0].shares[1].secret = ecies::encrypt(&C::point(), &[1], &mut thread_rng());
@@ -19,6 +19,11 @@ pub enum DKGError {
     #[error("threshold {0} is not in range [{1},{2}]")]
     InvalidThreshold(usize, usize, usize),
 
+    /// DuplicateIndex is raised when creating a group where several nodes have
+    /// the same index, since their shares would be evaluated at the same point.
+    #[error("node index {0} appears more than once in the group")]
+    DuplicateIndex(Idx),
+
     /// NotEnoughValidShares is raised when the DKG has not successfully
     /// processed enough shares because they were invalid. In that case, the DKG
     /// can not continue, the protocol MUST be aborted.
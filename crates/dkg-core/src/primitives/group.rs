@@ -56,7 +56,8 @@ where
 
     /// Creates a new group from the provided vector of nodes and threshold.
     ///
-    /// Valid thresholds are `>= 51% * nodes.len()` and `<= 100% * nodes.len()`
+    /// Valid thresholds are `>= 51% * nodes.len()` and `<= 100% * nodes.len()`,
+    /// and every node must have a distinct index.
     pub fn new(nodes: Vec<Node<C>>, threshold: usize) -> DKGResult<Group<C>> {
         let minimum = minimum_threshold(nodes.len());
         let maximum = nodes.len();
@@ -66,6 +67,13 @@ where
             return Err(DKGError::InvalidThreshold(threshold, minimum, maximum));
         }
 
+        // reject index collisions, the shares of such nodes would be the same
+        for (i, node) in nodes.iter().enumerate() {
+            if nodes[..i].iter().any(|other| other.0 == node.0) {
+                return Err(DKGError::DuplicateIndex(node.0));
+            }
+        }
+
         Ok(Self { nodes, threshold })
     }
 
@@ -105,3 +113,35 @@ where
         Self::new(nodes, thr).expect("threshold should be good here")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use threshold_bls::{curve::bls12381::Curve as BCurve, group::Element};
+
+    fn nodes_with_indices(indices: &[Idx]) -> Vec<Node<BCurve>> {
+        indices
+            .iter()
+            .map(|i| Node::new(*i, <BCurve as Curve>::Point::rand(&mut rand::thread_rng())))
+            .collect()
+    }
+
+    #[test]
+    fn new_rejects_duplicate_indices() {
+        let nodes = nodes_with_indices(&[0, 1, 2, 1]);
+
+        let res = Group::new(nodes, 3);
+
+        assert!(matches!(res, Err(DKGError::DuplicateIndex(1))));
+    }
+
+    #[test]
+    fn new_accepts_distinct_indices() {
+        let nodes = nodes_with_indices(&[3, 0, 2, 1]);
+
+        let group = Group::new(nodes, 3).unwrap();
+
+        assert_eq!(group.len(), 4);
+        assert!(group.contains_index(3));
+    }
+}
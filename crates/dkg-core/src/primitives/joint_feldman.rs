@@ -41,6 +41,11 @@ impl<C: Curve> DKGInfo<C> {
     fn thr(&self) -> usize {
         self.group.threshold
     }
+
+    /// Returns the index of the participant in the group for this DKG
+    pub fn index(&self) -> Idx {
+        self.index
+    }
 }
 
 /// DKG is the struct containing the logic to run the Distributed Key Generation
@@ -10,6 +10,7 @@ pub use types::*;
 
 /// 2D binary array utilities for tracking successful (or not) participation in the DKG
 pub(crate) mod status;
+pub use status::Status;
 
 pub mod joint_feldman;
 
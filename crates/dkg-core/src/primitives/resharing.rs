@@ -130,6 +130,11 @@ impl<C: Curve> RDKG<C> {
         };
         Ok(RDKG { info })
     }
+
+    /// Returns the index of the node in the new group, None if it is leaving the group
+    pub fn new_index(&self) -> Option<Idx> {
+        self.info.new_index
+    }
 }
 
 impl<C: Curve> Phase0<C> for RDKG<C> {
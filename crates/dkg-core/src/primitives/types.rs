@@ -22,6 +22,23 @@ pub struct DKGOutput<C: Curve> {
     pub share: Share<C::Scalar>,
 }
 
+impl<C: Curve> DKGOutput<C> {
+    /// Serializes the output so that a node can persist it and sign after a
+    /// restart without re-running the DKG.
+    ///
+    /// **Warning**: the returned bytes contain the participant's private share
+    /// in plaintext. Anyone with access to them can produce partial signatures
+    /// on behalf of the participant, so they must be stored securely.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, bincode::Error> {
+        bincode::serialize(self)
+    }
+
+    /// Reconstructs an output from bytes produced by `to_bytes`.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, bincode::Error> {
+        bincode::deserialize(bytes)
+    }
+}
+
 /// BundledShares holds all encrypted shares a dealer creates during the first
 /// phase of the protocol.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -0,0 +1,75 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use dkg_core::primitives::minimum_threshold;
+use randcast_mock_demo::fixtures::{dkg_outputs, FIXTURE_SEED};
+use threshold_bls::{
+    curve::bls12381::{self, PairingCurve as BLS12_381},
+    group::Curve,
+    sig::{G1Scheme, G2Scheme, Scheme, SignatureScheme, ThresholdScheme},
+};
+
+const GROUP_SIZES: [usize; 3] = [5, 21, 64];
+
+const MESSAGE: &[u8] = b"randcast benchmark";
+
+fn bench_scheme<C, S>(c: &mut Criterion, name: &str)
+where
+    C: Curve,
+    // We need to bind the Curve's Point and Scalars to the Scheme
+    S: Scheme<Public = <C as Curve>::Point, Private = <C as Curve>::Scalar>
+        + ThresholdScheme
+        + SignatureScheme,
+{
+    let mut group = c.benchmark_group(name);
+
+    // every iteration is a single operation, so the throughput is reported in operations
+    // per second
+    group.throughput(Throughput::Elements(1));
+
+    for &n in GROUP_SIZES.iter() {
+        let t = minimum_threshold(n);
+
+        let outputs = dkg_outputs::<C, S>(n, t, FIXTURE_SEED);
+
+        let public = &outputs[0].public;
+
+        let partials = outputs
+            .iter()
+            .map(|output| S::partial_sign(&output.share, MESSAGE).unwrap())
+            .collect::<Vec<_>>();
+
+        let signature = S::aggregate(t, &partials).unwrap();
+
+        group.bench_with_input(
+            BenchmarkId::new("partial_sign", n),
+            &outputs[0],
+            |b, output| b.iter(|| S::partial_sign(&output.share, MESSAGE).unwrap()),
+        );
+
+        group.bench_with_input(
+            BenchmarkId::new("partial_verify", n),
+            &partials[0],
+            |b, partial| b.iter(|| S::partial_verify(public, MESSAGE, partial).unwrap()),
+        );
+
+        group.bench_with_input(
+            BenchmarkId::new("aggregate", n),
+            &partials,
+            |b, partials| b.iter(|| S::aggregate(t, partials).unwrap()),
+        );
+
+        group.bench_with_input(BenchmarkId::new("verify", n), &signature, |b, signature| {
+            b.iter(|| S::verify(public.public_key(), MESSAGE, signature).unwrap())
+        });
+    }
+
+    group.finish();
+}
+
+fn bench_signatures(c: &mut Criterion) {
+    bench_scheme::<bls12381::Curve, G1Scheme<BLS12_381>>(c, "g1");
+
+    bench_scheme::<bls12381::G2Curve, G2Scheme<BLS12_381>>(c, "g2");
+}
+
+criterion_group!(benches, bench_signatures);
+criterion_main!(benches);
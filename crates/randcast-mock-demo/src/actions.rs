@@ -1 +1,2233 @@
+use crate::test_helpers::{AsyncDelayBoard, InMemoryBoard, ReadableBoard};
+use async_trait::async_trait;
+use dkg_core::primitives::{
+    group::{Group, Node},
+    joint_feldman, resharing,
+    types::{BundledShares, DKGOutput},
+    DKGError, Phase0, Phase1, Phase2,
+};
+use dkg_core::{board::BoardPublisher, DKGPhase, NodeError, Phase2Result};
+use log::{info, warn};
+use rand::RngCore;
+use std::time::{Duration, Instant};
+use thiserror::Error;
+use threshold_bls::{
+    group::Curve,
+    poly::Idx,
+    sig::{Scheme, ThresholdScheme},
+};
+use tokio::sync::watch;
 
+/// Runs all the phases of the DKG and returns the output of every participant. Fails with
+/// `DKGRunError::PublicKeyDivergence` unless all of them agree on the distributed key.
+pub async fn run_dkg<C, S, B, R>(
+    board: &mut B,
+    phase0s: Vec<joint_feldman::DKG<C>>,
+    rng: &mut R,
+) -> Result<Vec<DKGOutput<C>>, DKGRunError>
+where
+    C: Curve,
+    // We need to bind the Curve's Point and Scalars to the Scheme
+    S: Scheme<Public = <C as Curve>::Point, Private = <C as Curve>::Scalar>,
+    B: ReadableBoard<C>,
+    R: RngCore,
+{
+    let (outputs, _) = run_dkg_with_metrics::<C, S, B, R>(board, phase0s, rng).await?;
+
+    Ok(outputs)
+}
+
+/// Timing and size metrics of a DKG run, e.g. to estimate its bandwidth
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DkgMetrics {
+    // the wall-clock duration of the shares, responses and complaints handling phases,
+    // followed by Phase 3 if it was entered
+    pub phase_durations: Vec<Duration>,
+    pub shares_published: usize,
+    pub responses_published: usize,
+    // the number of complaints among the published responses
+    pub complaints: usize,
+    pub justifications_published: usize,
+    // the serialized size of all the bundles on the board
+    pub bytes_exchanged: u64,
+    pub entered_phase3: bool,
+    // the dealers disqualified before Phase 2 for showing another commitment than the
+    // one they published
+    pub equivocations: Vec<Equivocation>,
+}
+
+impl DkgMetrics {
+    /// Counts the bundles on the board along with their serialized size
+    fn record_board<C: Curve, B: ReadableBoard<C>>(&mut self, board: &B) {
+        let shares = board.shares();
+        let responses = board.responses();
+        let justifications = board.justifications();
+
+        self.shares_published = shares.len();
+        self.responses_published = responses.len();
+        self.complaints = board
+            .complaints()
+            .iter()
+            .map(|bundle| bundle.responses.len())
+            .sum();
+        self.justifications_published = justifications.len();
+
+        self.bytes_exchanged = shares
+            .iter()
+            .map(|bundle| bincode::serialized_size(bundle).unwrap())
+            .chain(
+                responses
+                    .iter()
+                    .map(|bundle| bincode::serialized_size(bundle).unwrap()),
+            )
+            .chain(
+                justifications
+                    .iter()
+                    .map(|bundle| bincode::serialized_size(bundle).unwrap()),
+            )
+            .sum();
+    }
+}
+
+/// Like `run_dkg`, but also returns the metrics of the run
+pub async fn run_dkg_with_metrics<C, S, B, R>(
+    board: &mut B,
+    phase0s: Vec<joint_feldman::DKG<C>>,
+    rng: &mut R,
+) -> Result<(Vec<DKGOutput<C>>, DkgMetrics), DKGRunError>
+where
+    C: Curve,
+    // We need to bind the Curve's Point and Scalars to the Scheme
+    S: Scheme<Public = <C as Curve>::Point, Private = <C as Curve>::Scalar>,
+    B: ReadableBoard<C>,
+    R: RngCore,
+{
+    // nobody can abort this DKG
+    let (_abort_sender, abort) = watch::channel(false);
+
+    let mut metrics = DkgMetrics::default();
+
+    let outputs = run_phases(board, phase0s, rng, &abort, &mut metrics, &mut NoopDriver).await?;
+
+    // The distributed public key must be the same
+    check_public_keys(&outputs)?;
+
+    Ok((outputs, metrics))
+}
+
+#[derive(Debug, Error)]
+pub enum DKGRunError {
+    #[error("the DKG was aborted")]
+    Aborted,
+    #[error(transparent)]
+    Node(#[from] NodeError),
+    // the positions in `phase0s` of the participants which timed out in the step
+    #[error("participants {1:?} did not complete the {0:?} step in time")]
+    MissingContributions(DkgStep, Vec<usize>),
+    // the index and the hex-encoded public key of every node
+    #[error("the nodes disagree on the distributed public key: {}", format_public_keys(.0))]
+    PublicKeyDivergence(Vec<(Idx, String)>),
+}
+
+fn format_public_keys(public_keys: &[(Idx, String)]) -> String {
+    public_keys
+        .iter()
+        .map(|(index, public_key)| format!("node {}: {}", index, public_key))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Returns an error listing the public key of every node unless they are all the same
+fn check_public_keys<C: Curve>(outputs: &[DKGOutput<C>]) -> Result<(), DKGRunError> {
+    if is_all_same(outputs.iter().map(|output| &output.public)) {
+        return Ok(());
+    }
+
+    let public_keys = outputs
+        .iter()
+        .map(|output| {
+            let public_key = bincode::serialize(output.public.public_key()).unwrap();
+
+            (output.share.index, hex::encode(public_key))
+        })
+        .collect();
+
+    Err(DKGRunError::PublicKeyDivergence(public_keys))
+}
+
+/// A dealer which showed a participant another commitment than the one it published
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Equivocation {
+    pub dealer_idx: Idx,
+    pub share_idx: Idx,
+}
+
+/// Compares the commitments delivered to each participant with the published ones, and
+/// returns every mismatch by ascending dealer and participant
+pub fn find_equivocations<C, B>(board: &B, share_indices: &[Idx]) -> Vec<Equivocation>
+where
+    C: Curve,
+    B: ReadableBoard<C>,
+{
+    equivocations_among(board, &board.shares(), share_indices)
+}
+
+/// Like `find_equivocations`, with the published shares already read from the board
+fn equivocations_among<C, B>(
+    board: &B,
+    published: &[BundledShares<C>],
+    share_indices: &[Idx],
+) -> Vec<Equivocation>
+where
+    C: Curve,
+    B: ReadableBoard<C>,
+{
+    let mut equivocations = share_indices
+        .iter()
+        .flat_map(|&share_idx| {
+            board
+                .shares_seen_by(share_idx)
+                .into_iter()
+                .filter(|seen| {
+                    published.iter().any(|bundle| {
+                        bundle.dealer_idx == seen.dealer_idx && bundle.public != seen.public
+                    })
+                })
+                .map(move |seen| Equivocation {
+                    dealer_idx: seen.dealer_idx,
+                    share_idx,
+                })
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    equivocations.sort_by_key(|equivocation| (equivocation.dealer_idx, equivocation.share_idx));
+    equivocations.dedup();
+
+    equivocations
+}
+
+/// Drops the bundles of the equivocating dealers, so that every participant complains
+/// about them and none of their justifications can be accepted
+fn exclude_equivocating_dealers<C: Curve>(
+    shares: Vec<BundledShares<C>>,
+    equivocations: &[Equivocation],
+) -> Vec<BundledShares<C>> {
+    shares
+        .into_iter()
+        .filter(|bundle| {
+            !equivocations
+                .iter()
+                .any(|equivocation| equivocation.dealer_idx == bundle.dealer_idx)
+        })
+        .collect()
+}
+
+/// Returns an error if the abort signal has been raised
+fn check_abort(abort: &watch::Receiver<bool>) -> Result<(), DKGRunError> {
+    if *abort.borrow() {
+        return Err(DKGRunError::Aborted);
+    }
+
+    Ok(())
+}
+
+/// Runs all the phases of the DKG and returns the output of every participant,
+/// without checking that the participants agree on the distributed key.
+///
+/// The `abort` signal is checked before every step of a participant, so that the
+/// DKG stops with `DKGRunError::Aborted` as soon as it gets raised, e.g. when the
+/// group was superseded.
+pub async fn run_dkg_phases<C, B, R>(
+    board: &mut B,
+    phase0s: Vec<joint_feldman::DKG<C>>,
+    rng: &mut R,
+    abort: &watch::Receiver<bool>,
+) -> Result<Vec<DKGOutput<C>>, DKGRunError>
+where
+    C: Curve,
+    B: ReadableBoard<C>,
+    R: RngCore,
+{
+    run_phases(
+        board,
+        phase0s,
+        rng,
+        abort,
+        &mut DkgMetrics::default(),
+        &mut NoopDriver,
+    )
+    .await
+}
+
+/// The steps `run_dkg` takes every participant through, in order
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DkgStep {
+    // publishes the shares
+    Shares,
+    // publishes the responses to the shares
+    Responses,
+    // publishes the justifications of the complained shares
+    Justifications,
+    // only entered if there were complaints
+    Phase3,
+}
+
+/// What `run_dkg_with_driver` does with a participant whose step failed
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Recovery {
+    // reads the board again and runs the step again
+    Retry,
+    // the participant drops out of the DKG
+    Skip,
+}
+
+/// Hooks called while `run_dkg_with_driver` runs the phases, e.g. for a simulation to
+/// snapshot the board or delay a step. A hook stops the DKG by returning an error.
+#[async_trait(?Send)]
+pub trait PhaseDriver<B> {
+    /// Called before the first participant runs the step
+    fn before_phase(&mut self, _step: DkgStep, _board: &B) -> Result<(), DKGRunError> {
+        Ok(())
+    }
+
+    /// Called after every participant ran the step
+    fn after_phase(&mut self, _step: DkgStep, _board: &B) -> Result<(), DKGRunError> {
+        Ok(())
+    }
+
+    /// Called after a participant ran the step, `participant` is its position in `phase0s`
+    fn on_phase_result(
+        &mut self,
+        _step: DkgStep,
+        _participant: usize,
+        _board: &B,
+    ) -> Result<(), DKGRunError> {
+        Ok(())
+    }
+
+    /// Called before the bundles processed by the step are read from the board, e.g. to
+    /// wait for their delivery. Every step but `DkgStep::Shares` reads the board.
+    async fn before_reading(&mut self, _step: DkgStep, _board: &B) -> Result<(), DKGRunError> {
+        Ok(())
+    }
+
+    /// Called when a participant failed a step which reads the board. The error is
+    /// returned unless the participant is retried or skipped.
+    async fn on_phase_error(
+        &mut self,
+        _step: DkgStep,
+        _participant: usize,
+        err: NodeError,
+        _board: &B,
+    ) -> Result<Recovery, DKGRunError> {
+        Err(err.into())
+    }
+
+    /// The time a participant gets to run the step. The participants which time out are
+    /// reported with `DKGRunError::MissingContributions` once the others are done with it.
+    fn step_timeout(&self, _step: DkgStep) -> Option<Duration> {
+        None
+    }
+
+    /// Returns true to end the DKG after the step, in which case nobody gets an output
+    fn stops_after(&self, _step: DkgStep) -> bool {
+        false
+    }
+}
+
+/// Runs the phases without any hook
+pub struct NoopDriver;
+
+impl<B> PhaseDriver<B> for NoopDriver {}
+
+/// Like `run_dkg_phases`, but calls the hooks of the driver around every step
+pub async fn run_dkg_with_driver<C, B, R, D>(
+    board: &mut B,
+    phase0s: Vec<joint_feldman::DKG<C>>,
+    rng: &mut R,
+    driver: &mut D,
+) -> Result<Vec<DKGOutput<C>>, DKGRunError>
+where
+    C: Curve,
+    B: ReadableBoard<C>,
+    R: RngCore,
+    D: PhaseDriver<B>,
+{
+    // only the driver can abort this DKG
+    let (_abort_sender, abort) = watch::channel(false);
+
+    run_phases(
+        board,
+        phase0s,
+        rng,
+        &abort,
+        &mut DkgMetrics::default(),
+        driver,
+    )
+    .await
+}
+
+fn log_phase_completed(step: DkgStep, duration: Duration) {
+    info!(
+        "event=dkg_phase_completed phase={:?} duration_ms={}",
+        step,
+        duration.as_millis()
+    );
+}
+
+/// The Phase 0 of a participant of a DKG or of a resharing
+trait Participant<C: Curve>: Phase0<C> {
+    /// The index of the share the participant gets, None if it doesn't get any
+    fn share_index(&self) -> Option<Idx>;
+}
+
+impl<C: Curve> Participant<C> for joint_feldman::DKG<C> {
+    fn share_index(&self) -> Option<Idx> {
+        Some(self.info.index())
+    }
+}
+
+impl<C: Curve> Participant<C> for resharing::RDKG<C> {
+    fn share_index(&self) -> Option<Idx> {
+        self.new_index()
+    }
+}
+
+/// Runs a step of a participant within `timeout`, if any. Returns None if it timed out.
+async fn run_step<C, B, T, P>(
+    phase: P,
+    board: &mut B,
+    arg: T,
+    timeout: Option<Duration>,
+) -> Result<Option<P::Next>, NodeError>
+where
+    C: Curve,
+    B: BoardPublisher<C>,
+    P: DKGPhase<C, B, T>,
+{
+    let duration = match timeout {
+        Some(duration) => duration,
+        None => return Ok(Some(phase.run(board, arg).await?)),
+    };
+
+    match run_with_timeout(phase, board, arg, duration).await {
+        Ok(next) => Ok(Some(next)),
+        Err(PhaseError::Timeout(_)) => Ok(None),
+        Err(PhaseError::NodeError(err)) => Err(err),
+    }
+}
+
+/// Reads the shares from the board, without the bundles of the dealers which equivocated
+fn read_shares<C, B>(
+    board: &B,
+    share_indices: &[Idx],
+    metrics: &mut DkgMetrics,
+) -> Vec<BundledShares<C>>
+where
+    C: Curve,
+    B: ReadableBoard<C>,
+{
+    let published = board.shares();
+
+    metrics.equivocations = equivocations_among(board, &published, share_indices);
+
+    exclude_equivocating_dealers(published, &metrics.equivocations)
+}
+
+/// Returns an error naming the participants which timed out in the step, if any
+fn check_missing(step: DkgStep, missing: Vec<usize>) -> Result<(), DKGRunError> {
+    if !missing.is_empty() {
+        return Err(DKGRunError::MissingContributions(step, missing));
+    }
+
+    Ok(())
+}
+
+async fn run_phases<C, B, R, D, P>(
+    board: &mut B,
+    phase0s: Vec<P>,
+    rng: &mut R,
+    abort: &watch::Receiver<bool>,
+    metrics: &mut DkgMetrics,
+    driver: &mut D,
+) -> Result<Vec<DKGOutput<C>>, DKGRunError>
+where
+    C: Curve,
+    B: ReadableBoard<C>,
+    R: RngCore,
+    D: PhaseDriver<B>,
+    P: Participant<C>,
+    // Phase 3 is run again when the driver retries it
+    <<<P as Phase0<C>>::Next as Phase1<C>>::Next as Phase2<C>>::Next: Clone,
+{
+    let indices = phase0s
+        .iter()
+        .filter_map(|phase0| phase0.share_index())
+        .collect::<Vec<_>>();
+
+    // Phase 1: Publishes shares
+    driver.before_phase(DkgStep::Shares, board)?;
+    let timeout = driver.step_timeout(DkgStep::Shares);
+    let start = Instant::now();
+    let mut phase1s = Vec::new();
+    let mut missing = Vec::new();
+    for (i, phase0) in phase0s.into_iter().enumerate() {
+        check_abort(abort)?;
+        // the shares can't be published twice, so the driver can't retry this step
+        match run_step(phase0, board, &mut *rng, timeout).await? {
+            Some(phase1) => {
+                phase1s.push((i, phase1));
+                driver.on_phase_result(DkgStep::Shares, i, board)?;
+            }
+            None => missing.push(i),
+        }
+    }
+    check_missing(DkgStep::Shares, missing)?;
+    metrics.phase_durations.push(start.elapsed());
+    log_phase_completed(DkgStep::Shares, start.elapsed());
+    driver.after_phase(DkgStep::Shares, board)?;
+
+    if driver.stops_after(DkgStep::Shares) {
+        metrics.record_board(board);
+        return Ok(Vec::new());
+    }
+
+    // Phase2
+    driver.before_phase(DkgStep::Responses, board)?;
+    driver.before_reading(DkgStep::Responses, board).await?;
+    let timeout = driver.step_timeout(DkgStep::Responses);
+    let mut shares = read_shares(board, &indices, metrics);
+    let start = Instant::now();
+    let mut phase2s = Vec::new();
+    let mut missing = Vec::new();
+    for (i, phase1) in phase1s {
+        loop {
+            check_abort(abort)?;
+            match run_step(phase1.clone(), board, &shares[..], timeout).await {
+                Ok(Some(phase2)) => {
+                    phase2s.push((i, phase2));
+                    driver.on_phase_result(DkgStep::Responses, i, board)?;
+                }
+                Ok(None) => missing.push(i),
+                Err(err) => match driver
+                    .on_phase_error(DkgStep::Responses, i, err, board)
+                    .await?
+                {
+                    Recovery::Retry => {
+                        shares = read_shares(board, &indices, metrics);
+                        continue;
+                    }
+                    Recovery::Skip => {}
+                },
+            }
+            break;
+        }
+    }
+    check_missing(DkgStep::Responses, missing)?;
+    metrics.phase_durations.push(start.elapsed());
+    log_phase_completed(DkgStep::Responses, start.elapsed());
+    // the shares of the dealers which equivocated were not processed
+    for equivocation in metrics.equivocations.iter() {
+        warn!(
+            "event=dkg_equivocation dealer_idx={} share_idx={}",
+            equivocation.dealer_idx, equivocation.share_idx
+        );
+    }
+    driver.after_phase(DkgStep::Responses, board)?;
+
+    if driver.stops_after(DkgStep::Responses) {
+        metrics.record_board(board);
+        return Ok(Vec::new());
+    }
+
+    // Get the complaints from the board, the dealers they target have to justify
+    // their shares in Phase 3
+    driver.before_phase(DkgStep::Justifications, board)?;
+    driver
+        .before_reading(DkgStep::Justifications, board)
+        .await?;
+    let timeout = driver.step_timeout(DkgStep::Justifications);
+    let mut complaints = board.complaints();
+    let start = Instant::now();
+    let mut results = Vec::new();
+    let mut missing = Vec::new();
+    for (i, phase2) in phase2s {
+        loop {
+            check_abort(abort)?;
+            match run_step(phase2.clone(), board, &complaints[..], timeout).await {
+                Ok(Some(result)) => {
+                    results.push((i, result));
+                    driver.on_phase_result(DkgStep::Justifications, i, board)?;
+                }
+                Ok(None) => missing.push(i),
+                Err(err) => match driver
+                    .on_phase_error(DkgStep::Justifications, i, err, board)
+                    .await?
+                {
+                    Recovery::Retry => {
+                        complaints = board.complaints();
+                        continue;
+                    }
+                    Recovery::Skip => {}
+                },
+            }
+            break;
+        }
+    }
+    check_missing(DkgStep::Justifications, missing)?;
+    metrics.phase_durations.push(start.elapsed());
+    log_phase_completed(DkgStep::Justifications, start.elapsed());
+    driver.after_phase(DkgStep::Justifications, board)?;
+
+    if driver.stops_after(DkgStep::Justifications) {
+        metrics.record_board(board);
+        return Ok(Vec::new());
+    }
+
+    // Phase3 is only run by everyone if there were complaints
+    let entering_phase3 = results
+        .iter()
+        .any(|(_, result)| matches!(result, Phase2Result::GoToPhase3(_)));
+    if entering_phase3 {
+        driver.before_phase(DkgStep::Phase3, board)?;
+        driver.before_reading(DkgStep::Phase3, board).await?;
+    }
+    let timeout = driver.step_timeout(DkgStep::Phase3);
+    // Get the justifications from the board
+    let mut justifications = board.justifications();
+    let start = Instant::now();
+    let mut outputs = Vec::new();
+    let mut missing = Vec::new();
+    for (i, result) in results {
+        check_abort(abort)?;
+        let phase3 = match result {
+            Phase2Result::Output(out) => {
+                outputs.push(out);
+                continue;
+            }
+            Phase2Result::GoToPhase3(phase3) => phase3,
+        };
+        metrics.entered_phase3 = true;
+        loop {
+            check_abort(abort)?;
+            match run_step(phase3.clone(), board, &justifications[..], timeout).await {
+                Ok(Some(out)) => {
+                    outputs.push(out);
+                    driver.on_phase_result(DkgStep::Phase3, i, board)?;
+                }
+                Ok(None) => missing.push(i),
+                Err(err) => match driver
+                    .on_phase_error(DkgStep::Phase3, i, err, board)
+                    .await?
+                {
+                    Recovery::Retry => {
+                        justifications = board.justifications();
+                        continue;
+                    }
+                    Recovery::Skip => {}
+                },
+            }
+            break;
+        }
+    }
+    check_missing(DkgStep::Phase3, missing)?;
+    if metrics.entered_phase3 {
+        metrics.phase_durations.push(start.elapsed());
+        log_phase_completed(DkgStep::Phase3, start.elapsed());
+        driver.after_phase(DkgStep::Phase3, board)?;
+    }
+
+    metrics.record_board(board);
+
+    Ok(outputs)
+}
+
+/// How a phase is retried while the board doesn't hold enough bundles yet
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RetryConfig {
+    // the number of times a phase is run before its error is returned
+    pub max_attempts: usize,
+    // the delay before the first retry, doubled after every attempt
+    pub initial_backoff: Duration,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        RetryConfig {
+            max_attempts: 5,
+            initial_backoff: Duration::from_millis(50),
+        }
+    }
+}
+
+/// Counts the attempts of a phase and waits between them
+struct Backoff {
+    attempts: usize,
+    delay: Duration,
+    config: RetryConfig,
+}
+
+impl Backoff {
+    fn new(config: RetryConfig) -> Self {
+        Backoff {
+            attempts: 1,
+            delay: config.initial_backoff,
+            config,
+        }
+    }
+
+    /// Waits before the next attempt, or returns false once all the attempts are used up
+    async fn wait(&mut self) -> bool {
+        if self.attempts >= self.config.max_attempts {
+            return false;
+        }
+
+        tokio::time::delay_for(self.delay).await;
+
+        self.attempts += 1;
+        self.delay *= 2;
+
+        true
+    }
+}
+
+/// Returns true if the phase failed because the board is missing some bundles, which
+/// may still arrive
+fn is_retryable(err: &NodeError) -> bool {
+    matches!(
+        err,
+        NodeError::DKGError(DKGError::NotEnoughValidShares(..))
+            | NodeError::DKGError(DKGError::NotEnoughJustifications(..))
+    )
+}
+
+/// Retries the participants which failed for lack of bundles on the board
+struct RetryDriver {
+    config: RetryConfig,
+    backoff: Backoff,
+}
+
+#[async_trait(?Send)]
+impl<B> PhaseDriver<B> for RetryDriver {
+    fn on_phase_result(
+        &mut self,
+        _step: DkgStep,
+        _participant: usize,
+        _board: &B,
+    ) -> Result<(), DKGRunError> {
+        // the next participant gets all the attempts again
+        self.backoff = Backoff::new(self.config);
+
+        Ok(())
+    }
+
+    async fn on_phase_error(
+        &mut self,
+        _step: DkgStep,
+        _participant: usize,
+        err: NodeError,
+        _board: &B,
+    ) -> Result<Recovery, DKGRunError> {
+        if is_retryable(&err) && self.backoff.wait().await {
+            return Ok(Recovery::Retry);
+        }
+
+        Err(err.into())
+    }
+}
+
+/// Runs the DKG like `run_dkg_phases`, but a participant which can't process the shares
+/// or the justifications because not enough of them are on the board yet reads the board
+/// again, with an exponential backoff. Any other error is returned right away.
+pub async fn run_dkg_with_retry<C, B, R>(
+    board: &mut B,
+    phase0s: Vec<joint_feldman::DKG<C>>,
+    rng: &mut R,
+    retry: RetryConfig,
+) -> Result<Vec<DKGOutput<C>>, DKGRunError>
+where
+    C: Curve,
+    B: ReadableBoard<C>,
+    R: RngCore,
+{
+    let mut driver = RetryDriver {
+        config: retry,
+        backoff: Backoff::new(retry),
+    };
+
+    run_dkg_with_driver(board, phase0s, rng, &mut driver).await
+}
+
+/// The complaints a participant would raise against the dealers of a DKG
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConsistencyReport {
+    pub index: Idx,
+    // the dealers whose share was missing or did not match their commitment
+    pub complaints: Vec<Idx>,
+}
+
+impl ConsistencyReport {
+    pub fn is_consistent(&self) -> bool {
+        self.complaints.is_empty()
+    }
+}
+
+/// Ends the DKG once every participant published its complaints
+struct DryrunDriver;
+
+impl<B> PhaseDriver<B> for DryrunDriver {
+    fn stops_after(&self, step: DkgStep) -> bool {
+        step == DkgStep::Responses
+    }
+}
+
+/// Runs the first two phases of the DKG, where every participant verifies the shares
+/// published on the board against the commitment of their dealer, and reports the
+/// complaints of each participant in the order of `phase0s`. The DKG is not finalized,
+/// so no share is produced.
+pub async fn run_dkg_dryrun<C, B, R>(
+    board: &mut B,
+    phase0s: Vec<joint_feldman::DKG<C>>,
+    rng: &mut R,
+) -> Result<Vec<ConsistencyReport>, DKGRunError>
+where
+    C: Curve,
+    B: ReadableBoard<C>,
+    R: RngCore,
+{
+    let indices = phase0s
+        .iter()
+        .map(|phase0| phase0.info.index())
+        .collect::<Vec<_>>();
+
+    run_dkg_with_driver(board, phase0s, rng, &mut DryrunDriver).await?;
+
+    let complaints = board.complaints();
+
+    let reports = indices
+        .into_iter()
+        .map(|index| ConsistencyReport {
+            index,
+            complaints: complaints
+                .iter()
+                .filter(|bundle| bundle.share_idx == index)
+                .flat_map(|bundle| bundle.responses.iter().map(|r| r.dealer_idx))
+                .collect(),
+        })
+        .collect();
+
+    Ok(reports)
+}
+
+/// Waits for the bundles of the previous step to be delivered before reading them
+struct DeliveryDriver {
+    timeout: Duration,
+}
+
+#[async_trait(?Send)]
+impl<C: Curve> PhaseDriver<AsyncDelayBoard<C>> for DeliveryDriver {
+    async fn before_reading(
+        &mut self,
+        _step: DkgStep,
+        board: &AsyncDelayBoard<C>,
+    ) -> Result<(), DKGRunError> {
+        board.wait_for_delivery(self.timeout).await;
+
+        Ok(())
+    }
+}
+
+/// Runs the DKG over a board with delayed delivery. Each phase waits for the bundles of
+/// the previous phase until `phase_timeout` elapses, and bundles arriving later are
+/// ignored. Like `run_dkg_phases`, the outputs are not checked to agree.
+pub async fn run_dkg_with_timeout<C, R>(
+    board: &mut AsyncDelayBoard<C>,
+    phase0s: Vec<joint_feldman::DKG<C>>,
+    rng: &mut R,
+    phase_timeout: Duration,
+) -> Result<Vec<DKGOutput<C>>, DKGRunError>
+where
+    C: Curve,
+    R: RngCore,
+{
+    let mut driver = DeliveryDriver {
+        timeout: phase_timeout,
+    };
+
+    run_dkg_with_driver(board, phase0s, rng, &mut driver).await
+}
+
+#[derive(Debug, Error)]
+pub enum PhaseError {
+    #[error("the phase did not complete within {0:?}")]
+    Timeout(Duration),
+    #[error(transparent)]
+    NodeError(#[from] NodeError),
+}
+
+/// Runs a single phase of a participant, giving up with `PhaseError::Timeout` if it
+/// doesn't complete within `duration`, e.g. because the board never accepts its bundle
+pub async fn run_with_timeout<C, B, T, P>(
+    phase: P,
+    board: &mut B,
+    arg: T,
+    duration: Duration,
+) -> Result<P::Next, PhaseError>
+where
+    C: Curve,
+    B: BoardPublisher<C>,
+    P: DKGPhase<C, B, T>,
+{
+    match tokio::time::timeout(duration, phase.run(board, arg)).await {
+        Ok(res) => Ok(res?),
+        Err(_) => Err(PhaseError::Timeout(duration)),
+    }
+}
+
+/// Bounds every step of every participant by the same timeout
+struct PhaseTimeoutDriver {
+    timeout: Duration,
+}
+
+impl<B> PhaseDriver<B> for PhaseTimeoutDriver {
+    fn step_timeout(&self, _step: DkgStep) -> Option<Duration> {
+        Some(self.timeout)
+    }
+}
+
+/// Runs the DKG with every phase of every participant bounded by `phase_timeout`. The
+/// participants which time out in a phase are reported together with
+/// `DKGRunError::MissingContributions` once the others are done with it.
+pub async fn run_dkg_with_phase_timeout<C, B, R>(
+    board: &mut B,
+    phase0s: Vec<joint_feldman::DKG<C>>,
+    rng: &mut R,
+    phase_timeout: Duration,
+) -> Result<Vec<DKGOutput<C>>, DKGRunError>
+where
+    C: Curve,
+    B: ReadableBoard<C>,
+    R: RngCore,
+{
+    let mut driver = PhaseTimeoutDriver {
+        timeout: phase_timeout,
+    };
+
+    run_dkg_with_driver(board, phase0s, rng, &mut driver).await
+}
+
+/// Lets the dealers which are leaving the group drop out in Phase 3
+struct ResharingDriver;
+
+#[async_trait(?Send)]
+impl<B> PhaseDriver<B> for ResharingDriver {
+    async fn on_phase_error(
+        &mut self,
+        step: DkgStep,
+        _participant: usize,
+        err: NodeError,
+        _board: &B,
+    ) -> Result<Recovery, DKGRunError> {
+        match err {
+            // leaving dealers always go to phase 3, where they get rejected since
+            // they do not hold a share in the new group
+            NodeError::DKGError(DKGError::NotShareHolder) if step == DkgStep::Phase3 => {
+                Ok(Recovery::Skip)
+            }
+            err => Err(err.into()),
+        }
+    }
+}
+
+/// Runs a resharing of an existing distributed key to a new group. Only the share
+/// holders of the new group get an output, i.e. the dealers which are leaving the
+/// group are not part of the returned vector. Fails with
+/// `DKGRunError::PublicKeyDivergence` unless the new share holders agree on the key.
+pub async fn run_resharing<C, B, R>(
+    board: &mut B,
+    phase0s: Vec<resharing::RDKG<C>>,
+    rng: &mut R,
+) -> Result<Vec<DKGOutput<C>>, DKGRunError>
+where
+    C: Curve,
+    B: ReadableBoard<C>,
+    R: RngCore,
+{
+    // nobody can abort this resharing
+    let (_abort_sender, abort) = watch::channel(false);
+
+    let outputs = run_phases(
+        board,
+        phase0s,
+        rng,
+        &abort,
+        &mut DkgMetrics::default(),
+        &mut ResharingDriver,
+    )
+    .await?;
+
+    // The distributed public key must be the same
+    check_public_keys(&outputs)?;
+
+    Ok(outputs)
+}
+
+#[derive(Debug, Error)]
+pub enum SetupError {
+    #[error("threshold {0} must be between 1 and the number of nodes {1}")]
+    InvalidThreshold(usize, usize),
+    #[error("threshold {0} is not an honest majority of {1} nodes, at least {2} is required")]
+    NoHonestMajority(usize, usize, usize),
+    #[error("node {0} has no weight")]
+    ZeroWeight(usize),
+    #[error(transparent)]
+    DKGError(#[from] DKGError),
+}
+
+/// Creates the keypairs and the Phase 0 of `n` participants. All the randomness of the
+/// DKG is drawn from `rng`, so a seeded RNG reproduces the same keys.
+pub fn setup<C, S, R: RngCore>(
+    n: usize,
+    t: usize,
+    rng: &mut R,
+) -> Result<(InMemoryBoard<C>, Vec<joint_feldman::DKG<C>>), SetupError>
+where
+    C: Curve,
+    // We need to bind the Curve's Point and Scalars to the Scheme
+    S: Scheme<Public = <C as Curve>::Point, Private = <C as Curve>::Scalar>,
+{
+    let (board, phase0s, _) = setup_with_private_keys::<C, S, R>(n, t, rng)?;
+
+    Ok((board, phase0s))
+}
+
+/// Like `setup`, but also returns the private key of every participant, e.g. for them to
+/// prove the possession of their keys to the controller
+pub fn setup_with_private_keys<C, S, R: RngCore>(
+    n: usize,
+    t: usize,
+    rng: &mut R,
+) -> Result<
+    (
+        InMemoryBoard<C>,
+        Vec<joint_feldman::DKG<C>>,
+        Vec<<C as Curve>::Scalar>,
+    ),
+    SetupError,
+>
+where
+    C: Curve,
+    // We need to bind the Curve's Point and Scalars to the Scheme
+    S: Scheme<Public = <C as Curve>::Point, Private = <C as Curve>::Scalar>,
+{
+    if t == 0 || t > n {
+        return Err(SetupError::InvalidThreshold(t, n));
+    }
+
+    if t < n / 2 + 1 {
+        return Err(SetupError::NoHonestMajority(t, n, n / 2 + 1));
+    }
+
+    // generate a keypair per participant
+    let keypairs = (0..n).map(|_| S::keypair(rng)).collect::<Vec<_>>();
+
+    let nodes = keypairs
+        .iter()
+        .enumerate()
+        .map(|(i, (_, public))| Node::<C>::new(i as Idx, public.clone()))
+        .collect::<Vec<_>>();
+
+    // This is setup phase during which publickeys and indexes must be exchanged
+    // across participants
+    let group = Group::new(nodes, t)?;
+
+    // Create the Phase 0 for each participant
+    let phase0s = keypairs
+        .iter()
+        .map(|(private, _)| joint_feldman::DKG::new_rand(private.clone(), group.clone(), rng))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // Create the board
+    let board = InMemoryBoard::<C>::new();
+
+    let private_keys = keypairs
+        .into_iter()
+        .map(|(private, _)| private)
+        .collect::<Vec<_>>();
+
+    Ok((board, phase0s, private_keys))
+}
+
+/// Converts the stakes of the nodes to their weights, one unit of weight per
+/// `stake_per_weight` staked
+pub fn weights_from_stakes(stakes: &[usize], stake_per_weight: usize) -> Vec<usize> {
+    stakes
+        .iter()
+        .map(|stake| stake / stake_per_weight)
+        .collect()
+}
+
+/// Like `setup`, but each node gets as many consecutive indices as its weight, so that
+/// its influence is proportional to its stake. The threshold `t` is counted over the
+/// total weight, i.e. the number of shares.
+pub fn setup_weighted<C, S, R: RngCore>(
+    weights: &[usize],
+    t: usize,
+    rng: &mut R,
+) -> Result<(InMemoryBoard<C>, Vec<joint_feldman::DKG<C>>), SetupError>
+where
+    C: Curve,
+    // We need to bind the Curve's Point and Scalars to the Scheme
+    S: Scheme<Public = <C as Curve>::Point, Private = <C as Curve>::Scalar>,
+{
+    if let Some(node) = weights.iter().position(|weight| *weight == 0) {
+        return Err(SetupError::ZeroWeight(node));
+    }
+
+    // `setup` assigns the indices in order, so the shares of a node are consecutive
+    setup::<C, S, R>(weights.iter().sum(), t, rng)
+}
+
+/// Groups the outputs of a weighted DKG by node, each node holding `weight` shares
+pub fn split_by_weight<C: Curve>(
+    outputs: Vec<DKGOutput<C>>,
+    weights: &[usize],
+) -> Vec<Vec<DKGOutput<C>>> {
+    let mut outputs = outputs.into_iter();
+
+    weights
+        .iter()
+        .map(|weight| outputs.by_ref().take(*weight).collect())
+        .collect()
+}
+
+/// Partially signs the message with every share of a weighted node
+pub fn partial_sign_weighted<C, S>(
+    outputs: &[DKGOutput<C>],
+    msg: &[u8],
+) -> Result<Vec<Vec<u8>>, <S as ThresholdScheme>::Error>
+where
+    C: Curve,
+    S: Scheme<Public = <C as Curve>::Point, Private = <C as Curve>::Scalar> + ThresholdScheme,
+{
+    outputs
+        .iter()
+        .map(|output| S::partial_sign(&output.share, msg))
+        .collect()
+}
+
+/// Creates the Phase 0 of a resharing from the current group to `new_group`.
+///
+/// `dealers` are the private keys along with the DKG outputs of the current share
+/// holders, and `new_members` are the private keys of the nodes joining without any
+/// share. Members staying in the group must keep their index in `new_group`.
+pub fn setup_resharing<C: Curve, R: RngCore>(
+    dealers: Vec<(C::Scalar, DKGOutput<C>)>,
+    new_members: Vec<C::Scalar>,
+    new_group: Group<C>,
+    rng: &mut R,
+) -> (InMemoryBoard<C>, Vec<resharing::RDKG<C>>) {
+    // every dealer agrees on the current group and its public polynomial
+    let curr_group = dealers[0].1.qual.clone();
+    let curr_public = dealers[0].1.public.clone();
+
+    let mut phase0s = dealers
+        .into_iter()
+        .map(|(private, output)| {
+            resharing::RDKG::new_from_share_rng(private, output, new_group.clone(), rng).unwrap()
+        })
+        .collect::<Vec<_>>();
+
+    phase0s.extend(new_members.into_iter().map(|private| {
+        resharing::RDKG::new_member(
+            private,
+            curr_group.clone(),
+            curr_public.clone(),
+            new_group.clone(),
+        )
+        .unwrap()
+    }));
+
+    // Create the board
+    let board = InMemoryBoard::<C>::new();
+
+    (board, phase0s)
+}
+
+pub fn is_all_same<T: PartialEq>(mut arr: impl Iterator<Item = T>) -> bool {
+    let first = arr.next().unwrap();
+    arr.all(|item| item == first)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_helpers::{ByzantineBoard, ByzantinePolicy};
+    use async_trait::async_trait;
+    use dkg_core::{
+        board::BoardPublisher,
+        primitives::types::{BundledJustification, BundledResponses, BundledShares},
+    };
+    use rand::SeedableRng;
+    use rand_chacha::ChaChaRng;
+    use std::cell::Cell;
+    use std::collections::HashMap;
+    use threshold_bls::{
+        curve::bls12381::{self, PairingCurve as BLS12_381},
+        group::Element,
+        sig::{G1Scheme, SignatureScheme, ThresholdScheme},
+    };
+
+    type C = bls12381::Curve;
+    type S = G1Scheme<BLS12_381>;
+
+    fn group_of(publics: &[<C as Curve>::Point], t: usize) -> Group<C> {
+        let nodes = publics
+            .iter()
+            .enumerate()
+            .map(|(i, public)| Node::<C>::new(i as Idx, *public))
+            .collect::<Vec<_>>();
+
+        Group::new(nodes, t).unwrap()
+    }
+
+    #[test]
+    fn setup_rejects_zero_threshold() {
+        let rng = &mut rand::thread_rng();
+
+        let res = setup::<C, S, _>(5, 0, rng);
+
+        assert!(matches!(res, Err(SetupError::InvalidThreshold(0, 5))));
+    }
+
+    #[test]
+    fn setup_rejects_threshold_above_group_size() {
+        let rng = &mut rand::thread_rng();
+
+        let res = setup::<C, S, _>(5, 6, rng);
+
+        assert!(matches!(res, Err(SetupError::InvalidThreshold(6, 5))));
+    }
+
+    #[test]
+    fn setup_accepts_honest_majority() {
+        let rng = &mut rand::thread_rng();
+
+        let (_, phase0s) = setup::<C, S, _>(5, 3, rng).unwrap();
+
+        assert_eq!(phase0s.len(), 5);
+
+        let res = setup::<C, S, _>(5, 2, rng);
+
+        assert!(matches!(res, Err(SetupError::NoHonestMajority(2, 5, 3))));
+    }
+
+    #[tokio::test]
+    async fn invalid_share_goes_to_phase3() {
+        let rng = &mut rand::thread_rng();
+        let (t, n) = (3, 5);
+
+        let keypairs = (0..n).map(|_| S::keypair(rng)).collect::<Vec<_>>();
+        let publics = keypairs.iter().map(|k| k.1).collect::<Vec<_>>();
+        let group = group_of(&publics, t);
+
+        // the first dealer encrypts the share of the second node to a wrong key,
+        // so that the second node fails to decrypt it and complains
+        let mut wrong_publics = publics.clone();
+        wrong_publics[1] = S::keypair(rng).1;
+        let wrong_group = group_of(&wrong_publics, t);
+
+        let phase0s = keypairs
+            .iter()
+            .enumerate()
+            .map(|(i, (private, _))| {
+                let group = if i == 0 { &wrong_group } else { &group };
+                joint_feldman::DKG::new(*private, group.clone()).unwrap()
+            })
+            .collect::<Vec<_>>();
+
+        let mut board = InMemoryBoard::<C>::new();
+        let outputs = run_dkg::<C, S, _, _>(&mut board, phase0s, rng)
+            .await
+            .unwrap();
+
+        // a single complaint was filed and the dealer justified its share
+        assert_eq!(board.responses.len(), 1);
+        assert_eq!(board.justifs.len(), 1);
+        assert_eq!(board.justifs[0].dealer_idx, 0);
+        assert_eq!(outputs.len(), n);
+
+        // the justified share is part of the distributed key
+        let msg = b"phase3";
+        let partials = outputs
+            .iter()
+            .map(|output| S::partial_sign(&output.share, &msg[..]).unwrap())
+            .collect::<Vec<_>>();
+        let sig = S::aggregate(t, &partials).unwrap();
+        S::verify(outputs[1].public.public_key(), &msg[..], &sig).unwrap();
+    }
+
+    /// Records the hooks it gets called with, and stops the DKG before `abort_before`
+    struct RecordingDriver {
+        calls: Vec<(&'static str, DkgStep, usize)>,
+        abort_before: Option<DkgStep>,
+    }
+
+    impl PhaseDriver<InMemoryBoard<C>> for RecordingDriver {
+        fn before_phase(
+            &mut self,
+            step: DkgStep,
+            board: &InMemoryBoard<C>,
+        ) -> Result<(), DKGRunError> {
+            if self.abort_before == Some(step) {
+                return Err(DKGRunError::Aborted);
+            }
+            self.calls.push(("before", step, board.shares().len()));
+            Ok(())
+        }
+
+        fn after_phase(
+            &mut self,
+            step: DkgStep,
+            board: &InMemoryBoard<C>,
+        ) -> Result<(), DKGRunError> {
+            self.calls.push(("after", step, board.shares().len()));
+            Ok(())
+        }
+
+        fn on_phase_result(
+            &mut self,
+            step: DkgStep,
+            participant: usize,
+            _board: &InMemoryBoard<C>,
+        ) -> Result<(), DKGRunError> {
+            self.calls.push(("result", step, participant));
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn driver_observes_phase_sequence() {
+        let rng = &mut rand::thread_rng();
+        let (t, n) = (3, 4);
+
+        let (mut board, phase0s) = setup::<C, S, _>(n, t, rng).unwrap();
+        let mut driver = RecordingDriver {
+            calls: vec![],
+            abort_before: None,
+        };
+        let outputs = run_dkg_with_driver(&mut board, phase0s, rng, &mut driver)
+            .await
+            .unwrap();
+        assert_eq!(outputs.len(), n);
+
+        // no complaint was filed, so Phase 3 is skipped
+        let mut expected = vec![];
+        for (step, shares_before) in [
+            (DkgStep::Shares, 0),
+            (DkgStep::Responses, n),
+            (DkgStep::Justifications, n),
+        ]
+        .iter()
+        {
+            expected.push(("before", *step, *shares_before));
+            expected.extend((0..n).map(|i| ("result", *step, i)));
+            expected.push(("after", *step, n));
+        }
+        assert_eq!(driver.calls, expected);
+
+        // the driver stops the DKG once the shares are published
+        let (mut board, phase0s) = setup::<C, S, _>(n, t, rng).unwrap();
+        let mut driver = RecordingDriver {
+            calls: vec![],
+            abort_before: Some(DkgStep::Responses),
+        };
+        assert!(matches!(
+            run_dkg_with_driver(&mut board, phase0s, rng, &mut driver).await,
+            Err(DKGRunError::Aborted)
+        ));
+        assert_eq!(board.shares().len(), n);
+        assert!(board.responses().is_empty());
+        assert_eq!(driver.calls.last(), Some(&("after", DkgStep::Shares, n)));
+    }
+
+    async fn run_byzantine_dkg(policy: ByzantinePolicy) {
+        let rng = &mut rand::thread_rng();
+        let (t, n) = (3, 5);
+
+        let keypairs = (0..n).map(|_| S::keypair(rng)).collect::<Vec<_>>();
+        let publics = keypairs.iter().map(|k| k.1).collect::<Vec<_>>();
+        let group = group_of(&publics, t);
+
+        let phase0s = keypairs
+            .iter()
+            .map(|(private, _)| joint_feldman::DKG::new(*private, group.clone()).unwrap())
+            .collect::<Vec<_>>();
+
+        // the first node is malicious
+        let mut board = ByzantineBoard::<C>::new(vec![0], policy);
+        let (_abort_sender, abort) = watch::channel(false);
+        let outputs = run_dkg_phases(&mut board, phase0s, rng, &abort)
+            .await
+            .unwrap();
+
+        // the honest nodes complained about the malicious dealer
+        assert_eq!(board.board.responses.len(), n - 1);
+
+        // everybody was shown the same bundles
+        assert!(find_equivocations(&board, &(0..n as Idx).collect::<Vec<_>>()).is_empty());
+
+        let honest_outputs = outputs
+            .into_iter()
+            .filter(|output| output.share.index != 0)
+            .collect::<Vec<_>>();
+
+        assert!(is_all_same(
+            honest_outputs.iter().map(|output| &output.public)
+        ));
+
+        // only a correct share can be justified
+        let qualified = policy == ByzantinePolicy::CorruptShare;
+        assert_eq!(honest_outputs[0].qual.contains_index(0), qualified);
+
+        // the honest shares produce signatures which verify against the distributed key
+        let msg = b"byzantine";
+        let partials = honest_outputs
+            .iter()
+            .map(|output| S::partial_sign(&output.share, &msg[..]).unwrap())
+            .collect::<Vec<_>>();
+        let sig = S::aggregate(t, &partials).unwrap();
+        S::verify(honest_outputs[0].public.public_key(), &msg[..], &sig).unwrap();
+    }
+
+    #[tokio::test]
+    async fn byzantine_dealer_drops_shares() {
+        run_byzantine_dkg(ByzantinePolicy::DropShare).await;
+    }
+
+    #[tokio::test]
+    async fn byzantine_dealer_corrupts_shares() {
+        run_byzantine_dkg(ByzantinePolicy::CorruptShare).await;
+    }
+
+    #[tokio::test]
+    async fn byzantine_dealer_commits_wrong_polynomial() {
+        run_byzantine_dkg(ByzantinePolicy::WrongCommitment).await;
+    }
+
+    #[tokio::test]
+    async fn equivocating_dealer_is_disqualified_before_phase3() {
+        let rng = &mut rand::thread_rng();
+        let (t, n) = (3, 5);
+
+        let keypairs = (0..n).map(|_| S::keypair(rng)).collect::<Vec<_>>();
+        let publics = keypairs.iter().map(|k| k.1).collect::<Vec<_>>();
+        let group = group_of(&publics, t);
+
+        let phase0s = keypairs
+            .iter()
+            .map(|(private, _)| joint_feldman::DKG::new(*private, group.clone()).unwrap())
+            .collect::<Vec<_>>();
+
+        // the first node shows another commitment to the nodes 1 and 3
+        let mut board = ByzantineBoard::<C>::new(vec![0], ByzantinePolicy::Equivocate);
+        let (_abort_sender, abort) = watch::channel(false);
+        let outputs = run_dkg_phases(&mut board, phase0s, rng, &abort)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            find_equivocations(&board, &(0..n as Idx).collect::<Vec<_>>()),
+            vec![
+                Equivocation {
+                    dealer_idx: 0,
+                    share_idx: 1
+                },
+                Equivocation {
+                    dealer_idx: 0,
+                    share_idx: 3
+                },
+            ]
+        );
+
+        // every honest node complained, not only those who were shown the forged commitment
+        let complaints = board.complaints();
+        assert_eq!(complaints.len(), n - 1);
+        assert!(complaints.iter().all(|bundle| bundle
+            .responses
+            .iter()
+            .all(|response| response.dealer_idx == 0)));
+
+        let honest_outputs = outputs
+            .into_iter()
+            .filter(|output| output.share.index != 0)
+            .collect::<Vec<_>>();
+
+        assert!(is_all_same(
+            honest_outputs.iter().map(|output| &output.public)
+        ));
+
+        // the justifications of the dealer were ignored
+        assert!(!honest_outputs[0].qual.contains_index(0));
+        assert_eq!(honest_outputs[0].qual.len(), n - 1);
+
+        let msg = b"equivocation";
+        let partials = honest_outputs
+            .iter()
+            .map(|output| S::partial_sign(&output.share, &msg[..]).unwrap())
+            .collect::<Vec<_>>();
+        let sig = S::aggregate(t, &partials).unwrap();
+        S::verify(honest_outputs[0].public.public_key(), &msg[..], &sig).unwrap();
+    }
+
+    #[tokio::test]
+    async fn only_the_recipient_decrypts_its_share() {
+        let rng = &mut rand::thread_rng();
+        let (t, n) = (3, 5);
+
+        let keypairs = (0..n).map(|_| S::keypair(rng)).collect::<Vec<_>>();
+        let publics = keypairs.iter().map(|k| k.1).collect::<Vec<_>>();
+        let group = group_of(&publics, t);
+
+        let phase0s = keypairs
+            .iter()
+            .map(|(private, _)| joint_feldman::DKG::new(*private, group.clone()).unwrap())
+            .collect::<Vec<_>>();
+
+        let mut board = InMemoryBoard::<C>::new();
+        run_dkg::<C, S, _, _>(&mut board, phase0s, rng)
+            .await
+            .unwrap();
+
+        for bundle in board.shares() {
+            for (i, (private, _)) in keypairs.iter().enumerate() {
+                let share_idx = i as Idx;
+
+                // the decrypted share matches the commitment of the dealer
+                let share = board
+                    .decrypt_share(bundle.dealer_idx, share_idx, private)
+                    .unwrap();
+                let mut public_share = <C as Curve>::Point::one();
+                public_share.mul(&share);
+                assert_eq!(bundle.public.eval(share_idx).value, public_share);
+
+                // the key of another participant can't open the ciphertext
+                let (other_private, _) = &keypairs[(i + 1) % n];
+                assert!(board
+                    .decrypt_share(bundle.dealer_idx, share_idx, other_private)
+                    .is_none());
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn board_rolls_back_to_snapshot() {
+        let rng = &mut rand::thread_rng();
+        let (t, n) = (3, 5);
+
+        let contents = |board: &InMemoryBoard<C>| {
+            bincode::serialize(&(board.shares(), board.responses(), board.justifications()))
+                .unwrap()
+        };
+
+        let (mut board, phase0s) = setup::<C, S, _>(n, t, rng).unwrap();
+        run_dkg::<C, S, _, _>(&mut board, phase0s, rng)
+            .await
+            .unwrap();
+        let snapshot = board.snapshot();
+        let first = contents(&board);
+
+        // a second DKG runs on the same board once it is reset
+        board.reset();
+        assert!(board.shares().is_empty());
+        assert!(board.responses().is_empty());
+        assert!(board.justifications().is_empty());
+
+        let (_, phase0s) = setup::<C, S, _>(n, t, rng).unwrap();
+        run_dkg::<C, S, _, _>(&mut board, phase0s, rng)
+            .await
+            .unwrap();
+        assert_eq!(board.shares().len(), n);
+        assert_ne!(contents(&board), first);
+
+        board.restore(snapshot);
+        assert_eq!(contents(&board), first);
+    }
+
+    #[tokio::test]
+    async fn diverging_public_keys_are_reported() {
+        let rng = &mut rand::thread_rng();
+        let (t, n) = (3, 5);
+
+        let keypairs = (0..n).map(|_| S::keypair(rng)).collect::<Vec<_>>();
+        let publics = keypairs.iter().map(|k| k.1).collect::<Vec<_>>();
+        let group = group_of(&publics, t);
+
+        let phase0s = keypairs
+            .iter()
+            .map(|(private, _)| joint_feldman::DKG::new(*private, group.clone()).unwrap())
+            .collect::<Vec<_>>();
+
+        // the others disqualify the first node, which still counts its own polynomial
+        let mut board = ByzantineBoard::<C>::new(vec![0], ByzantinePolicy::DropShare);
+        let public_keys = match run_dkg::<C, S, _, _>(&mut board, phase0s, rng).await {
+            Err(DKGRunError::PublicKeyDivergence(public_keys)) => public_keys,
+            res => panic!("unexpected result: {:?}", res.map(|outputs| outputs.len())),
+        };
+
+        // every node is listed, only the first one disagrees
+        assert_eq!(
+            public_keys
+                .iter()
+                .map(|(index, _)| *index)
+                .collect::<Vec<_>>(),
+            (0..n as Idx).collect::<Vec<_>>()
+        );
+        assert!(is_all_same(public_keys[1..].iter().map(|(_, key)| key)));
+        assert_ne!(public_keys[0].1, public_keys[1].1);
+
+        let message = DKGRunError::PublicKeyDivergence(public_keys.clone()).to_string();
+        for (index, public_key) in public_keys.iter() {
+            assert!(message.contains(&format!("node {}: {}", index, public_key)));
+        }
+    }
+
+    #[tokio::test]
+    async fn defrauded_node_complains_and_dealer_is_disqualified() {
+        let rng = &mut rand::thread_rng();
+        let (t, n) = (3, 5);
+
+        let keypairs = (0..n).map(|_| S::keypair(rng)).collect::<Vec<_>>();
+        let publics = keypairs.iter().map(|k| k.1).collect::<Vec<_>>();
+        let group = group_of(&publics, t);
+
+        let phase0s = keypairs
+            .iter()
+            .map(|(private, _)| joint_feldman::DKG::new(*private, group.clone()).unwrap())
+            .collect::<Vec<_>>();
+
+        // the first node defrauds the third one and never justifies its share
+        let mut board = ByzantineBoard::<C>::new(vec![0], ByzantinePolicy::DefraudShare(2));
+        let (_abort_sender, abort) = watch::channel(false);
+        let outputs = run_dkg_phases(&mut board, phase0s, rng, &abort)
+            .await
+            .unwrap();
+
+        // only the defrauded node complained, and only about the malicious dealer
+        let complaints = board.complaints();
+        assert_eq!(complaints.len(), 1);
+        assert_eq!(complaints[0].share_idx, 2);
+        assert_eq!(complaints[0].responses.len(), 1);
+        assert_eq!(complaints[0].responses[0].dealer_idx, 0);
+        assert!(board.justifications().is_empty());
+
+        let honest_outputs = outputs
+            .into_iter()
+            .filter(|output| output.share.index != 0)
+            .collect::<Vec<_>>();
+
+        assert!(is_all_same(
+            honest_outputs.iter().map(|output| &output.public)
+        ));
+
+        // the dealer could not justify the share, so it got disqualified
+        assert!(!honest_outputs[0].qual.contains_index(0));
+        assert!(honest_outputs[0].qual.contains_index(2));
+    }
+
+    #[tokio::test]
+    async fn dryrun_reports_the_misbehaving_dealer() {
+        let rng = &mut rand::thread_rng();
+        let (t, n) = (3, 5);
+
+        let (mut board, phase0s) = setup::<C, S, _>(n, t, rng).unwrap();
+        let reports = run_dkg_dryrun(&mut board, phase0s, rng).await.unwrap();
+
+        // an honest DKG has no complaints, and nothing was finalized
+        assert_eq!(reports.len(), n);
+        assert!(reports.iter().all(|report| report.is_consistent()));
+        assert!(board.justifications().is_empty());
+
+        let keypairs = (0..n).map(|_| S::keypair(rng)).collect::<Vec<_>>();
+        let publics = keypairs.iter().map(|k| k.1).collect::<Vec<_>>();
+        let group = group_of(&publics, t);
+
+        let phase0s = keypairs
+            .iter()
+            .map(|(private, _)| joint_feldman::DKG::new(*private, group.clone()).unwrap())
+            .collect::<Vec<_>>();
+
+        // the first node corrupts the shares it deals
+        let mut board = ByzantineBoard::<C>::new(vec![0], ByzantinePolicy::CorruptShare);
+        let reports = run_dkg_dryrun(&mut board, phase0s, rng).await.unwrap();
+
+        for report in reports {
+            if report.index == 0 {
+                assert!(report.is_consistent());
+            } else {
+                assert_eq!(report.complaints, vec![0]);
+            }
+        }
+
+        assert!(board.justifications().is_empty());
+    }
+
+    /// A board whose shares can only be read from the `ready_at`-th poll on
+    struct LaggingBoard {
+        board: InMemoryBoard<C>,
+        polls: Cell<usize>,
+        ready_at: usize,
+    }
+
+    #[async_trait(?Send)]
+    impl BoardPublisher<C> for LaggingBoard {
+        type Error = ();
+
+        async fn publish_shares(&mut self, bundle: BundledShares<C>) -> Result<(), Self::Error> {
+            self.board.publish_shares(bundle).await
+        }
+
+        async fn publish_responses(&mut self, bundle: BundledResponses) -> Result<(), Self::Error> {
+            self.board.publish_responses(bundle).await
+        }
+
+        async fn publish_justifications(
+            &mut self,
+            bundle: BundledJustification<C>,
+        ) -> Result<(), Self::Error> {
+            self.board.publish_justifications(bundle).await
+        }
+    }
+
+    impl ReadableBoard<C> for LaggingBoard {
+        fn shares(&self) -> Vec<BundledShares<C>> {
+            self.polls.set(self.polls.get() + 1);
+
+            if self.polls.get() < self.ready_at {
+                return vec![];
+            }
+
+            self.board.shares()
+        }
+
+        fn responses(&self) -> Vec<BundledResponses> {
+            self.board.responses()
+        }
+
+        fn justifications(&self) -> Vec<BundledJustification<C>> {
+            self.board.justifications()
+        }
+
+        // only the published shares are counted, not the ones checked for equivocations
+        fn shares_seen_by(&self, share_idx: Idx) -> Vec<BundledShares<C>> {
+            self.board.shares_seen_by(share_idx)
+        }
+    }
+
+    #[tokio::test]
+    async fn phase_succeeds_after_retries() {
+        let rng = &mut rand::thread_rng();
+        let (t, n) = (3, 5);
+
+        let (board, phase0s) = setup::<C, S, _>(n, t, rng).unwrap();
+        let mut board = LaggingBoard {
+            board,
+            polls: Cell::new(0),
+            ready_at: 3,
+        };
+
+        let retry = RetryConfig {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(1),
+        };
+
+        let outputs = run_dkg_with_retry(&mut board, phase0s, rng, retry)
+            .await
+            .unwrap();
+
+        assert_eq!(outputs.len(), n);
+        assert!(is_all_same(outputs.iter().map(|output| &output.public)));
+
+        // the board was only read again while the first node retried
+        assert_eq!(board.polls.get(), 3);
+    }
+
+    #[tokio::test]
+    async fn phase_fails_once_attempts_are_used_up() {
+        let rng = &mut rand::thread_rng();
+        let (t, n) = (3, 5);
+
+        let (board, phase0s) = setup::<C, S, _>(n, t, rng).unwrap();
+        let mut board = LaggingBoard {
+            board,
+            polls: Cell::new(0),
+            ready_at: 3,
+        };
+
+        let retry = RetryConfig {
+            max_attempts: 2,
+            initial_backoff: Duration::from_millis(1),
+        };
+
+        let res = run_dkg_with_retry(&mut board, phase0s, rng, retry).await;
+
+        assert!(matches!(
+            res,
+            Err(DKGRunError::Node(NodeError::DKGError(
+                DKGError::NotEnoughValidShares(0, 3)
+            )))
+        ));
+        assert_eq!(board.polls.get(), 2);
+    }
+
+    #[tokio::test]
+    async fn phase_errors_are_returned() {
+        let rng = &mut rand::thread_rng();
+        let (t, n) = (3, 5);
+
+        let (board, phase0s) = setup::<C, S, _>(n, t, rng).unwrap();
+        let mut board = LaggingBoard {
+            board,
+            polls: Cell::new(0),
+            ready_at: usize::MAX,
+        };
+
+        let (_abort_sender, abort) = watch::channel(false);
+        let res = run_dkg_phases(&mut board, phase0s, rng, &abort).await;
+
+        assert!(matches!(
+            res,
+            Err(DKGRunError::Node(NodeError::DKGError(
+                DKGError::NotEnoughValidShares(0, 3)
+            )))
+        ));
+    }
+
+    /// A board which never accepts the shares of the `withheld` dealer
+    struct WithholdingBoard {
+        board: InMemoryBoard<C>,
+        withheld: Idx,
+    }
+
+    #[async_trait(?Send)]
+    impl BoardPublisher<C> for WithholdingBoard {
+        type Error = ();
+
+        async fn publish_shares(&mut self, bundle: BundledShares<C>) -> Result<(), Self::Error> {
+            if bundle.dealer_idx == self.withheld {
+                futures::future::pending::<()>().await;
+            }
+
+            self.board.publish_shares(bundle).await
+        }
+
+        async fn publish_responses(&mut self, bundle: BundledResponses) -> Result<(), Self::Error> {
+            self.board.publish_responses(bundle).await
+        }
+
+        async fn publish_justifications(
+            &mut self,
+            bundle: BundledJustification<C>,
+        ) -> Result<(), Self::Error> {
+            self.board.publish_justifications(bundle).await
+        }
+    }
+
+    impl ReadableBoard<C> for WithholdingBoard {
+        fn shares(&self) -> Vec<BundledShares<C>> {
+            self.board.shares()
+        }
+
+        fn responses(&self) -> Vec<BundledResponses> {
+            self.board.responses()
+        }
+
+        fn justifications(&self) -> Vec<BundledJustification<C>> {
+            self.board.justifications()
+        }
+    }
+
+    #[tokio::test]
+    async fn phase_timeout_names_missing_node() {
+        let rng = &mut rand::thread_rng();
+        let (t, n) = (3, 5);
+
+        let (board, phase0s) = setup::<C, S, _>(n, t, rng).unwrap();
+        let mut board = WithholdingBoard { board, withheld: 2 };
+
+        let res =
+            run_dkg_with_phase_timeout(&mut board, phase0s, rng, Duration::from_millis(50)).await;
+
+        match res {
+            Err(DKGRunError::MissingContributions(step, missing)) => {
+                assert_eq!(step, DkgStep::Shares);
+                assert_eq!(missing, vec![2]);
+            }
+            _ => panic!("the DKG did not time out"),
+        }
+
+        // the other dealers did publish their shares
+        assert_eq!(board.shares().len(), n - 1);
+    }
+
+    #[tokio::test]
+    async fn phase_timeout_lets_a_complete_dkg_finish() {
+        let rng = &mut rand::thread_rng();
+        let (t, n) = (3, 5);
+
+        let (mut board, phase0s) = setup::<C, S, _>(n, t, rng).unwrap();
+
+        let outputs = run_dkg_with_phase_timeout(&mut board, phase0s, rng, Duration::from_secs(5))
+            .await
+            .unwrap();
+
+        assert_eq!(outputs.len(), n);
+        assert!(is_all_same(outputs.iter().map(|output| &output.public)));
+    }
+
+    /// A board which raises the abort signal once the first share is published
+    struct AbortingBoard {
+        board: InMemoryBoard<C>,
+        abort: watch::Sender<bool>,
+    }
+
+    #[async_trait(?Send)]
+    impl BoardPublisher<C> for AbortingBoard {
+        type Error = ();
+
+        async fn publish_shares(&mut self, bundle: BundledShares<C>) -> Result<(), Self::Error> {
+            self.abort.broadcast(true).unwrap();
+            self.board.publish_shares(bundle).await
+        }
+
+        async fn publish_responses(&mut self, bundle: BundledResponses) -> Result<(), Self::Error> {
+            self.board.publish_responses(bundle).await
+        }
+
+        async fn publish_justifications(
+            &mut self,
+            bundle: BundledJustification<C>,
+        ) -> Result<(), Self::Error> {
+            self.board.publish_justifications(bundle).await
+        }
+    }
+
+    impl ReadableBoard<C> for AbortingBoard {
+        fn shares(&self) -> Vec<BundledShares<C>> {
+            self.board.shares()
+        }
+
+        fn responses(&self) -> Vec<BundledResponses> {
+            self.board.responses()
+        }
+
+        fn justifications(&self) -> Vec<BundledJustification<C>> {
+            self.board.justifications()
+        }
+    }
+
+    #[tokio::test]
+    async fn abort_during_phase1() {
+        let rng = &mut rand::thread_rng();
+        let (t, n) = (3, 5);
+
+        let (board, phase0s) = setup::<C, S, _>(n, t, rng).unwrap();
+
+        let (abort_sender, abort) = watch::channel(false);
+        let mut board = AbortingBoard {
+            board,
+            abort: abort_sender,
+        };
+
+        let res = run_dkg_phases(&mut board, phase0s, rng, &abort).await;
+
+        assert!(matches!(res, Err(DKGRunError::Aborted)));
+
+        // the DKG stopped right after the first node published its shares
+        assert_eq!(board.board.shares.len(), 1);
+        assert!(board.board.responses.is_empty());
+    }
+
+    #[tokio::test]
+    async fn late_shares_are_ignored_after_timeout() {
+        let rng = &mut rand::thread_rng();
+        let (t, n) = (3, 5);
+
+        let keypairs = (0..n).map(|_| S::keypair(rng)).collect::<Vec<_>>();
+        let publics = keypairs.iter().map(|k| k.1).collect::<Vec<_>>();
+        let group = group_of(&publics, t);
+
+        let phase0s = keypairs
+            .iter()
+            .map(|(private, _)| joint_feldman::DKG::new(*private, group.clone()).unwrap())
+            .collect::<Vec<_>>();
+
+        // the shares of the last node arrive after the phase timeout while the
+        // other nodes have slightly different delays
+        let mut delays = (0..n as Idx - 1)
+            .map(|i| (i, Duration::from_millis(5 * i as u64)))
+            .collect::<HashMap<_, _>>();
+        delays.insert(n as Idx - 1, Duration::from_secs(5));
+
+        let mut board = AsyncDelayBoard::<C>::new(delays);
+        let outputs = run_dkg_with_timeout(&mut board, phase0s, rng, Duration::from_millis(100))
+            .await
+            .unwrap();
+
+        let on_time_outputs = outputs
+            .into_iter()
+            .filter(|output| output.share.index != n as Idx - 1)
+            .collect::<Vec<_>>();
+
+        assert!(is_all_same(
+            on_time_outputs.iter().map(|output| &output.public)
+        ));
+
+        // the late node is disqualified, but enough shares were delivered to complete
+        assert!(!on_time_outputs[0].qual.contains_index(n as Idx - 1));
+        assert_eq!(on_time_outputs[0].qual.len(), n - 1);
+
+        let msg = b"late";
+        let partials = on_time_outputs
+            .iter()
+            .map(|output| S::partial_sign(&output.share, &msg[..]).unwrap())
+            .collect::<Vec<_>>();
+        let sig = S::aggregate(t, &partials).unwrap();
+        S::verify(on_time_outputs[0].public.public_key(), &msg[..], &sig).unwrap();
+    }
+
+    /// A board which counts the bundles published to it
+    struct CountingBoard {
+        board: InMemoryBoard<C>,
+        published: usize,
+    }
+
+    #[async_trait(?Send)]
+    impl BoardPublisher<C> for CountingBoard {
+        type Error = ();
+
+        async fn publish_shares(&mut self, bundle: BundledShares<C>) -> Result<(), Self::Error> {
+            self.published += 1;
+            self.board.publish_shares(bundle).await
+        }
+
+        async fn publish_responses(&mut self, bundle: BundledResponses) -> Result<(), Self::Error> {
+            self.published += 1;
+            self.board.publish_responses(bundle).await
+        }
+
+        async fn publish_justifications(
+            &mut self,
+            bundle: BundledJustification<C>,
+        ) -> Result<(), Self::Error> {
+            self.published += 1;
+            self.board.publish_justifications(bundle).await
+        }
+    }
+
+    impl ReadableBoard<C> for CountingBoard {
+        fn shares(&self) -> Vec<BundledShares<C>> {
+            self.board.shares()
+        }
+
+        fn responses(&self) -> Vec<BundledResponses> {
+            self.board.responses()
+        }
+
+        fn justifications(&self) -> Vec<BundledJustification<C>> {
+            self.board.justifications()
+        }
+    }
+
+    #[tokio::test]
+    async fn resharing_keeps_public_key() {
+        let rng = &mut rand::thread_rng();
+        let (t, n) = (3, 5);
+
+        let keypairs = (0..n).map(|_| S::keypair(rng)).collect::<Vec<_>>();
+        let publics = keypairs.iter().map(|k| k.1).collect::<Vec<_>>();
+        let group = group_of(&publics, t);
+
+        let mut board = InMemoryBoard::<C>::new();
+        let phase0s = keypairs
+            .iter()
+            .map(|(private, _)| joint_feldman::DKG::new(*private, group.clone()).unwrap())
+            .collect::<Vec<_>>();
+        let outputs = run_dkg::<C, S, _, _>(&mut board, phase0s, rng)
+            .await
+            .unwrap();
+        let pubkey = bincode::serialize(outputs[0].public.public_key()).unwrap();
+
+        // the last node leaves and two new nodes that have no share join
+        let joining = (0..2).map(|_| S::keypair(rng)).collect::<Vec<_>>();
+        let new_publics = publics[..n - 1]
+            .iter()
+            .chain(joining.iter().map(|k| &k.1))
+            .cloned()
+            .collect::<Vec<_>>();
+        let (new_t, new_n) = (4, new_publics.len());
+        let new_group = group_of(&new_publics, new_t);
+
+        let dealers = keypairs
+            .iter()
+            .map(|k| k.0)
+            .zip(outputs.into_iter())
+            .collect::<Vec<_>>();
+        let new_members = joining.iter().map(|k| k.0).collect::<Vec<_>>();
+
+        let (board, phase0s) = setup_resharing(dealers, new_members, new_group, rng);
+        let mut board = CountingBoard {
+            board,
+            published: 0,
+        };
+        let reshared = run_resharing(&mut board, phase0s, rng).await.unwrap();
+
+        // every current share holder dealt its share
+        assert_eq!(board.board.shares.len(), n);
+        assert!(board.published >= n);
+
+        // the leaving node does not get a share while the new ones do
+        assert_eq!(reshared.len(), new_n);
+        assert_eq!(
+            pubkey,
+            bincode::serialize(reshared[0].public.public_key()).unwrap()
+        );
+
+        // the new shares produce signatures which verify against the old key
+        let msg = b"resharing";
+        let partials = reshared
+            .iter()
+            .map(|output| S::partial_sign(&output.share, &msg[..]).unwrap())
+            .collect::<Vec<_>>();
+        let sig = S::aggregate(new_t, &partials).unwrap();
+        let old_key = bincode::deserialize(&pubkey).unwrap();
+        S::verify(&old_key, &msg[..], &sig).unwrap();
+    }
+
+    #[tokio::test]
+    async fn persisted_output_signs_identically() {
+        let rng = &mut rand::thread_rng();
+        let (t, n) = (3, 5);
+
+        let (mut board, phase0s) = setup::<C, S, _>(n, t, rng).unwrap();
+        let outputs = run_dkg::<C, S, _, _>(&mut board, phase0s, rng)
+            .await
+            .unwrap();
+
+        let bytes = outputs[0].to_bytes().unwrap();
+        let restored = DKGOutput::<C>::from_bytes(&bytes).unwrap();
+
+        let msg = b"persistence";
+        assert_eq!(
+            S::partial_sign(&outputs[0].share, &msg[..]).unwrap(),
+            S::partial_sign(&restored.share, &msg[..]).unwrap()
+        );
+        assert_eq!(outputs[0].public, restored.public);
+    }
+
+    #[tokio::test]
+    async fn run_dkg_reports_metrics() {
+        let rng = &mut rand::thread_rng();
+        let (t, n) = (3, 5);
+
+        let (mut board, phase0s) = setup::<C, S, _>(n, t, rng).unwrap();
+        let (outputs, metrics) = run_dkg_with_metrics::<C, S, _, _>(&mut board, phase0s, rng)
+            .await
+            .unwrap();
+
+        assert_eq!(outputs.len(), n);
+        assert_eq!(metrics.phase_durations.len(), 3);
+        assert_eq!(metrics.shares_published, n);
+        assert_eq!(metrics.responses_published, 0);
+        assert_eq!(metrics.complaints, 0);
+        assert_eq!(metrics.justifications_published, 0);
+        assert!(!metrics.entered_phase3);
+
+        let shares_size: u64 = board
+            .shares
+            .iter()
+            .map(|bundle| bincode::serialized_size(bundle).unwrap())
+            .sum();
+        assert_eq!(metrics.bytes_exchanged, shares_size);
+    }
+
+    #[tokio::test]
+    async fn weighted_shares_count_towards_threshold() {
+        let rng = &mut rand::thread_rng();
+        let weights = weights_from_stakes(&[1000, 2500, 2000], 1000);
+        assert_eq!(weights, vec![1, 2, 2]);
+        let t = 3;
+
+        let (mut board, phase0s) = setup_weighted::<C, S, _>(&weights, t, rng).unwrap();
+        let outputs = run_dkg::<C, S, _, _>(&mut board, phase0s, rng)
+            .await
+            .unwrap();
+        let pubkey = *outputs[0].public.public_key();
+
+        let nodes = split_by_weight(outputs, &weights);
+        let indices = nodes
+            .iter()
+            .map(|shares| shares.iter().map(|o| o.share.index).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        assert_eq!(indices, vec![vec![0], vec![1, 2], vec![3, 4]]);
+
+        let msg = b"weighted";
+        let partials = nodes
+            .iter()
+            .map(|shares| partial_sign_weighted::<C, S>(shares, &msg[..]).unwrap())
+            .collect::<Vec<_>>();
+
+        // the light node and a heavy node together hold 3 shares
+        let sig = S::aggregate(t, &[&partials[0][..], &partials[1][..]].concat()).unwrap();
+        S::verify(&pubkey, &msg[..], &sig).unwrap();
+
+        // the two heavy nodes produce the same signature
+        let heavy = S::aggregate(t, &[&partials[1][..], &partials[2][..]].concat()).unwrap();
+        assert_eq!(sig, heavy);
+
+        // a single heavy node does not reach the threshold
+        assert!(S::aggregate(t, &partials[2]).is_err());
+    }
+
+    #[test]
+    fn setup_weighted_rejects_zero_weight() {
+        let rng = &mut rand::thread_rng();
+
+        let res = setup_weighted::<C, S, _>(&[2, 0, 1], 2, rng);
+
+        assert!(matches!(res, Err(SetupError::ZeroWeight(1))));
+    }
+
+    async fn run_seeded_flow(seed: u64) -> (Vec<u8>, Vec<u8>) {
+        let rng = &mut ChaChaRng::seed_from_u64(seed);
+        let (t, n) = (3, 5);
+
+        let (mut board, phase0s) = setup::<C, S, _>(n, t, rng).unwrap();
+        let outputs = run_dkg::<C, S, _, _>(&mut board, phase0s, rng)
+            .await
+            .unwrap();
+
+        let msg = b"reproducible";
+        let partials = outputs
+            .iter()
+            .map(|output| S::partial_sign(&output.share, &msg[..]).unwrap())
+            .collect::<Vec<_>>();
+        let sig = S::aggregate(t, &partials).unwrap();
+
+        let pubkey = bincode::serialize(outputs[0].public.public_key()).unwrap();
+        (pubkey, sig)
+    }
+
+    #[tokio::test]
+    async fn seeded_rng_reproduces_the_dkg() {
+        let (pubkey, sig) = run_seeded_flow(42).await;
+
+        // every key and share is drawn from the injected RNG
+        assert_eq!(run_seeded_flow(42).await, (pubkey.clone(), sig.clone()));
+
+        let (other_pubkey, other_sig) = run_seeded_flow(43).await;
+        assert_ne!(pubkey, other_pubkey);
+        assert_ne!(sig, other_sig);
+    }
+}
@@ -0,0 +1,55 @@
+use gumdrop::Options;
+use randcast_mock_demo::contract::*;
+use randcast_mock_demo::controller_server::serve;
+use std::fs::File;
+use std::net::TcpListener;
+use std::sync::{Arc, Mutex};
+
+#[derive(Debug, Options)]
+struct ControllerServerOpts {
+    help: bool,
+
+    #[options(help = "the address to listen on", default = "127.0.0.1:8760")]
+    bind: String,
+
+    #[options(
+        help = "the signature scheme, g1 or g2 (BLS12-381 public keys on G1 or G2), or bn254",
+        default = "g1"
+    )]
+    scheme: SchemeKind,
+
+    #[options(help = "a JSON file with a list of nodes to register before serving")]
+    nodes: Option<String>,
+}
+
+fn main() -> anyhow::Result<()> {
+    let opts = ControllerServerOpts::parse_args_default_or_exit();
+
+    let mut controller = Controller::new(0x8762_4875_6548_6346, RewardConfig::default());
+
+    controller.scheme = opts.scheme;
+
+    if let Some(path) = opts.nodes {
+        let nodes: Vec<NodeConfig> = serde_json::from_reader(File::open(path)?)?;
+
+        let ids = nodes
+            .iter()
+            .map(|node| node.id_address.clone())
+            .collect::<Vec<_>>();
+
+        for (id_address, res) in ids.iter().zip(controller.register_from_config(nodes)) {
+            match res {
+                Ok(index) => println!("registered {} as member {}", id_address, index),
+                Err(err) => println!("could not register {}: {}", id_address, err),
+            }
+        }
+    }
+
+    let listener = TcpListener::bind(&opts.bind)?;
+
+    println!("controller is listening on {}", listener.local_addr()?);
+
+    serve(listener, Arc::new(Mutex::new(controller)))?;
+
+    Ok(())
+}
@@ -0,0 +1,248 @@
+use futures::future::join_all;
+use gumdrop::Options;
+use randcast_mock_demo::contract::*;
+use randcast_mock_demo::node_client::{ChannelBoard, NodeClient, PartialSignature};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+use threshold_bls::{
+    curve::bls12381::{self, PairingCurve as BLS12_381},
+    curve::bn254::{self, PairingCurve as BN254},
+    group::Curve,
+    sig::{G1Scheme, G2Scheme, Scheme, SignatureScheme, ThresholdScheme},
+};
+use tokio::sync::{broadcast, mpsc, Mutex};
+
+/// The endpoint of the controller living in the same process as the nodes
+const IN_PROCESS_ENDPOINT: &str = "in-process";
+
+#[derive(Debug, Options, Clone)]
+struct NodeClientOpts {
+    help: bool,
+
+    #[options(help = "the id-address of this node", default = "0x0")]
+    id_address: String,
+
+    #[options(
+        help = "the controller endpoint, only the in-process controller is supported",
+        default = "in-process"
+    )]
+    controller: String,
+
+    #[options(
+        help = "the number of nodes of the group, this node included",
+        default = "5"
+    )]
+    group_size: usize,
+
+    #[options(
+        help = "how long each DKG phase waits for the other nodes (ms)",
+        default = "500"
+    )]
+    phase_timeout: u64,
+
+    #[options(
+        help = "the rpc port of this node, the other nodes use the following ones",
+        default = "8000"
+    )]
+    rpc_port: u16,
+
+    #[options(help = "the randomness request seed", default = "ujehwsndfgljkhrlkg")]
+    seed: String,
+
+    #[options(
+        help = "the signature scheme, g1 or g2 (BLS12-381 public keys on G1 or G2), or bn254",
+        default = "g1"
+    )]
+    scheme: SchemeKind,
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let opts = NodeClientOpts::parse_args_default_or_exit();
+
+    if opts.controller != IN_PROCESS_ENDPOINT {
+        anyhow::bail!(
+            "unsupported controller endpoint {}, only {} is available",
+            opts.controller,
+            IN_PROCESS_ENDPOINT
+        );
+    }
+
+    match opts.scheme {
+        SchemeKind::G1 => run::<bls12381::Curve, G1Scheme<BLS12_381>>(opts).await,
+        SchemeKind::G2 => run::<bls12381::G2Curve, G2Scheme<BLS12_381>>(opts).await,
+        SchemeKind::Bn254 => run::<bn254::G2Curve, G2Scheme<BN254>>(opts).await,
+    }
+}
+
+async fn run<C, S>(opts: NodeClientOpts) -> anyhow::Result<()>
+where
+    C: Curve,
+    // We need to bind the Curve's Point and Scalars to the Scheme
+    S: Scheme<Public = <C as Curve>::Point, Private = <C as Curve>::Scalar>
+        + ThresholdScheme
+        + SignatureScheme,
+{
+    let mut controller = Controller::new(0x8762_4875_6548_6346, RewardConfig::default());
+
+    controller.scheme = opts.scheme;
+
+    let controller = Arc::new(Mutex::new(controller));
+
+    // the board is shared in-process, each node gets its own subscription
+    let board = ChannelBoard::<C>::new(4 * opts.group_size);
+
+    let phase_timeout = Duration::from_millis(opts.phase_timeout);
+
+    let rng = &mut rand::thread_rng();
+
+    // the other members of the group run in this process as well
+    let id_addresses = std::iter::once(opts.id_address.clone())
+        .chain((1..opts.group_size).map(|i| format!("peer-{}", i)))
+        .collect::<Vec<_>>();
+
+    let mut nodes = id_addresses
+        .iter()
+        .enumerate()
+        .map(|(i, id_address)| {
+            NodeClient::<C, S>::new(
+                id_address.clone(),
+                SocketAddr::from(([127, 0, 0, 1], opts.rpc_port + i as u16)),
+                controller.clone(),
+                board.subscribe(),
+                phase_timeout,
+                rng,
+            )
+        })
+        .collect::<Vec<_>>();
+
+    for node in nodes.iter() {
+        node.register().await?;
+    }
+
+    println!("{} nodes registered to the controller", nodes.len());
+
+    let group_size = opts.group_size;
+
+    let dkg_results = join_all(nodes.iter_mut().map(|node| async move {
+        let task = node
+            .wait_for_dkg_task(group_size, 10 * phase_timeout)
+            .await?;
+
+        if task.is_coordinator(node.id_address()) {
+            println!(
+                "[{}] coordinates the DKG of group {}",
+                node.id_address(),
+                task.group_index
+            );
+        }
+
+        let (index, public_key) = node.run_dkg(&task).await?;
+
+        println!(
+            "[{}] share index: {} committed public key: {}",
+            node.id_address(),
+            index,
+            hex::encode(bincode::serialize(&public_key)?)
+        );
+
+        Ok::<_, anyhow::Error>(task.group_index)
+    }))
+    .await
+    .into_iter()
+    .collect::<Result<Vec<_>, _>>()?;
+
+    println!("group {} finished its DKG", dkg_results[0]);
+
+    let (tasks_sender, _) = broadcast::channel(16);
+
+    let (partials_sender, partials_receiver) = mpsc::unbounded_channel();
+
+    let listening = join_all(
+        nodes
+            .iter()
+            .map(|node| node.sign_tasks(tasks_sender.subscribe(), partials_sender.clone())),
+    );
+
+    // the sender held here must not keep the partials stream open
+    drop(partials_sender);
+
+    let committing = commit_randomness(
+        controller.clone(),
+        opts.seed.clone(),
+        tasks_sender,
+        partials_receiver,
+    );
+
+    let (listening_results, committing_result) = futures::join!(listening, committing);
+
+    listening_results
+        .into_iter()
+        .collect::<Result<Vec<_>, _>>()?;
+
+    committing_result?;
+
+    println!(
+        "randomness output: {}",
+        controller.lock().await.get_last_output()
+    );
+
+    Ok(())
+}
+
+/// Requests a randomness, emits the signature task to the nodes and submits their partial
+/// signatures as they arrive, until the task gets fulfilled. Closes the task stream once done.
+async fn commit_randomness(
+    controller: Arc<Mutex<Controller>>,
+    seed: String,
+    tasks_sender: broadcast::Sender<SignatureTask>,
+    mut partials_receiver: mpsc::UnboundedReceiver<PartialSignature>,
+) -> anyhow::Result<()> {
+    let mut controller = controller.lock().await;
+
+    println!("An user is requesting a randomness... seed: {}", seed);
+
+    controller.request(String::from("0xuser"), seed, 1000, DEFAULT_PRIORITY)?;
+
+    let task = controller
+        .emit_signature_task()
+        .ok_or_else(|| anyhow::anyhow!("no signature task was emitted"))?;
+
+    if tasks_sender.send(task.clone()).is_err() {
+        anyhow::bail!("no node is listening for signature tasks");
+    }
+
+    // the nodes stop listening once the task stream is closed
+    drop(tasks_sender);
+
+    while let Some(partial) = partials_receiver.recv().await {
+        println!(
+            "[{}] partial signature of task {}: {}",
+            partial.id_address,
+            partial.signature_index,
+            hex::encode(&partial.partial_signature)
+        );
+
+        let id_address = partial.id_address.clone();
+
+        match controller.submit_partial(
+            partial.signature_index,
+            partial.id_address,
+            partial.partial_signature,
+        ) {
+            Ok(true) => {
+                println!("[{}] fulfilled task {}", id_address, task.index);
+
+                return Ok(());
+            }
+            Ok(false) => {}
+            Err(err) => println!(
+                "[{}] could not submit its partial signature of task {}: {}",
+                id_address, task.index, err
+            ),
+        }
+    }
+
+    anyhow::bail!("task {} did not reach the threshold", task.index)
+}
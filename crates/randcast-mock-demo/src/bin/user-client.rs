@@ -0,0 +1,342 @@
+use dkg_core::primitives::types::DKGOutput;
+use gumdrop::Options;
+use randcast_mock_demo::actions::{run_dkg, setup_with_private_keys};
+use randcast_mock_demo::contract::*;
+use serde::{Deserialize, Serialize};
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+use threshold_bls::{
+    curve::bls12381::{self, PairingCurve as BLS12_381},
+    sig::{G1Scheme, Scheme, ThresholdScheme},
+};
+
+type C = bls12381::Curve;
+
+type S = G1Scheme<BLS12_381>;
+
+/// How often the mock group of the server fulfills the pending signature tasks
+const SIGNING_INTERVAL: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Options)]
+struct UserClientOpts {
+    help: bool,
+
+    #[options(
+        help = "the address of the controller server",
+        default = "127.0.0.1:8750"
+    )]
+    controller: String,
+
+    #[options(command)]
+    command: Option<Command>,
+}
+
+#[derive(Debug, Options)]
+enum Command {
+    #[options(help = "serve the controller along with a mock group of nodes")]
+    Serve(ServeOpts),
+    #[options(help = "request a randomness")]
+    Request(RequestOpts),
+    #[options(help = "query the randomness of a request, or the last output")]
+    Query(QueryOpts),
+    #[options(help = "cancel a request which hasn't been assigned yet")]
+    Cancel(CancelOpts),
+}
+
+#[derive(Debug, Options)]
+struct ServeOpts {
+    help: bool,
+}
+
+#[derive(Debug, Options)]
+struct RequestOpts {
+    help: bool,
+
+    #[options(help = "the seed of the request", default = "ujehwsndfgljkhrlkg")]
+    seed: String,
+
+    #[options(help = "the address of the requester", default = "0xuser")]
+    requester: String,
+
+    #[options(help = "the fee paid for the request", default = "0")]
+    fee: u64,
+
+    #[options(
+        help = "the requests with a higher priority are served first",
+        default = "0"
+    )]
+    priority: u8,
+}
+
+#[derive(Debug, Options)]
+struct QueryOpts {
+    help: bool,
+
+    #[options(help = "the request index, the last output is returned if omitted")]
+    index: Option<usize>,
+}
+
+#[derive(Debug, Options)]
+struct CancelOpts {
+    help: bool,
+
+    #[options(help = "the request index")]
+    index: usize,
+
+    #[options(help = "the address of the requester", default = "0xuser")]
+    requester: String,
+}
+
+/// A message sent by a user to the controller server, one JSON object per line
+#[derive(Debug, Serialize, Deserialize)]
+enum UserRequest {
+    Request {
+        requester: String,
+        seed: String,
+        fee: u64,
+        #[serde(default)]
+        priority: u8,
+    },
+    Query {
+        index: usize,
+    },
+    LastOutput,
+    Cancel {
+        requester: String,
+        index: usize,
+    },
+}
+
+/// The answer of the controller server to a `UserRequest`
+#[derive(Debug, Serialize, Deserialize)]
+enum UserResponse {
+    Requested { index: usize },
+    Randomness { index: usize, randomness: u64 },
+    Pending { index: usize },
+    LastOutput { randomness: u64 },
+    Cancelled { index: usize, refund: u64 },
+    Error { message: String },
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let opts = UserClientOpts::parse_args_default_or_exit();
+
+    let request = match opts.command {
+        Some(Command::Serve(_)) => return serve(&opts.controller).await,
+        Some(Command::Request(request_opts)) => UserRequest::Request {
+            requester: request_opts.requester,
+            seed: request_opts.seed,
+            fee: request_opts.fee,
+            priority: request_opts.priority,
+        },
+        Some(Command::Query(QueryOpts {
+            index: Some(index), ..
+        })) => UserRequest::Query { index },
+        Some(Command::Query(_)) => UserRequest::LastOutput,
+        Some(Command::Cancel(cancel_opts)) => UserRequest::Cancel {
+            requester: cancel_opts.requester,
+            index: cancel_opts.index,
+        },
+        None => {
+            println!("{}", UserClientOpts::usage());
+            return Ok(());
+        }
+    };
+
+    match send(&opts.controller, &request)? {
+        UserResponse::Requested { index } => println!("request index: {}", index),
+        UserResponse::Randomness { index, randomness } => {
+            println!("randomness of request {}: {}", index, randomness)
+        }
+        UserResponse::Pending { index } => {
+            println!("request {} has not been fulfilled yet", index)
+        }
+        UserResponse::LastOutput { randomness } => println!("last output: {}", randomness),
+        UserResponse::Cancelled { index, refund } => {
+            println!("request {} was cancelled, refunded fee: {}", index, refund)
+        }
+        UserResponse::Error { message } => anyhow::bail!(message),
+    }
+
+    Ok(())
+}
+
+/// Sends a request to the controller server and waits for its response
+fn send(address: &str, request: &UserRequest) -> anyhow::Result<UserResponse> {
+    let mut stream = TcpStream::connect(address)?;
+
+    writeln!(stream, "{}", serde_json::to_string(request)?)?;
+
+    let mut line = String::new();
+
+    BufReader::new(stream).read_line(&mut line)?;
+
+    Ok(serde_json::from_str(&line)?)
+}
+
+/// Runs the controller behind a local server. A mock group of nodes runs its DKG first
+/// and then periodically fulfills the requested randomness.
+async fn serve(address: &str) -> anyhow::Result<()> {
+    let mut controller = Controller::new(0x8762_4875_6548_6346, RewardConfig::default());
+
+    let (t, n) = (3, 5);
+
+    let outputs = setup_group(&mut controller, n, t).await?;
+
+    println!("the group is ready, listening on {}", address);
+
+    let controller = Arc::new(Mutex::new(controller));
+
+    let signing_controller = controller.clone();
+
+    thread::spawn(move || loop {
+        thread::sleep(SIGNING_INTERVAL);
+
+        let mut controller = signing_controller.lock().unwrap();
+
+        while let Some(task) = controller.emit_signature_task() {
+            let res = fulfill(&mut controller, &outputs, &task);
+
+            println!("fulfilled task {}: {}", task.index, format_res(&res));
+        }
+    });
+
+    for stream in TcpListener::bind(address)?.incoming() {
+        let stream = stream?;
+
+        let controller = controller.clone();
+
+        thread::spawn(move || {
+            if let Err(err) = handle(stream, &controller) {
+                println!("could not handle the request: {}", err);
+            }
+        });
+    }
+
+    Ok(())
+}
+
+/// Answers a single request of a user
+fn handle(stream: TcpStream, controller: &Mutex<Controller>) -> anyhow::Result<()> {
+    let mut line = String::new();
+
+    BufReader::new(&stream).read_line(&mut line)?;
+
+    let request: UserRequest = serde_json::from_str(&line)?;
+
+    let mut controller = controller.lock().unwrap();
+
+    let response = match request {
+        UserRequest::Request {
+            requester,
+            seed,
+            fee,
+            priority,
+        } => match controller.request(requester, seed, fee, priority) {
+            Ok(index) => UserResponse::Requested { index },
+            Err(err) => UserResponse::Error {
+                message: err.to_string(),
+            },
+        },
+        UserRequest::Query { index } => match controller.get_randomness(index) {
+            Ok(randomness) => UserResponse::Randomness { index, randomness },
+            Err(ControllerError::SignatureResultNotFound(_)) => UserResponse::Pending { index },
+            Err(err) => UserResponse::Error {
+                message: err.to_string(),
+            },
+        },
+        UserRequest::LastOutput => UserResponse::LastOutput {
+            randomness: controller.get_last_output(),
+        },
+        UserRequest::Cancel { requester, index } => {
+            match controller.cancel_request(requester, index) {
+                Ok(refund) => UserResponse::Cancelled { index, refund },
+                Err(err) => UserResponse::Error {
+                    message: err.to_string(),
+                },
+            }
+        }
+    };
+
+    writeln!(&stream, "{}", serde_json::to_string(&response)?)?;
+
+    Ok(())
+}
+
+/// Registers `n` nodes, runs their DKG and commits its result to the controller
+async fn setup_group(
+    controller: &mut Controller,
+    n: usize,
+    t: usize,
+) -> anyhow::Result<Vec<DKGOutput<C>>> {
+    let rng = &mut rand::thread_rng();
+
+    let (mut board, phase0s, private_keys) = setup_with_private_keys::<C, S, _>(n, t, rng)?;
+
+    for (i, (phase0, private_key)) in phase0s.iter().zip(private_keys.iter()).enumerate() {
+        let id_public_key = bincode::serialize(&phase0.info.public_key)?;
+
+        let proof = proof_of_possession::<S>(private_key, &id_public_key)?;
+
+        controller.node_register(
+            String::from("0x") + &i.to_string(),
+            id_public_key,
+            format!("127.0.0.1:{}", 8000 + i),
+            String::from("0x") + &i.to_string(),
+            proof,
+        )?;
+    }
+
+    let dkg_task = controller.emit_dkg_task()[0].clone();
+
+    let outputs = run_dkg::<C, S, _, _>(&mut board, phase0s, rng).await?;
+
+    let public_poly = &outputs[0].public;
+
+    let serialized_poly = bincode::serialize(public_poly)?;
+
+    for i in 0..n {
+        controller.commit_dkg(
+            String::from("0x") + &i.to_string(),
+            dkg_task.group_index,
+            dkg_task.epoch,
+            serialized_poly.clone(),
+            bincode::serialize(&public_poly.eval(i as u32).value)?,
+            vec![],
+        )?;
+    }
+
+    Ok(outputs)
+}
+
+/// Signs the task with the shares of the group and submits the partial signatures one by
+/// one, until the threshold is reached and the task gets fulfilled
+fn fulfill(
+    controller: &mut Controller,
+    outputs: &[DKGOutput<C>],
+    task: &SignatureTask,
+) -> anyhow::Result<()> {
+    for (i, output) in outputs.iter().enumerate() {
+        let partial = S::partial_sign(&output.share, &task.message)?;
+
+        let fulfilled =
+            controller.submit_partial(task.index, String::from("0x") + &i.to_string(), partial)?;
+
+        if fulfilled {
+            return Ok(());
+        }
+    }
+
+    anyhow::bail!("the group could not reach the threshold")
+}
+
+fn format_res<T>(res: &anyhow::Result<T>) -> String {
+    match res {
+        Ok(_) => String::from("ok"),
+        Err(err) => err.to_string(),
+    }
+}
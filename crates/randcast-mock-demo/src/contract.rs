@@ -1,192 +1,1718 @@
+use crate::evaluated_poly::EvaluatedPoly;
+use crate::transform::{RandomnessTransform, RangeTransform};
+use crate::verify::{group_address_from_pubkey, RandomnessHash, RandomnessProof};
 use dkg_core::primitives::minimum_threshold;
-use paired::bls12_381::G1;
-use std::cmp::max;
+use log::{debug, info, warn};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::cmp::{max, Reverse};
 use std::collections::hash_map::DefaultHasher;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::fs::File;
 use std::hash::{Hash, Hasher};
-use threshold_bls::schemes::bls12_381::G1Scheme as SigScheme;
-use threshold_bls::sig::SignatureScheme;
-
-pub const REWARD_PER_SIGNATURE: usize = 50;
-
-pub const COMMITTER_REWARD_PER_SIGNATURE: usize = 100;
+use std::io::{BufReader, BufWriter};
+use std::net::SocketAddr;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use thiserror::Error;
+use threshold_bls::poly::{Eval, Idx, Poly};
+use threshold_bls::schemes::bls12_381::{G1Scheme, G2Scheme};
+use threshold_bls::schemes::bn254;
+use threshold_bls::sig::{SignatureScheme, ThresholdScheme};
+
+pub const REWARD_PER_SIGNATURE: u64 = 50;
+
+pub const COMMITTER_REWARD_PER_SIGNATURE: u64 = 100;
 
 pub const COMMITTER_PENALTY_PER_SIGNATURE: usize = 1000;
 
-pub const CHALLENGE_REWARD_PER_SIGNATURE: usize = 300;
+pub const CHALLENGE_REWARD_PER_SIGNATURE: u64 = 300;
 
 pub const DEFAULT_MINIMUM_THRESHOLD: usize = 3;
 
+pub const DEFAULT_GROUP_CAPACITY: usize = 10;
+
+pub const DEFAULT_MINIMUM_GROUP_SIZE: usize = 3;
+
+pub const DEFAULT_COMMITTER_COUNT: usize = 3;
+
+pub const DKG_DEADLINE_BLOCKS: usize = 10;
+
+pub const DKG_PENALTY: usize = 1000;
+
+pub const SIGNATURE_TASK_DEADLINE_BLOCKS: usize = 10;
+
+pub const MAX_SIGNATURE_TASK_ROTATIONS: usize = 3;
+
+pub const MAX_DKG_RETRIES: usize = 3;
+
+pub const VERIFICATION_CACHE_CAPACITY: usize = 256;
+
+pub const DEFAULT_STAKING: usize = 50000;
+
+pub const DEFAULT_MIN_STAKE: usize = 1000;
+
+pub const DEFAULT_PRIORITY: u8 = 0;
+
+#[derive(Serialize, Deserialize)]
 pub struct Controller {
     pub block_height: usize,
     pub epoch: usize,
     pub signature_count: usize,
     pub last_output: u64,
+    // the task whose signature produced the last output
+    last_signature_index: Option<usize>,
+    // the groups whose signatures produced the last output
+    last_output_groups: Vec<usize>,
     pub last_group_index: usize,
+    pub group_capacity: usize,
+    // the members a group needs before it runs a DKG, it can't reach the threshold of
+    // a DKG with less than `DEFAULT_MINIMUM_THRESHOLD`
+    minimum_group_size: usize,
+    // the nodes staking less are rejected at registration
+    pub min_stake: usize,
+    // the committer count of the new groups, see `set_default_committer_count`
+    committer_count: usize,
+    pub scheme: SchemeKind,
+    // derives the randomness from the signatures, set it before the first request since
+    // the outputs of the fulfilled tasks are derived again on every read
+    pub randomness_hash: RandomnessHash,
+    // not persisted, a custom hasher has to be set again after `load`
+    #[serde(skip, default = "default_request_hasher")]
+    pub request_hasher: Box<dyn RequestHasher>,
+    // not persisted, a mock clock has to be set again after `load`
+    #[serde(skip, default = "default_clock")]
+    pub clock: Box<dyn Clock>,
     groups: HashMap<usize, Group>,
     nodes: HashMap<String, Node>,
-    pub rewards: HashMap<String, usize>,
-    pending_signature_tasks: HashMap<usize, SignatureTask>,
+    pub reward_config: RewardConfig,
+    pub rewards: HashMap<String, u64>,
+    pub slashed: Vec<String>,
+    // the request fees which haven't been paid out as rewards yet
+    fee_pool: u64,
+    // the fees refunded to the requesters of cancelled tasks
+    requester_balances: HashMap<String, u64>,
+    // the nonce of the next request of every requester, a nonce is never used twice
+    requester_nonces: HashMap<String, u64>,
+    signature_tasks: BLSTasksQueue,
     verifiable_signature_rewards: HashMap<usize, SignatureReward>,
-    // mock for locally test environment
-    dkg_task: Option<DKGTask>,
-    signature_task: Option<SignatureTask>,
+    signature_result_caches: HashMap<usize, SignatureResultCache>,
+    // the valid partial signatures submitted so far, by task index and member
+    partial_signature_caches: HashMap<usize, HashMap<String, Vec<u8>>>,
+    dkg_tasks: HashMap<usize, DKGTask>,
+    // the committers which let a signature task stall, by task index
+    stalled_committers: HashMap<usize, Vec<String>>,
+    // the committers which submitted an invalid aggregated signature, by task index
+    faulty_committers: HashMap<usize, Vec<String>>,
+    last_multigroup_index: usize,
+    multigroup_requests: HashMap<usize, MultiGroupRequest>,
+    // not persisted, the results are verified again after `load`
+    #[serde(skip)]
+    verification_cache: VerificationCache,
+    // not persisted, the public keys of the shares are evaluated again after `load`
+    #[serde(skip)]
+    evaluated_polys: HashMap<usize, EvaluatedPoly>,
+    // not persisted, the listeners have to be registered again after `load`
+    #[serde(skip)]
+    signature_ready_listeners: Vec<SignatureReadyListener>,
+    #[serde(skip)]
+    group_finalized_listeners: Vec<GroupFinalizedListener>,
 }
 
 impl Controller {
-    pub fn new(initial_entropy: u64) -> Self {
+    pub fn new(initial_entropy: u64, reward_config: RewardConfig) -> Self {
+        Self::new_with_clock(initial_entropy, reward_config, SystemClock)
+    }
+
+    /// Creates a controller which reads the time from `clock`, e.g. a `MockClock`
+    pub fn new_with_clock<K: Clock + 'static>(
+        initial_entropy: u64,
+        reward_config: RewardConfig,
+        clock: K,
+    ) -> Self {
         Controller {
             block_height: 0,
             epoch: 1,
             signature_count: 0,
             last_output: initial_entropy,
+            last_signature_index: None,
+            last_output_groups: vec![],
             last_group_index: 0,
+            group_capacity: DEFAULT_GROUP_CAPACITY,
+            minimum_group_size: DEFAULT_MINIMUM_GROUP_SIZE,
+            min_stake: DEFAULT_MIN_STAKE,
+            committer_count: DEFAULT_COMMITTER_COUNT,
+            scheme: SchemeKind::G1,
+            randomness_hash: RandomnessHash::default(),
+            request_hasher: default_request_hasher(),
+            clock: Box::new(clock),
             groups: HashMap::new(),
             nodes: HashMap::new(),
+            reward_config,
             rewards: HashMap::new(),
-            pending_signature_tasks: HashMap::new(),
+            slashed: vec![],
+            fee_pool: 0,
+            requester_balances: HashMap::new(),
+            requester_nonces: HashMap::new(),
+            signature_tasks: BLSTasksQueue::new(),
             verifiable_signature_rewards: HashMap::new(),
-            dkg_task: None,
-            signature_task: None,
+            signature_result_caches: HashMap::new(),
+            partial_signature_caches: HashMap::new(),
+            dkg_tasks: HashMap::new(),
+            stalled_committers: HashMap::new(),
+            faulty_committers: HashMap::new(),
+            last_multigroup_index: 0,
+            multigroup_requests: HashMap::new(),
+            verification_cache: VerificationCache::default(),
+            evaluated_polys: HashMap::new(),
+            signature_ready_listeners: vec![],
+            group_finalized_listeners: vec![],
+        }
+    }
+
+    /// Registers a listener which is called once for every signature task, as soon as
+    /// its group has signed it, i.e. when the threshold of valid partial signatures is
+    /// reached or a committer fulfills it
+    pub fn on_signature_ready<F>(&mut self, listener: F)
+    where
+        F: Fn(&SignatureReadyEvent) + Send + Sync + 'static,
+    {
+        self.signature_ready_listeners.push(Box::new(listener));
+    }
+
+    /// Registers a listener which is called every time a group becomes active, i.e. as
+    /// soon as the threshold of its members committed the same DKG result
+    pub fn on_group_finalized<F>(&mut self, listener: F)
+    where
+        F: Fn(&GroupFinalizedEvent) + Send + Sync + 'static,
+    {
+        self.group_finalized_listeners.push(Box::new(listener));
+    }
+
+    /// The number of members a group needs before its DKG is emitted
+    pub fn minimum_group_size(&self) -> usize {
+        self.minimum_group_size
+    }
+
+    /// Sets the number of members a group needs before its DKG is emitted. Sizes below
+    /// `DEFAULT_MINIMUM_THRESHOLD` are raised to it, since the threshold of such a DKG
+    /// would exceed the size of the group.
+    pub fn set_minimum_group_size(&mut self, minimum_group_size: usize) {
+        self.minimum_group_size = max(minimum_group_size, DEFAULT_MINIMUM_THRESHOLD);
+    }
+
+    /// The number of committers a new group selects, see `set_committer_count` for the
+    /// count of a single group
+    pub fn default_committer_count(&self) -> usize {
+        self.committer_count
+    }
+
+    /// Sets the number of committers a new group selects, between 1 and the group capacity.
+    /// A group which ends up with fewer members selects all of them.
+    pub fn set_default_committer_count(
+        &mut self,
+        committer_count: usize,
+    ) -> Result<(), ControllerError> {
+        if committer_count == 0 || committer_count > self.group_capacity {
+            return Err(ControllerError::InvalidCommitterCount(
+                committer_count,
+                self.group_capacity,
+            ));
+        }
+
+        self.committer_count = committer_count;
+
+        Ok(())
+    }
+
+    /// Writes the whole state of the controller to the file at `path`
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), PersistenceError> {
+        let file = File::create(path)?;
+
+        bincode::serialize_into(BufWriter::new(file), self)?;
+
+        Ok(())
+    }
+
+    /// Restores a controller from a file written by `save`
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, PersistenceError> {
+        let file = File::open(path)?;
+
+        let controller = bincode::deserialize_from(BufReader::new(file))?;
+
+        Ok(controller)
+    }
+}
+
+/// The threshold signature of a signature task, as passed to the listeners registered
+/// with `Controller::on_signature_ready`
+#[derive(Clone, Debug, PartialEq)]
+pub struct SignatureReadyEvent {
+    pub signature_index: usize,
+    pub group_index: usize,
+    pub group_epoch: usize,
+    pub signature: Vec<u8>,
+}
+
+pub type SignatureReadyListener = Box<dyn Fn(&SignatureReadyEvent) + Send + Sync>;
+
+/// A group which has just become active, as passed to the listeners registered with
+/// `Controller::on_group_finalized`. The members are sorted by their index.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GroupFinalizedEvent {
+    pub group_index: usize,
+    pub epoch: usize,
+    pub public_key: Vec<u8>,
+    pub members: Vec<String>,
+    pub committers: Vec<String>,
+}
+
+pub type GroupFinalizedListener = Box<dyn Fn(&GroupFinalizedEvent) + Send + Sync>;
+
+/// Derives the message a group signs for a request
+pub trait RequestHasher: Send + Sync {
+    /// `seed` is the seed of the request chained with the last randomness output, and
+    /// `nonce` counts the previous requests of the requester
+    fn hash(&self, seed: &[u8], requester: &str, nonce: u64) -> Vec<u8>;
+}
+
+/// Signs the chained seed followed by the hex-encoded nonce, without hashing
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PlainRequestHasher;
+
+impl RequestHasher for PlainRequestHasher {
+    fn hash(&self, seed: &[u8], _requester: &str, nonce: u64) -> Vec<u8> {
+        let mut message = seed.to_vec();
+
+        message.extend(format!("{:016x}", nonce).as_bytes());
+
+        message
+    }
+}
+
+/// The source of the timestamps of the controller
+pub trait Clock: Send + Sync {
+    /// The seconds elapsed since the Unix epoch
+    fn now(&self) -> u64;
+}
+
+/// Reads the time of the system
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or(0)
+    }
+}
+
+/// A clock which only moves when it is told to, for deterministic tests. Its clones share
+/// the same time, so a test can keep one to advance the clock it gave to the controller.
+#[derive(Clone, Debug, Default)]
+pub struct MockClock {
+    now: Arc<AtomicU64>,
+}
+
+impl MockClock {
+    pub fn new(now: u64) -> Self {
+        MockClock {
+            now: Arc::new(AtomicU64::new(now)),
+        }
+    }
+
+    pub fn advance(&self, seconds: u64) {
+        self.now.fetch_add(seconds, Ordering::SeqCst);
+    }
+
+    pub fn set(&self, now: u64) {
+        self.now.store(now, Ordering::SeqCst);
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> u64 {
+        self.now.load(Ordering::SeqCst)
+    }
+}
+
+fn default_clock() -> Box<dyn Clock> {
+    Box::new(SystemClock)
+}
+
+/// Formats a Unix timestamp as an ISO 8601 date in UTC, e.g. `2000-02-29T00:00:00Z`
+fn format_timestamp(timestamp: u64) -> String {
+    let (days, seconds) = (timestamp / 86400, timestamp % 86400);
+
+    // converts the days to a civil date, with the years starting in March
+    let days = days + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = era * 400 + year_of_era + if month <= 2 { 1 } else { 0 };
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year,
+        month,
+        day,
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    )
+}
+
+fn hex_bytes(bytes: &[u8]) -> String {
+    format!("0x{}", hex::encode(bytes))
+}
+
+/// Orders members by the SHA-256 of the public key of their group, the salt and their
+/// index, so that every platform ranks them the same whatever order they come in
+fn rank_members<'a, I>(public_key: &[u8], salt: &[u8], members: I) -> Vec<&'a Member>
+where
+    I: IntoIterator<Item = &'a Member>,
+{
+    let mut ranked = members
+        .into_iter()
+        .map(|member| {
+            let mut hasher = Sha256::new();
+
+            hasher.input(public_key);
+            hasher.input(salt);
+            hasher.input(&(member.index as u64).to_be_bytes());
+
+            (hasher.result().to_vec(), member)
+        })
+        .collect::<Vec<_>>();
+
+    // distinct indices can't tie on the hash, the index only keeps the sort total
+    ranked.sort_by(|(a, m), (b, n)| a.cmp(b).then(m.index.cmp(&n.index)));
+
+    ranked.into_iter().map(|(_, member)| member).collect()
+}
+
+/// Maps the index of every member of a group to the public key of its share
+fn public_shares_by_index(group: &Group) -> HashMap<usize, &[u8]> {
+    group
+        .members
+        .values()
+        .map(|member| (member.index, member.partial_public_key.as_slice()))
+        .collect()
+}
+
+// an empty partial public key discloses nothing, any other one has to be the public key
+// the member's share has in the public polynomial of its group
+fn is_consistent_partial_key(member: &Member, partial_public_key: &[u8]) -> bool {
+    partial_public_key.is_empty() || partial_public_key == member.partial_public_key.as_slice()
+}
+
+// a disqualified member leaves the group and counts as a dissenter, so it gets slashed once
+// the DKG is finalized
+fn disqualify_inconsistent_member(group: &mut Group, id_address: &str) {
+    warn!(
+        "event=partial_public_key_mismatch group_index={} epoch={} id_address={}",
+        group.index, group.epoch, id_address
+    );
+
+    group.members.remove(id_address);
+
+    group.size -= 1;
+
+    fit_committer_count(group);
+
+    group.dissenters.push(id_address.to_string());
+}
+
+// a group which lost members can't select more committers than it has members left
+fn fit_committer_count(group: &mut Group) {
+    if group.committer_count > group.members.len() {
+        warn!(
+            "event=committer_count_lowered group_index={} committer_count={} members={}",
+            group.index,
+            group.committer_count,
+            group.members.len()
+        );
+
+        group.committer_count = group.members.len();
+    }
+}
+
+fn is_valid_partial(
+    scheme: SchemeKind,
+    public_shares: &HashMap<usize, &[u8]>,
+    index: usize,
+    message: &[u8],
+    partial: &[u8],
+) -> bool {
+    match public_shares.get(&index) {
+        Some(public_share) => {
+            scheme.partial_verify(public_share, (index - 1) as Idx, message, partial)
+        }
+        None => false,
+    }
+}
+
+/// Decodes a hex string with an optional `0x` prefix
+fn decode_hex(value: &str) -> Option<Vec<u8>> {
+    hex::decode(value.trim_start_matches("0x")).ok()
+}
+
+/// Signs the serialized id public key of a node with its private key, which proves to
+/// `node_register` that the node holds the key
+pub fn proof_of_possession<S: SignatureScheme>(
+    private: &S::Private,
+    id_public_key: &[u8],
+) -> Result<Vec<u8>, S::Error> {
+    S::sign(private, id_public_key)
+}
+
+/// Prefixes the seed of a request with the hex-encoded last output, the message of the
+/// request is derived from the result
+pub fn chain_seed(last_output: u64, seed: &[u8]) -> Vec<u8> {
+    let mut chained_seed = format!("{:016x}", last_output).into_bytes();
+
+    chained_seed.extend_from_slice(seed);
+
+    chained_seed
+}
+
+fn default_request_hasher() -> Box<dyn RequestHasher> {
+    Box::new(PlainRequestHasher)
+}
+
+/// The rewards paid out for every fulfilled signature task
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RewardConfig {
+    // paid to every member who contributed a valid partial signature
+    pub signature_reward: u64,
+    // paid to the committer who submitted the aggregated signature
+    pub committer_reward: u64,
+}
+
+impl Default for RewardConfig {
+    fn default() -> Self {
+        RewardConfig {
+            signature_reward: REWARD_PER_SIGNATURE,
+            committer_reward: COMMITTER_REWARD_PER_SIGNATURE,
+        }
+    }
+}
+
+/// The signature scheme used by the groups
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum SchemeKind {
+    // BLS12-381, public keys on G1, signatures on G2
+    G1,
+    // BLS12-381, public keys on G2, signatures on G1
+    G2,
+    // BN254, public keys on G2, signatures on G1
+    Bn254,
+}
+
+impl SchemeKind {
+    /// Checks that the bytes are the canonical serialization of a public key of the scheme.
+    /// Deserializing a point fails unless it lies on the curve.
+    pub fn check_public_key(&self, public_key: &[u8]) -> Result<(), String> {
+        fn check<S: SignatureScheme>(public_key: &[u8]) -> Result<(), String> {
+            let point =
+                bincode::deserialize::<S::Public>(public_key).map_err(|err| err.to_string())?;
+
+            // trailing bytes would let several byte strings commit to the same key
+            if bincode::serialize(&point).map_err(|err| err.to_string())? != public_key {
+                return Err(String::from("trailing bytes after the point"));
+            }
+
+            Ok(())
+        }
+
+        match self {
+            SchemeKind::G1 => check::<G1Scheme>(public_key),
+            SchemeKind::G2 => check::<G2Scheme>(public_key),
+            SchemeKind::Bn254 => check::<bn254::G2Scheme>(public_key),
+        }
+    }
+
+    /// Checks that the bytes are the canonical serialization of a public polynomial of the
+    /// scheme and returns its degree
+    pub fn check_public_poly(&self, public_poly: &[u8]) -> Result<usize, String> {
+        fn check<S: SignatureScheme>(public_poly: &[u8]) -> Result<usize, String> {
+            let poly = bincode::deserialize::<Poly<S::Public>>(public_poly)
+                .map_err(|err| err.to_string())?;
+
+            if bincode::serialize(&poly).map_err(|err| err.to_string())? != public_poly {
+                return Err(String::from("trailing bytes after the polynomial"));
+            }
+
+            let coefficients = Vec::from(poly);
+
+            if coefficients.is_empty() {
+                return Err(String::from("the polynomial has no coefficients"));
+            }
+
+            Ok(coefficients.len() - 1)
+        }
+
+        match self {
+            SchemeKind::G1 => check::<G1Scheme>(public_poly),
+            SchemeKind::G2 => check::<G2Scheme>(public_poly),
+            SchemeKind::Bn254 => check::<bn254::G2Scheme>(public_poly),
+        }
+    }
+
+    /// Returns the serialized public key of the group, the constant term of the serialized
+    /// public polynomial
+    pub fn public_key_of_poly(&self, public_poly: &[u8]) -> Option<Vec<u8>> {
+        fn public_key<S: SignatureScheme>(public_poly: &[u8]) -> Option<Vec<u8>> {
+            let poly = bincode::deserialize::<Poly<S::Public>>(public_poly).ok()?;
+
+            Vec::from(poly)
+                .first()
+                .and_then(|public_key| bincode::serialize(public_key).ok())
+        }
+
+        match self {
+            SchemeKind::G1 => public_key::<G1Scheme>(public_poly),
+            SchemeKind::G2 => public_key::<G2Scheme>(public_poly),
+            SchemeKind::Bn254 => public_key::<bn254::G2Scheme>(public_poly),
+        }
+    }
+
+    /// Returns the serialized public key of the share `idx` of the serialized public
+    /// polynomial, i.e. `public_poly.eval(idx).value`
+    pub fn eval_public_poly(&self, public_poly: &[u8], idx: Idx) -> Option<Vec<u8>> {
+        fn eval<S: SignatureScheme>(public_poly: &[u8], idx: Idx) -> Option<Vec<u8>> {
+            let poly = bincode::deserialize::<Poly<S::Public>>(public_poly).ok()?;
+
+            bincode::serialize(&poly.eval(idx).value).ok()
+        }
+
+        match self {
+            SchemeKind::G1 => eval::<G1Scheme>(public_poly, idx),
+            SchemeKind::G2 => eval::<G2Scheme>(public_poly, idx),
+            SchemeKind::Bn254 => eval::<bn254::G2Scheme>(public_poly, idx),
+        }
+    }
+
+    /// Returns true if the signature on the message was produced by the serialized public key
+    pub fn verify(&self, public_key: &[u8], message: &[u8], signature: &[u8]) -> bool {
+        fn verify<S: SignatureScheme>(public_key: &[u8], message: &[u8], signature: &[u8]) -> bool {
+            match bincode::deserialize::<S::Public>(public_key) {
+                Ok(public_key) => S::verify(&public_key, message, signature).is_ok(),
+                Err(_) => false,
+            }
+        }
+
+        match self {
+            SchemeKind::G1 => verify::<G1Scheme>(public_key, message, signature),
+            SchemeKind::G2 => verify::<G2Scheme>(public_key, message, signature),
+            SchemeKind::Bn254 => verify::<bn254::G2Scheme>(public_key, message, signature),
+        }
+    }
+
+    /// Recovers the threshold signature from the serialized partial signatures
+    pub fn aggregate(&self, threshold: usize, partials: &[Vec<u8>]) -> Option<Vec<u8>> {
+        fn aggregate<S: SignatureScheme>(
+            threshold: usize,
+            partials: &[Vec<u8>],
+        ) -> Option<Vec<u8>> {
+            S::aggregate(threshold, partials).ok()
+        }
+
+        match self {
+            SchemeKind::G1 => aggregate::<G1Scheme>(threshold, partials),
+            SchemeKind::G2 => aggregate::<G2Scheme>(threshold, partials),
+            SchemeKind::Bn254 => aggregate::<bn254::G2Scheme>(threshold, partials),
+        }
+    }
+
+    /// Returns true if the partial signature on the message was produced by the share of
+    /// index `share_index`, whose serialized public key is `public_share`
+    pub fn partial_verify(
+        &self,
+        public_share: &[u8],
+        share_index: Idx,
+        message: &[u8],
+        partial: &[u8],
+    ) -> bool {
+        // the partial signature is prefixed with the index of its share, a member can't pass
+        // it off as the share of another one
+        match bincode::deserialize::<Eval<Vec<u8>>>(partial) {
+            Ok(partial) => {
+                partial.index == share_index && self.verify(public_share, message, &partial.value)
+            }
+            Err(_) => false,
+        }
+    }
+}
+
+impl FromStr for SchemeKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "g1" => Ok(SchemeKind::G1),
+            "g2" => Ok(SchemeKind::G2),
+            "bn254" => Ok(SchemeKind::Bn254),
+            _ => Err(format!("unknown signature scheme: {}", s)),
         }
     }
 }
 
+/// A node to register with `register_from_config`, e.g. read from a JSON file. The
+/// keys are hex-encoded, with or without a `0x` prefix, and the rewards go to
+/// `id_address`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct NodeConfig {
+    pub id_address: String,
+    pub public_key_hex: String,
+    pub proof_of_possession_hex: String,
+    pub rpc_endpoint: String,
+    pub stake: usize,
+}
+
+#[derive(Serialize, Deserialize)]
 pub struct Node {
     pub id_address: String,
     pub id_public_key: Vec<u8>,
-    pub endpoint: String,
+    // where the other members reach the node for the private share exchange
+    pub rpc_endpoint: SocketAddr,
     pub reward_address: String,
     pub state: bool,
     pub pending_until_block: usize,
     pub staking: usize,
+    // the block height the node registered at
+    pub registered_at: usize,
+    pub participation_count: usize,
+    pub slash_count: usize,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Group {
     pub index: usize,
     pub epoch: usize,
     pub capacity: usize,
     pub size: usize,
     pub threshold: usize,
-    pub state: bool,
+    pub state: GroupState,
     pub public_key: Vec<u8>,
+    // the public polynomial of the last DKG, its constant term is the public key
+    pub public_poly: Vec<u8>,
     pub members: HashMap<String, Member>,
+    pub committer_count: usize,
     pub committers: Vec<String>,
     pub commit_cache: HashMap<String, CommitCache>,
+    // members which committed a public polynomial that disagrees with the majority
+    pub dissenters: Vec<String>,
+    // the public keys of the finalized epochs, so that past signatures stay verifiable
+    pub pubkey_history: Vec<(usize, Vec<u8>)>,
+    // the members slashed since the group started its current epoch
+    pub slashed_members: Vec<String>,
+    // the failed DKGs retried since the group was last active
+    pub dkg_retries: usize,
+    // the members whose index changed when a new DKG closed the holes of the left ones
+    pub index_reassignments: Vec<IndexReassignment>,
+}
+
+/// The index of a member before and after the DKG of `epoch` made the indices of its group
+/// contiguous again
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct IndexReassignment {
+    pub epoch: usize,
+    pub id_address: String,
+    pub old_index: usize,
+    pub new_index: usize,
+}
+
+/// The lifecycle of a group:
+///
+/// - `Registering` -> `WaitingForDkg` once enough members joined to run a DKG
+/// - `WaitingForDkg` or `Regrouping` -> `DkgInProgress` on the first commitment
+/// - `DkgInProgress` -> `Active` once a public key reaches the threshold
+/// - `Active` -> `Regrouping` when the members change and a new epoch starts
+/// - any state -> `Disabled` when the group shrinks below the minimum size after its first
+///   DKG, or when its DKG passes the deadline without a public key
+/// - `Active` -> `Disabled` when so many members got slashed that the healthy ones
+///   can't reach the threshold, the healthy members then form a replacement group
+/// - `Disabled` -> `Regrouping` when enough members joined again
+///
+/// Only `Active` groups get assigned signature tasks.
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+pub enum GroupState {
+    Registering,
+    WaitingForDkg,
+    DkgInProgress,
+    Active,
+    Regrouping,
+    Disabled,
 }
 
-#[derive(Clone)]
+impl fmt::Display for GroupState {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+/// The transitions `tick` went through, by index of their group or signature task
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct TickReport {
+    // the groups whose DKG failed and runs again in a new epoch
+    pub retried_dkgs: Vec<usize>,
+    // the groups whose DKG failed and can't be retried
+    pub disabled_groups: Vec<usize>,
+    pub rotated_tasks: Vec<usize>,
+    // the tasks cancelled after their last rotation, their fee went back to the requester
+    pub refunded_tasks: Vec<usize>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
 pub struct Member {
     index: usize,
     id_address: String,
     partial_public_key: Vec<u8>,
 }
 
-#[derive(Clone)]
+impl Member {
+    // the member of index i holds the DKG share of index i - 1
+    fn share_index(&self) -> Idx {
+        (self.index - 1) as Idx
+    }
+}
+
+/// A readable view of a group, e.g. for a block explorer. The keys are hex-encoded.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GroupSnapshot {
+    pub index: usize,
+    pub epoch: usize,
+    pub size: usize,
+    pub threshold: usize,
+    pub state: GroupState,
+    // empty until the group finished its first DKG
+    pub public_key: String,
+    // by ascending index
+    pub members: Vec<MemberSnapshot>,
+    pub committers: Vec<String>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MemberSnapshot {
+    pub index: usize,
+    pub id_address: String,
+    // the key the node registered with
+    pub id_public_key: String,
+    // the public key of its share, empty until the group finished its first DKG
+    pub partial_public_key: String,
+}
+
+/// A summary of the whole network, e.g. for monitoring
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct HealthReport {
+    pub node_count: usize,
+    pub active_group_count: usize,
+    pub disabled_group_count: usize,
+    pub pending_request_count: usize,
+    pub fulfilled_request_count: usize,
+    // every node is counted once, however many times it got slashed
+    pub slashed_node_count: usize,
+    // the task whose signature produced the last output, None until one got fulfilled
+    pub last_signature_index: Option<usize>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
 pub struct CommitCache {
     commit_result: CommitResult,
     partial_public_key: Vec<u8>,
 }
 
-#[derive(Hash, Clone)]
+#[derive(Hash, Clone, Serialize, Deserialize)]
 pub struct CommitResult {
     group_epoch: usize,
     public_key: Vec<u8>,
+    public_poly: Vec<u8>,
     disqualified_nodes: Vec<String>,
 }
 
-#[derive(Clone)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct SignatureTask {
     pub index: usize,
-    pub message: String,
+    pub requester: String,
+    pub fee: u64,
+    pub seed: Vec<u8>,
+    // the last output when the task was requested, which the seed is chained with
+    pub previous_output: u64,
+    pub message: Vec<u8>,
+    // the nonce of the requester which went into the message
+    pub nonce: u64,
     pub group_index: usize,
     pub assignment_block_height: usize,
+    // the committers can be rotated once the task is still pending after this block
+    pub deadline_block_height: usize,
+    pub rotation_count: usize,
+    // the Unix timestamp of the request
+    pub requested_at: u64,
+    // the tasks with a higher priority are emitted first
+    pub priority: u8,
 }
 
-#[derive(Clone)]
-pub struct DKGTask {
-    pub group_index: usize,
-    pub epoch: usize,
-    pub size: usize,
-    pub threshold: usize,
-    pub members: HashMap<String, usize>,
-    pub assignment_block_height: usize,
+/// The group index, the group epoch and the SHA-256 digests of the message and the
+/// signature of a verification
+type VerificationKey = (usize, usize, Vec<u8>, Vec<u8>);
+
+/// Memoizes the verifications of the threshold signatures of the groups, so that a
+/// signature submitted again e.g. by another committer doesn't cost another pairing.
+/// The least recently used result is evicted once the cache is full, and the results of
+/// a group are dropped when it starts a new epoch.
+#[derive(Debug)]
+pub struct VerificationCache {
+    capacity: usize,
+    results: HashMap<VerificationKey, bool>,
+    // the keys from the least to the most recently used
+    usage: VecDeque<VerificationKey>,
+    hits: usize,
+    misses: usize,
 }
 
-pub struct SignatureReward {
-    signature_task: SignatureTask,
-    committer: String,
-    group: Group,
-    partial_signatures: HashMap<String, Vec<u8>>,
+impl Default for VerificationCache {
+    fn default() -> Self {
+        Self::new(VERIFICATION_CACHE_CAPACITY)
+    }
 }
 
-trait Internal {
-    fn freeze_node(&mut self, id_address: &str, pending_until_block: usize);
+impl VerificationCache {
+    pub fn new(capacity: usize) -> Self {
+        VerificationCache {
+            capacity,
+            results: HashMap::new(),
+            usage: VecDeque::new(),
+            hits: 0,
+            misses: 0,
+        }
+    }
 
-    fn calculate_hash<T: Hash>(t: &T) -> u64;
-}
+    /// Returns the cached result of the verification, or calls `verify` and caches its
+    /// result
+    pub fn get_or_verify(
+        &mut self,
+        group_index: usize,
+        group_epoch: usize,
+        message: &[u8],
+        signature: &[u8],
+        verify: impl FnOnce() -> bool,
+    ) -> bool {
+        let key = (
+            group_index,
+            group_epoch,
+            Sha256::digest(message).to_vec(),
+            Sha256::digest(signature).to_vec(),
+        );
 
-pub trait MockHelper {
-    fn emit_dkg_task(&self) -> &DKGTask;
+        if let Some(result) = self.results.get(&key) {
+            let result = *result;
 
-    fn emit_signature_task(&self) -> &SignatureTask;
+            self.hits += 1;
 
-    fn mine(&mut self, block_number: usize);
-}
+            // the key becomes the most recently used one
+            if let Some(position) = self.usage.iter().position(|used| *used == key) {
+                self.usage.remove(position);
+            }
 
-pub trait Transactions {
-    fn node_register(
-        &mut self,
-        id_address: String,
-        id_public_key: Vec<u8>,
-        endpoint: String,
-        reward_address: String,
-    ) -> bool;
+            self.usage.push_back(key);
 
-    fn node_quit(&mut self, id_address: String);
+            return result;
+        }
 
-    fn node_activate(&mut self, id_address: String);
+        let result = verify();
 
-    fn redeem(&mut self, id_address: String);
+        self.misses += 1;
 
-    fn claim(&mut self, id_address: String);
+        if self.capacity == 0 {
+            return result;
+        }
 
-    fn commit_dkg(
-        &mut self,
-        id_address: String,
-        group_index: usize,
-        group_epoch: usize,
-        public_key: Vec<u8>,
-        partial_public_key: Vec<u8>,
-        disqualified_nodes: Vec<String>,
-    ) -> bool;
+        if self.results.len() >= self.capacity {
+            if let Some(evicted) = self.usage.pop_front() {
+                self.results.remove(&evicted);
+            }
+        }
 
-    fn request(&mut self, message: String) -> bool;
+        self.results.insert(key.clone(), result);
 
-    fn fulfill(
-        &mut self,
-        id_address: String,
+        self.usage.push_back(key);
+
+        result
+    }
+
+    /// Drops the results of a group, e.g. once its key changes in a new epoch
+    pub fn evict_group(&mut self, group_index: usize) {
+        self.results
+            .retain(|(index, _, _, _), _| *index != group_index);
+
+        self.usage.retain(|(index, _, _, _)| *index != group_index);
+    }
+
+    pub fn len(&self) -> usize {
+        self.results.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.results.is_empty()
+    }
+
+    /// The number of verifications read from the cache
+    pub fn hits(&self) -> usize {
+        self.hits
+    }
+
+    /// The number of verifications which had to be computed
+    pub fn misses(&self) -> usize {
+        self.misses
+    }
+}
+
+/// Holds the pending signature tasks. Tasks are emitted by priority, in the order they
+/// were requested among equal priorities, and stay pending until they get fulfilled.
+#[derive(Default, Serialize, Deserialize)]
+pub struct BLSTasksQueue {
+    last_index: usize,
+    // indices of the tasks which haven't been emitted yet, in request order
+    // whatever their priority
+    unassigned: VecDeque<usize>,
+    pending: HashMap<usize, SignatureTask>,
+    fulfilled: HashMap<usize, SignatureTask>,
+}
+
+impl BLSTasksQueue {
+    pub fn new() -> Self {
+        BLSTasksQueue::default()
+    }
+
+    /// Enqueues a new task and returns its index
+    pub fn push(
+        &mut self,
+        requester: String,
+        fee: u64,
+        seed: Vec<u8>,
+        previous_output: u64,
+        message: Vec<u8>,
+        nonce: u64,
+        group_index: usize,
+        assignment_block_height: usize,
+        priority: u8,
+        requested_at: u64,
+    ) -> usize {
+        self.last_index += 1;
+
+        let signature_task = SignatureTask {
+            index: self.last_index,
+            requester,
+            fee,
+            seed,
+            previous_output,
+            message,
+            nonce,
+            group_index,
+            assignment_block_height,
+            deadline_block_height: assignment_block_height + SIGNATURE_TASK_DEADLINE_BLOCKS,
+            rotation_count: 0,
+            requested_at,
+            priority,
+        };
+
+        self.unassigned.push_back(signature_task.index);
+
+        self.pending.insert(signature_task.index, signature_task);
+
+        self.last_index
+    }
+
+    /// Returns the task with the highest priority which hasn't been emitted yet, the
+    /// earliest requested one among equal priorities
+    pub fn pop(&mut self) -> Option<SignatureTask> {
+        let pending = &self.pending;
+
+        // the task may have been fulfilled before it was emitted
+        self.unassigned.retain(|index| pending.contains_key(index));
+
+        let (position, _) = self
+            .unassigned
+            .iter()
+            .enumerate()
+            .max_by_key(|(position, index)| (pending[*index].priority, Reverse(*position)))?;
+
+        let index = self.unassigned.remove(position)?;
+
+        self.pending.get(&index).cloned()
+    }
+
+    pub fn get(&self, index: usize) -> Option<&SignatureTask> {
+        self.pending.get(&index)
+    }
+
+    pub fn get_fulfilled(&self, index: usize) -> Option<&SignatureTask> {
+        self.fulfilled.get(&index)
+    }
+
+    /// Emits a pending task again with a new deadline
+    pub fn reassign(&mut self, index: usize, deadline_block_height: usize) {
+        if let Some(signature_task) = self.pending.get_mut(&index) {
+            signature_task.deadline_block_height = deadline_block_height;
+
+            signature_task.rotation_count += 1;
+
+            if !self.unassigned.contains(&index) {
+                self.unassigned.push_back(index);
+            }
+        }
+    }
+
+    /// Hands a pending task over to another group, which gets a full deadline and all
+    /// the rotations of its committers, and emits it again
+    pub fn migrate(&mut self, index: usize, group_index: usize, assignment_block_height: usize) {
+        if let Some(signature_task) = self.pending.get_mut(&index) {
+            signature_task.group_index = group_index;
+
+            signature_task.assignment_block_height = assignment_block_height;
+
+            signature_task.deadline_block_height =
+                assignment_block_height + SIGNATURE_TASK_DEADLINE_BLOCKS;
+
+            signature_task.rotation_count = 0;
+
+            if !self.unassigned.contains(&index) {
+                self.unassigned.push_back(index);
+            }
+        }
+    }
+
+    /// Marks the task as fulfilled and moves it out of the pending tasks
+    pub fn complete(&mut self, index: usize) -> Option<SignatureTask> {
+        self.unassigned.retain(|&i| i != index);
+
+        let signature_task = self.pending.remove(&index)?;
+
+        self.fulfilled.insert(index, signature_task.clone());
+
+        Some(signature_task)
+    }
+
+    /// Returns true if the task is pending and has already been emitted
+    pub fn is_assigned(&self, index: usize) -> bool {
+        self.pending.contains_key(&index) && !self.unassigned.contains(&index)
+    }
+
+    /// Drops a task which will never be fulfilled
+    pub fn cancel(&mut self, index: usize) -> Option<SignatureTask> {
+        self.unassigned.retain(|&i| i != index);
+
+        self.pending.remove(&index)
+    }
+
+    pub fn pending(&self) -> Vec<&SignatureTask> {
+        let mut signature_tasks = self.pending.values().collect::<Vec<_>>();
+
+        signature_tasks.sort_by_key(|signature_task| signature_task.index);
+
+        signature_tasks
+    }
+
+    pub fn fulfilled(&self) -> Vec<&SignatureTask> {
+        let mut signature_tasks = self.fulfilled.values().collect::<Vec<_>>();
+
+        signature_tasks.sort_by_key(|signature_task| signature_task.index);
+
+        signature_tasks
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct DKGTask {
+    pub group_index: usize,
+    pub epoch: usize,
+    pub size: usize,
+    pub threshold: usize,
+    // the (index, id-address) of the members, by ascending index
+    pub members: Vec<(usize, String)>,
+    // the member with the lowest index coordinates the DKG
+    pub coordinator: String,
+    pub assignment_block_height: usize,
+    pub deadline_block_height: usize,
+}
+
+impl DKGTask {
+    /// Returns the index of the member in the group, if it is assigned to this task
+    pub fn index_of(&self, id_address: &str) -> Option<usize> {
+        self.members
+            .iter()
+            .find(|(_, member_id_address)| member_id_address == id_address)
+            .map(|(index, _)| *index)
+    }
+
+    pub fn is_coordinator(&self, id_address: &str) -> bool {
+        self.coordinator == id_address
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SignatureReward {
+    signature_task: SignatureTask,
+    committer: String,
+    group: Group,
+    partial_signatures: HashMap<String, Vec<u8>>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SignatureResultCache {
+    pub group_index: usize,
+    pub group_epoch: usize,
+    // the first valid aggregated signature which has been fulfilled
+    pub signature: Vec<u8>,
+    // the committers who have committed the same signature, in order
+    pub confirmations: Vec<String>,
+    // the Unix timestamp of the fulfillment
+    pub fulfilled_at: u64,
+}
+
+/// A randomness which needs the threshold signatures of several groups over the same
+/// message. Every group signs its own task, the output combines all of them.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct MultiGroupRequest {
+    pub index: usize,
+    pub requester: String,
+    pub message: Vec<u8>,
+    pub group_indices: Vec<usize>,
+    // the signature task of every group, in the order of `group_indices`
+    pub signature_indices: Vec<usize>,
+    // the XOR of the randomness of every group, once all of them have signed
+    pub output: Option<u64>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum DeregisterResult {
+    NodeNotFound,
+    // the group the node belonged to still has enough members to sign
+    Removed,
+    // the group fell below its threshold and needs to run a new DKG
+    RegroupRequired,
+}
+
+#[derive(Debug, Error, PartialEq)]
+pub enum ControllerError {
+    #[error("group {0} does not exist")]
+    GroupNotFound(usize),
+    #[error("node {0} is not registered")]
+    NodeNotRegistered(String),
+    #[error("node {0} is already registered")]
+    NodeAlreadyRegistered(String),
+    #[error("the endpoint {1} of node {0} is not a socket address")]
+    InvalidEndpoint(String, String),
+    #[error("node {0} did not prove the possession of its public key")]
+    InvalidProofOfPossession(String),
+    #[error("the {1} of node {0} is not valid hex")]
+    InvalidNodeConfig(String, String),
+    #[error("the public key of node {0} is already registered by node {1}")]
+    PublicKeyAlreadyRegistered(String, String),
+    #[error("node {0} is not a member of group {1}")]
+    NotGroupMember(String, usize),
+    #[error("group {0} is at epoch {1}, not {2}")]
+    EpochMismatch(usize, usize, usize),
+    #[error("node {0} has already committed to group {1}")]
+    AlreadyCommitted(String, usize),
+    #[error("the DKG task of group {0} has passed its deadline")]
+    DKGDeadlinePassed(usize),
+    #[error("the last DKG of group {0} has not failed")]
+    DKGNotFailed(usize),
+    #[error("group {0} has retried its DKG too many times")]
+    DKGRetryLimitReached(usize),
+    #[error("group {0} would be left with {1} members, fewer than the minimum group size")]
+    NotEnoughMembers(usize, usize),
+    #[error("there is no group available to sign")]
+    NoAvailableGroup,
+    #[error("group {0} has not finished its DKG")]
+    GroupNotReady(usize),
+    #[error("signature task {0} does not exist")]
+    TaskNotFound(usize),
+    #[error("signature task {0} has already been fulfilled")]
+    TaskAlreadyFulfilled(usize),
+    #[error("signature task {0} has already been assigned to its committers")]
+    TaskAlreadyAssigned(usize),
+    #[error("{0} is not the requester of signature task {1}")]
+    NotRequester(String, usize),
+    #[error("node {0} is not a committer of group {1}")]
+    NotCommitter(String, usize),
+    #[error("group {0} has no committer")]
+    NoCommitter(usize),
+    #[error("the signature of task {0} is invalid")]
+    InvalidSignature(usize),
+    #[error("the signature of task {0} disagrees with the fulfilled one")]
+    ConflictingSignature(usize),
+    #[error("signature task {0} has fewer valid partial signatures than the threshold")]
+    NotEnoughValidPartials(usize),
+    #[error("the partial signature of node {0} for task {1} is invalid")]
+    InvalidPartialSignature(String, usize),
+    #[error("node {0} has already submitted a partial signature for task {1}")]
+    PartialAlreadySubmitted(String, usize),
+    #[error("signature task {0} has not been fulfilled")]
+    SignatureResultNotFound(usize),
+    #[error("group {0} has no public key at epoch {1}")]
+    PublicKeyNotFound(usize, usize),
+    #[error("signature task {0} has not passed its deadline")]
+    SignatureDeadlineNotPassed(usize),
+    #[error("the committers of signature task {0} have been rotated too many times")]
+    RotationLimitReached(usize),
+    #[error("the committers of signature task {0} can still be rotated")]
+    RotationsNotExhausted(usize),
+    #[error("a group of {1} members can't have {0} committers")]
+    InvalidCommitterCount(usize, usize),
+    #[error("only {0} partial signatures are available, {1} are required")]
+    NotEnoughPartials(usize, usize),
+    #[error("the aggregated signature does not verify against the public key of group {0}")]
+    InvalidAggregatedSignature(usize),
+    #[error("group {0} aggregates exactly its threshold of {2} partial signatures, got {1}")]
+    ThresholdCountMismatch(usize, usize, usize),
+    #[error("could not deserialize the {0} committed by node {1}: {2}")]
+    DeserializationError(String, String, String),
+    #[error("node {0} committed a public polynomial of degree {1}, group {2} expects {3}")]
+    WrongPolynomialDegree(String, usize, usize, usize),
+    #[error("a multi-group request needs at least 2 groups, got {0}")]
+    NotEnoughGroups(usize),
+    #[error("group {0} is requested more than once")]
+    DuplicateGroup(usize),
+    #[error("multi-group request {0} does not exist")]
+    MultiGroupRequestNotFound(usize),
+    #[error("group {0} has {1} healthy members left, {2} are required to sign")]
+    InsufficientSigners(usize, usize, usize),
+    #[error("group {0} is still active")]
+    GroupStillActive(usize),
+    #[error("node {0} stakes {1}, at least {2} are required")]
+    InsufficientStake(String, usize, usize),
+}
+
+#[derive(Debug, Error)]
+pub enum PersistenceError {
+    #[error("could not access the controller file: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("could not (de)serialize the controller: {0}")]
+    Serialization(#[from] bincode::Error),
+}
+
+trait Internal {
+    fn freeze_node(&mut self, id_address: &str, pending_until_block: usize);
+
+    fn create_dkg_task(&mut self, group_index: usize);
+
+    /// Selects the committers of an active group from its public key. A committer count
+    /// over the number of members is rejected.
+    fn select_committers(&mut self, group_index: usize) -> Result<(), ControllerError>;
+
+    fn create_group(&mut self) -> usize;
+
+    /// Penalizes a node, and disables its group once the slashed members make the
+    /// threshold unreachable
+    fn slash_node(&mut self, id_address: &str, penalty: usize);
+
+    /// Registers a node with the given stake, see `node_register`
+    fn register_node(
+        &mut self,
+        id_address: String,
+        id_public_key: Vec<u8>,
+        rpc_endpoint: String,
+        reward_address: String,
+        proof_of_possession: Vec<u8>,
+        staking: usize,
+    ) -> Result<usize, ControllerError>;
+
+    /// Disables a group whose threshold is unreachable and moves its healthy members to
+    /// a replacement group, which runs a DKG as soon as it has enough members
+    fn disable_group(&mut self, group_index: usize);
+
+    /// Counts the members of a group which can still sign: registered, not frozen and not
+    /// slashed in the current epoch
+    fn healthy_member_count(&self, group_index: usize) -> usize;
+
+    /// Checks that enough members of an active group can sign to reach its threshold
+    fn check_signers(&self, group_index: usize) -> Result<(), ControllerError>;
+
+    /// Picks the next group in turn which can sign, skipping the `excluded` ones
+    fn choose_group(&self, excluded: &[usize]) -> Result<usize, ControllerError>;
+
+    /// Moves the pending tasks of a group which can't sign anymore to other groups,
+    /// returns the indices of the moved tasks
+    fn reassign_requests(&mut self, group_index: usize) -> Vec<usize>;
+
+    /// Derives the message of the next request of the requester, returns it along with
+    /// the nonce it used
+    fn next_request_message(&mut self, requester: &str, seed: &[u8]) -> (Vec<u8>, u64);
+
+    fn create_signature_task(
+        &mut self,
+        requester: String,
+        fee: u64,
+        seed: Vec<u8>,
+        group_index: usize,
+        priority: u8,
+    ) -> usize;
+
+    /// Combines the outputs of a multi-group request once all of its groups have signed
+    fn finalize_multigroup_request(&mut self, signature_index: usize);
+
+    fn refund_fee(&mut self, signature_task: SignatureTask) -> u64;
+
+    /// Builds the proof of the output of a fulfilled signature task
+    fn output_proof(&self, signature_index: usize) -> Result<RandomnessProof, ControllerError>;
+
+    /// Verifies the threshold signature of a group at its current epoch, through the
+    /// verification cache
+    fn verify_group_signature(&mut self, group: &Group, message: &[u8], signature: &[u8]) -> bool;
+
+    /// Picks at most `threshold` of the partial signatures which verify against the
+    /// shares of their signers, by ascending member index
+    fn trim_partials(
+        &self,
+        group_index: usize,
+        message: &[u8],
+        partial_signatures: &HashMap<String, Vec<u8>>,
+    ) -> Vec<Vec<u8>>;
+
+    /// Aggregates the partial signatures of a group, which have to be exactly as many as
+    /// its threshold. Too few of them are reported as `NotEnoughPartials`, so that the
+    /// task can wait for more.
+    fn aggregate_partials(
+        &self,
+        group_index: usize,
+        partials: &[Vec<u8>],
+    ) -> Result<Vec<u8>, ControllerError>;
+
+    /// Settles a signature task with the aggregated signature of a committer and rewards
+    /// the members whose partial signatures verify. Both carry the compressed form of their
+    /// curve point, e.g. as `bls12381::compress` encodes it.
+    fn fulfill(
+        &mut self,
+        id_address: String,
+        signature_index: usize,
+        signature: Vec<u8>,
+        partial_signatures: HashMap<String, Vec<u8>>,
+    ) -> Result<(), ControllerError>;
+
+    fn calculate_hash<T: Hash>(t: &T) -> u64;
+}
+
+pub trait MockHelper {
+    fn emit_dkg_task(&self) -> Vec<&DKGTask>;
+
+    fn emit_signature_task(&mut self) -> Option<SignatureTask>;
+
+    fn mine(&mut self, block_number: usize);
+
+    /// Moves to the block `block_height`, unless it is in the past, and runs every
+    /// transition whose deadline has passed at once: the overdue DKGs are finalized and
+    /// retried, or their group stays disabled, the stalled signature tasks get new
+    /// committers, and those out of rotations are cancelled and refunded
+    fn tick(&mut self, block_height: usize) -> TickReport;
+}
+
+pub trait Transactions {
+    /// Registers a node and assigns it to a group. `rpc_endpoint` has to be a socket
+    /// address, e.g. `127.0.0.1:8000`. The node proves that it holds the private key of
+    /// `id_public_key` with a signature of the key, see `proof_of_possession`.
+    fn node_register(
+        &mut self,
+        id_address: String,
+        id_public_key: Vec<u8>,
+        rpc_endpoint: String,
+        reward_address: String,
+        proof_of_possession: Vec<u8>,
+    ) -> Result<usize, ControllerError>;
+
+    /// Registers every node of a config, with the same checks as `node_register`. The
+    /// result of each node is returned in order, a failed node doesn't stop the others.
+    fn register_from_config(
+        &mut self,
+        nodes: Vec<NodeConfig>,
+    ) -> Vec<Result<usize, ControllerError>>;
+
+    fn node_deregister(&mut self, id_address: String) -> DeregisterResult;
+
+    fn node_quit(&mut self, id_address: String);
+
+    fn node_activate(&mut self, id_address: String);
+
+    fn redeem(&mut self, id_address: String);
+
+    fn claim(&mut self, id_address: String);
+
+    /// Commits the result of a DKG. A `group_epoch` other than the current epoch of the
+    /// group is rejected, e.g. the late commit of a DKG which was superseded by a regroup.
+    /// The public polynomial must have the degree `threshold - 1` of the group, its constant
+    /// term is the public key of the group. A commit which leaves no public key a chance to
+    /// reach the threshold is recorded all the same, the DKG then fails at its deadline.
+    fn commit_dkg(
+        &mut self,
+        id_address: String,
+        group_index: usize,
+        group_epoch: usize,
+        public_poly: Vec<u8>,
+        partial_public_key: Vec<u8>,
+        disqualified_nodes: Vec<String>,
+    ) -> Result<(), ControllerError>;
+
+    fn finalize_dkg(&mut self, group_index: usize, group_epoch: usize) -> bool;
+
+    /// Runs the DKG of a group again after it failed, in a new epoch. A DKG past its
+    /// deadline is finalized first. The members slashed for the failed round can be
+    /// dropped from the group, and the deadline doubles with every retry. Once
+    /// `MAX_DKG_RETRIES` is reached the group stays disabled.
+    fn retry_dkg(
+        &mut self,
+        group_index: usize,
+        drop_failed_members: bool,
+    ) -> Result<DKGTask, ControllerError>;
+
+    /// Sets how many members of the group commit the signatures, between 1 and the size of
+    /// the group. The committers of an active group are selected again right away.
+    fn set_committer_count(
+        &mut self,
+        group_index: usize,
+        committer_count: usize,
+    ) -> Result<(), ControllerError>;
+
+    /// Requests a randomness from the next group. Among the pending tasks, the ones with
+    /// a higher `priority` are emitted first.
+    fn request(
+        &mut self,
+        requester: String,
+        seed: String,
+        fee: u64,
+        priority: u8,
+    ) -> Result<usize, ControllerError>;
+
+    /// Like `request`, but with a binary seed such as a block hash, which is signed as is
+    fn request_bytes(
+        &mut self,
+        requester: String,
+        seed: Vec<u8>,
+        fee: u64,
+        priority: u8,
+    ) -> Result<usize, ControllerError>;
+
+    fn request_from_group(
+        &mut self,
+        requester: String,
+        seed: String,
+        fee: u64,
+        group_index: usize,
+    ) -> Result<usize, ControllerError>;
+
+    /// Requests a randomness which every given group has to sign. Each group gets its own
+    /// task over the same message, and the fee is split between them.
+    fn request_multigroup(
+        &mut self,
+        requester: String,
+        seed: String,
+        fee: u64,
+        group_indices: Vec<usize>,
+    ) -> Result<usize, ControllerError>;
+
+    /// Verifies the partial signature of a group member for a task and stores it. Once the
+    /// threshold of valid partial signatures is reached, they are aggregated and the task
+    /// gets fulfilled. The signature point is taken in its compressed form. Returns true if
+    /// this partial signature fulfilled the task.
+    fn submit_partial(
+        &mut self,
+        signature_index: usize,
+        id_address: String,
+        partial_signature: Vec<u8>,
+    ) -> Result<bool, ControllerError>;
+
+    /// Fulfills a task with the signature a committer of its group aggregated, along with
+    /// the partial signatures of the members by id address. An invalid signature marks the
+    /// committer as faulty, the task is then fulfilled from the valid partial signatures.
+    /// The tasks reaching the threshold in `submit_partial` are fulfilled on behalf of the
+    /// first committer.
+    fn submit_signature(
+        &mut self,
+        id_address: String,
         signature_index: usize,
         signature: Vec<u8>,
         partial_signatures: HashMap<String, Vec<u8>>,
-    ) -> bool;
+    ) -> Result<(), ControllerError>;
 
     fn challenge_reward(&mut self, id_address: String, signature_index: usize) -> bool;
+
+    fn rotate_signature_committers(
+        &mut self,
+        signature_index: usize,
+    ) -> Result<Vec<String>, ControllerError>;
+
+    /// Moves a pending task of a group which can't sign anymore to the next active group,
+    /// which gets a new deadline. Returns the index of the new group. This happens to
+    /// every pending task of a group as soon as it gets disabled.
+    fn reassign_request(&mut self, signature_index: usize) -> Result<usize, ControllerError>;
+
+    /// Cancels a task whose committers can't be rotated anymore and refunds its fee to
+    /// the requester. Returns the refunded amount.
+    fn cancel_signature_task(&mut self, signature_index: usize) -> Result<u64, ControllerError>;
+
+    /// Withdraws a request which hasn't been emitted to its group yet and refunds its fee
+    /// to the requester. Returns the refunded amount.
+    fn cancel_request(
+        &mut self,
+        requester: String,
+        signature_index: usize,
+    ) -> Result<u64, ControllerError>;
 }
 
 pub trait Views {
     fn get_last_output(&self) -> u64;
 
+    /// Applies a transform to the entropy of the last output, i.e. the hash of its
+    /// signature, or the hash of the initial entropy until a task has been fulfilled
+    fn get_last_output_transformed<T: RandomnessTransform>(&self, transform: &T) -> T::Output;
+
+    /// Returns the last output reduced to a uniform integer in `min..=max`. Panics if
+    /// `min` is greater than `max`.
+    fn get_last_output_in_range(&self, min: u128, max: u128) -> u128;
+
+    /// Returns the last output along with the signed message, the signature and the key
+    /// of the group, or None until a task has been fulfilled
+    fn get_last_output_with_proof(&self) -> Option<RandomnessProof>;
+
+    /// Returns the outputs of the signature tasks `from_index..=to_index` along with their
+    /// proofs, by ascending index. Fails on the first task which has not been fulfilled.
+    fn get_outputs(
+        &self,
+        from_index: usize,
+        to_index: usize,
+    ) -> Result<Vec<(u64, RandomnessProof)>, ControllerError>;
+
+    /// Returns the request which produced the last output as JSON, see `get_request_json`,
+    /// or None until a task has been fulfilled
+    fn get_last_output_json(&self) -> Option<serde_json::Value>;
+
+    /// Returns a request as JSON. The byte fields are `0x`-prefixed hex strings and the
+    /// timestamps are ISO 8601 dates in UTC. The randomness, signature and key of the
+    /// group are only present once the request has been fulfilled.
+    fn get_request_json(&self, index: usize) -> Result<serde_json::Value, ControllerError>;
+
+    /// Returns the groups whose signatures produced the last output, more than one if it
+    /// came from a multi-group request
+    fn get_last_output_groups(&self) -> &[usize];
+
+    fn get_multigroup_request(&self, index: usize) -> Result<&MultiGroupRequest, ControllerError>;
+
     fn get_node(&self, id_address: String) -> &Node;
 
-    fn get_group(&self, index: usize) -> &Group;
+    /// Returns the endpoint of a node, for its peers to exchange their private shares
+    fn get_node_endpoint(&self, id_address: String) -> Result<SocketAddr, ControllerError>;
+
+    fn get_group(&self, index: usize) -> Result<&Group, ControllerError>;
+
+    /// Sums the stakes of the members of a group, after their penalties
+    fn get_group_stake(&self, group_index: usize) -> Result<usize, ControllerError>;
+
+    /// Returns a snapshot of the group which serializes to readable JSON
+    fn get_group_snapshot(&self, index: usize) -> Result<GroupSnapshot, ControllerError>;
+
+    /// Summarizes the nodes, groups and requests of the network
+    fn health_report(&self) -> HealthReport;
+
+    /// Returns the address of the group derived from its public key, see
+    /// `group_address_from_pubkey`. The group needs to have finished its DKG.
+    fn get_group_address(&self, group_index: usize) -> Result<[u8; 20], ControllerError>;
+
+    fn get_group_pubkey_at(
+        &self,
+        group_index: usize,
+        epoch: usize,
+    ) -> Result<&[u8], ControllerError>;
+
+    /// Returns the (share index, serialized public key share) of the members of a finalized
+    /// group, i.e. the evaluations `public_poly.eval(idx)` of its public polynomial which
+    /// verify their partial signatures, by ascending index
+    fn get_member_public_shares(
+        &self,
+        group_index: usize,
+    ) -> Result<Vec<(Idx, Vec<u8>)>, ControllerError>;
 
     fn valid_group_indices(&self) -> Vec<usize>;
 
+    /// Returns the groups which get assigned the requests, by ascending index
+    fn get_active_groups(&self) -> Vec<&Group>;
+
     fn pending_signature_tasks(&self) -> Vec<&SignatureTask>;
 
     fn verifiable_signature_rewards(&self) -> Vec<&SignatureReward>;
+
+    fn get_signature_confirmations(&self, index: usize) -> Result<&[String], ControllerError>;
+
+    fn get_node_reward(&self, id_address: String) -> u64;
+
+    fn get_fee_pool(&self) -> u64;
+
+    fn get_requester_balance(&self, requester: String) -> u64;
+
+    /// Returns the nonce which the next request of the requester will use
+    fn get_requester_nonce(&self, requester: String) -> u64;
+
+    /// Verifies the partial signatures of the members of a group, each given along with
+    /// the index of its member, and returns the indices of the invalid ones in ascending
+    /// order. An index which isn't a member of the group is invalid.
+    fn verify_partials(
+        &self,
+        group_index: usize,
+        message: &[u8],
+        partials: &[(usize, Vec<u8>)],
+    ) -> Result<Vec<usize>, ControllerError>;
+
+    /// Like `verify_partials`, but spreads the verifications over the thread pool of rayon
+    #[cfg(feature = "parallel")]
+    fn verify_partials_parallel(
+        &self,
+        group_index: usize,
+        message: &[u8],
+        partials: &[(usize, Vec<u8>)],
+    ) -> Result<Vec<usize>, ControllerError>;
+
+    /// Aggregates the first `threshold` partial signatures of distinct indices, e.g. those
+    /// of the fastest responders, and checks the result against the key of the group
+    fn aggregate_subset(
+        &self,
+        group_index: usize,
+        threshold: usize,
+        message: &[u8],
+        partials: &[(Idx, Vec<u8>)],
+    ) -> Result<Vec<u8>, ControllerError>;
+
+    fn get_randomness(&self, index: usize) -> Result<u64, ControllerError>;
+
+    fn get_stalled_committers(&self, signature_index: usize) -> &[String];
+
+    /// Returns the committers whose aggregated signature of the task failed to verify
+    /// and got replaced by the aggregate of the valid partial signatures
+    fn get_faulty_committers(&self, signature_index: usize) -> &[String];
+
+    /// Returns the (index, seed, group index, requested block) of the requests which
+    /// haven't been fulfilled yet
+    fn get_pending_requests(&self) -> Vec<(usize, Vec<u8>, usize, usize)>;
+
+    /// Returns the (index, seed, group index, requested block, randomness) of the
+    /// fulfilled requests
+    fn get_fulfilled_requests(&self) -> Vec<(usize, Vec<u8>, usize, usize, u64)>;
 }
 
 impl Internal for Controller {
@@ -198,491 +1724,7287 @@ impl Internal for Controller {
         todo!()
     }
 
-    fn calculate_hash<T: Hash>(t: &T) -> u64 {
-        let mut s = DefaultHasher::new();
-        t.hash(&mut s);
-        s.finish()
-    }
-}
+    fn create_dkg_task(&mut self, group_index: usize) {
+        let group = self.groups.get_mut(&group_index).unwrap();
 
-impl MockHelper for Controller {
-    fn emit_dkg_task(&self) -> &DKGTask {
-        &self.dkg_task.as_ref().unwrap()
-    }
+        group.epoch += 1;
+
+        // the signatures of the previous key don't need to be verified anymore
+        self.verification_cache.evict_group(group_index);
+
+        // and the public keys of its shares change with its polynomial
+        self.evaluated_polys.remove(&group_index);
+
+        // the group regroups, so the commitments to the previous epoch are dropped
+        group.state = if group.pubkey_history.is_empty() {
+            GroupState::WaitingForDkg
+        } else {
+            GroupState::Regrouping
+        };
+
+        group.committers.clear();
+
+        group.commit_cache.clear();
+
+        group.dissenters.clear();
+
+        group.slashed_members.clear();
+
+        let mut members = group.members.values_mut().collect::<Vec<_>>();
+
+        members.sort_by_key(|member| member.index);
+
+        // the DKG evaluates the polynomial at the indices 1..=size, the indices of the
+        // members who left are given to the following ones
+        for (i, member) in members.iter_mut().enumerate() {
+            if member.index != i + 1 {
+                info!(
+                    "event=member_index_reassigned group_index={} epoch={} id_address={} old_index={} new_index={}",
+                    group.index,
+                    group.epoch,
+                    member.id_address,
+                    member.index,
+                    i + 1
+                );
+
+                group.index_reassignments.push(IndexReassignment {
+                    epoch: group.epoch,
+                    id_address: member.id_address.clone(),
+                    old_index: member.index,
+                    new_index: i + 1,
+                });
+
+                member.index = i + 1;
+            }
+        }
+
+        let members = members
+            .into_iter()
+            .map(|member| (member.index, member.id_address.clone()))
+            .collect::<Vec<_>>();
+
+        // the group always has at least the minimum size when it runs a DKG
+        let coordinator = members[0].1.clone();
+
+        let dkg_task = DKGTask {
+            group_index: group.index,
+            epoch: group.epoch,
+            size: group.size,
+            threshold: group.threshold,
+            members,
+            coordinator,
+            assignment_block_height: self.block_height,
+            deadline_block_height: self.block_height + DKG_DEADLINE_BLOCKS,
+        };
+
+        info!(
+            "event=dkg_task_created group_index={} epoch={} size={} threshold={} coordinator={}",
+            dkg_task.group_index,
+            dkg_task.epoch,
+            dkg_task.size,
+            dkg_task.threshold,
+            dkg_task.coordinator
+        );
+
+        // a new epoch supersedes the pending task of the group
+        self.dkg_tasks.insert(group_index, dkg_task);
+    }
+
+    fn select_committers(&mut self, group_index: usize) -> Result<(), ControllerError> {
+        // the same DKG output always chooses the same committers
+        let group = self.groups.get_mut(&group_index).unwrap();
+
+        if group.committer_count > group.members.len() {
+            return Err(ControllerError::InvalidCommitterCount(
+                group.committer_count,
+                group.members.len(),
+            ));
+        }
+
+        group.committers = rank_members(&group.public_key, &[], group.members.values())
+            .into_iter()
+            .take(group.committer_count)
+            .map(|member| member.id_address.clone())
+            .collect::<Vec<_>>();
+
+        Ok(())
+    }
+
+    fn create_group(&mut self) -> usize {
+        let group_index = self.groups.len() + 1;
+
+        let group = Group {
+            index: group_index,
+            epoch: 0,
+            capacity: self.group_capacity,
+            size: 0,
+            threshold: DEFAULT_MINIMUM_THRESHOLD,
+            state: GroupState::Registering,
+            public_key: vec![],
+            public_poly: vec![],
+            members: HashMap::new(),
+            committer_count: self.committer_count,
+            committers: vec![],
+            commit_cache: HashMap::new(),
+            dissenters: vec![],
+            pubkey_history: vec![],
+            slashed_members: vec![],
+            dkg_retries: 0,
+            index_reassignments: vec![],
+        };
+
+        self.groups.insert(group_index, group);
+
+        group_index
+    }
+
+    fn register_node(
+        &mut self,
+        id_address: String,
+        id_public_key: Vec<u8>,
+        rpc_endpoint: String,
+        reward_address: String,
+        proof_of_possession: Vec<u8>,
+        staking: usize,
+    ) -> Result<usize, ControllerError> {
+        if self.nodes.contains_key(&id_address) {
+            return Err(ControllerError::NodeAlreadyRegistered(id_address));
+        }
+
+        if staking < self.min_stake {
+            return Err(ControllerError::InsufficientStake(
+                id_address,
+                staking,
+                self.min_stake,
+            ));
+        }
+
+        let rpc_endpoint = rpc_endpoint
+            .parse::<SocketAddr>()
+            .map_err(|_| ControllerError::InvalidEndpoint(id_address.clone(), rpc_endpoint))?;
+
+        // a rogue key, derived from the keys of other nodes, can't be signed for
+        if !self
+            .scheme
+            .verify(&id_public_key, &id_public_key, &proof_of_possession)
+        {
+            return Err(ControllerError::InvalidProofOfPossession(id_address));
+        }
+
+        // a key registered under several addresses could grind its way into a group
+        if let Some(node) = self
+            .nodes
+            .values()
+            .find(|node| node.id_public_key == id_public_key)
+        {
+            return Err(ControllerError::PublicKeyAlreadyRegistered(
+                id_address,
+                node.id_address.clone(),
+            ));
+        }
+
+        let node = Node {
+            id_address: id_address.clone(),
+            id_public_key,
+            rpc_endpoint,
+            reward_address,
+            state: true,
+            pending_until_block: 0,
+            staking,
+            registered_at: self.block_height,
+            participation_count: 0,
+            slash_count: 0,
+        };
+
+        self.nodes.insert(id_address.clone(), node);
+
+        self.rewards.insert(id_address.clone(), 0);
+
+        // the node joins the group with the fewest members
+        let available_group_index = self
+            .groups
+            .values()
+            .filter(|g| g.size < g.capacity)
+            .min_by_key(|g| (g.size, g.index))
+            .map(|g| g.index);
+
+        let group_index = match available_group_index {
+            Some(group_index) => group_index,
+            // all groups are full
+            None => self.create_group(),
+        };
+
+        let group = self.groups.get_mut(&group_index).unwrap();
+
+        group.size += 1;
+
+        // indices of deregistered members are not reused until the next DKG
+        let index = group.members.values().map(|m| m.index).max().unwrap_or(0) + 1;
+
+        let member = Member {
+            index,
+            id_address: id_address.clone(),
+            partial_public_key: vec![],
+        };
+
+        group.members.insert(id_address.clone(), member);
+
+        let minimum = minimum_threshold(group.size);
+
+        group.threshold = max(DEFAULT_MINIMUM_THRESHOLD, minimum);
+
+        info!(
+            "event=node_registered id_address={} group_index={} member_index={} staking={}",
+            id_address, group_index, index, staking
+        );
+
+        if group.size >= self.minimum_group_size {
+            self.create_dkg_task(group_index);
+        }
+
+        // the DKG may have moved the node to the index of a member who left
+        Ok(self.groups[&group_index].members[&id_address].index)
+    }
+
+    fn slash_node(&mut self, id_address: &str, penalty: usize) {
+        let node = self.nodes.get_mut(id_address).unwrap();
+
+        node.staking = node.staking.saturating_sub(penalty);
+
+        node.slash_count += 1;
+
+        self.slashed.push(id_address.to_string());
+
+        let group = match self
+            .groups
+            .values_mut()
+            .find(|g| g.members.contains_key(id_address))
+        {
+            Some(group) => group,
+            None => return,
+        };
+
+        if !group.slashed_members.iter().any(|m| m == id_address) {
+            group.slashed_members.push(id_address.to_string());
+        }
+
+        // the healthy members alone can't reach the threshold anymore
+        if group.state == GroupState::Active
+            && group.slashed_members.len() > group.size - group.threshold
+        {
+            let group_index = group.index;
+
+            self.disable_group(group_index);
+        }
+    }
+
+    fn healthy_member_count(&self, group_index: usize) -> usize {
+        let group = self.groups.get(&group_index).unwrap();
+
+        group
+            .members
+            .keys()
+            .filter(|id_address| !group.slashed_members.contains(id_address))
+            .filter(|id_address| match self.nodes.get(*id_address) {
+                Some(node) => node.state,
+                None => false,
+            })
+            .count()
+    }
+
+    fn check_signers(&self, group_index: usize) -> Result<(), ControllerError> {
+        let group = self.groups.get(&group_index).unwrap();
+
+        let healthy_member_count = self.healthy_member_count(group_index);
+
+        if healthy_member_count < group.threshold {
+            return Err(ControllerError::InsufficientSigners(
+                group_index,
+                healthy_member_count,
+                group.threshold,
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn disable_group(&mut self, group_index: usize) {
+        let group = self.groups.get_mut(&group_index).unwrap();
+
+        group.state = GroupState::Disabled;
+
+        group.committers.clear();
+
+        group.commit_cache.clear();
+
+        group.dissenters.clear();
+
+        let slashed_members = std::mem::take(&mut group.slashed_members);
+
+        // every member leaves, the slashed ones don't join any other group
+        let mut healthy_members = group
+            .members
+            .drain()
+            .map(|(_, member)| member)
+            .filter(|member| !slashed_members.contains(&member.id_address))
+            .collect::<Vec<_>>();
+
+        group.size = 0;
+
+        healthy_members.sort_by_key(|member| member.index);
+
+        let replacement_index = self.create_group();
+
+        let replacement = self.groups.get_mut(&replacement_index).unwrap();
+
+        for (i, member) in healthy_members.into_iter().enumerate() {
+            let member = Member {
+                index: i + 1,
+                id_address: member.id_address,
+                partial_public_key: vec![],
+            };
+
+            replacement
+                .members
+                .insert(member.id_address.clone(), member);
+        }
+
+        replacement.size = replacement.members.len();
+
+        replacement.threshold = max(
+            DEFAULT_MINIMUM_THRESHOLD,
+            minimum_threshold(replacement.size),
+        );
+
+        if replacement.size >= self.minimum_group_size {
+            self.create_dkg_task(replacement_index);
+        }
+
+        self.reassign_requests(group_index);
+    }
+
+    fn choose_group(&self, excluded: &[usize]) -> Result<usize, ControllerError> {
+        let mut active_group_indices = self
+            .valid_group_indices()
+            .into_iter()
+            .filter(|group_index| !excluded.contains(group_index))
+            .collect::<Vec<_>>();
+
+        if active_group_indices.is_empty() {
+            return Err(ControllerError::NoAvailableGroup);
+        }
+
+        active_group_indices.sort();
+
+        // the requests skip the groups which can't reach their threshold anymore
+        let valid_group_indices = active_group_indices
+            .iter()
+            .copied()
+            .filter(|group_index| self.check_signers(*group_index).is_ok())
+            .collect::<Vec<_>>();
+
+        if valid_group_indices.is_empty() {
+            return Err(self.check_signers(active_group_indices[0]).unwrap_err());
+        }
+
+        let mut assignment_group_index = self.last_group_index;
+
+        loop {
+            assignment_group_index = (assignment_group_index + 1) % (self.groups.len() + 1);
+
+            if valid_group_indices.contains(&assignment_group_index) {
+                return Ok(assignment_group_index);
+            }
+        }
+    }
+
+    fn reassign_requests(&mut self, group_index: usize) -> Vec<usize> {
+        let signature_indices = self
+            .signature_tasks
+            .pending()
+            .iter()
+            .filter(|signature_task| signature_task.group_index == group_index)
+            .map(|signature_task| signature_task.index)
+            .collect::<Vec<_>>();
+
+        // the tasks stay with the group if no other one can take them
+        signature_indices
+            .into_iter()
+            .filter(|signature_index| self.reassign_request(*signature_index).is_ok())
+            .collect()
+    }
+
+    fn next_request_message(&mut self, requester: &str, seed: &[u8]) -> (Vec<u8>, u64) {
+        // chain the randomness by the last output so that the message can't be chosen freely
+        let chained_seed = chain_seed(self.last_output, seed);
+
+        // identical seeds of the same requester still yield distinct messages
+        let next_nonce = self
+            .requester_nonces
+            .entry(requester.to_string())
+            .or_insert(0);
+
+        let nonce = *next_nonce;
+
+        *next_nonce += 1;
+
+        let message = self.request_hasher.hash(&chained_seed, requester, nonce);
+
+        (message, nonce)
+    }
+
+    fn create_signature_task(
+        &mut self,
+        requester: String,
+        fee: u64,
+        seed: Vec<u8>,
+        group_index: usize,
+        priority: u8,
+    ) -> usize {
+        self.signature_count += 1;
+
+        // mock: payment for request
+        self.fee_pool += fee;
+
+        let previous_output = self.last_output;
+
+        let (message, nonce) = self.next_request_message(&requester, &seed);
+
+        let signature_index = self.signature_tasks.push(
+            requester.clone(),
+            fee,
+            seed,
+            previous_output,
+            message,
+            nonce,
+            group_index,
+            self.block_height,
+            priority,
+            self.clock.now(),
+        );
+
+        info!(
+            "event=randomness_requested signature_index={} requester={} group_index={} fee={} priority={}",
+            signature_index, requester, group_index, fee, priority
+        );
+
+        signature_index
+    }
+
+    fn finalize_multigroup_request(&mut self, signature_index: usize) {
+        let request = match self
+            .multigroup_requests
+            .values_mut()
+            .find(|request| request.signature_indices.contains(&signature_index))
+        {
+            Some(request) => request,
+            None => return,
+        };
+
+        let mut output = 0;
+
+        for index in &request.signature_indices {
+            match self.signature_result_caches.get(index) {
+                Some(cache) => output ^= self.randomness_hash.output(&cache.signature),
+                // some groups haven't signed yet
+                None => return,
+            }
+        }
+
+        request.output = Some(output);
+
+        self.last_output = output;
+
+        self.last_output_groups = request.group_indices.clone();
+
+        // a combined output can't be proven by a single signature
+        self.last_signature_index = None;
+    }
+
+    fn trim_partials(
+        &self,
+        group_index: usize,
+        message: &[u8],
+        partial_signatures: &HashMap<String, Vec<u8>>,
+    ) -> Vec<Vec<u8>> {
+        let group = self.groups.get(&group_index).unwrap();
+
+        let mut verified = partial_signatures
+            .iter()
+            .filter_map(|(id_address, partial_signature)| {
+                let member = group.members.get(id_address)?;
+
+                if self.scheme.partial_verify(
+                    &member.partial_public_key,
+                    member.share_index(),
+                    message,
+                    partial_signature,
+                ) {
+                    Some((member.index, partial_signature.clone()))
+                } else {
+                    None
+                }
+            })
+            .collect::<Vec<_>>();
+
+        verified.sort_by_key(|(index, _)| *index);
+
+        verified
+            .into_iter()
+            .take(group.threshold)
+            .map(|(_, partial_signature)| partial_signature)
+            .collect()
+    }
+
+    fn aggregate_partials(
+        &self,
+        group_index: usize,
+        partials: &[Vec<u8>],
+    ) -> Result<Vec<u8>, ControllerError> {
+        let group = self.get_group(group_index)?;
+
+        if partials.len() < group.threshold {
+            return Err(ControllerError::NotEnoughPartials(
+                partials.len(),
+                group.threshold,
+            ));
+        }
+
+        if partials.len() > group.threshold {
+            return Err(ControllerError::ThresholdCountMismatch(
+                group_index,
+                partials.len(),
+                group.threshold,
+            ));
+        }
+
+        self.scheme
+            .aggregate(group.threshold, partials)
+            .ok_or(ControllerError::InvalidAggregatedSignature(group_index))
+    }
+
+    fn output_proof(&self, signature_index: usize) -> Result<RandomnessProof, ControllerError> {
+        let cache = self
+            .signature_result_caches
+            .get(&signature_index)
+            .ok_or(ControllerError::SignatureResultNotFound(signature_index))?;
+
+        let signature_task = self
+            .signature_tasks
+            .get_fulfilled(signature_index)
+            .ok_or(ControllerError::SignatureResultNotFound(signature_index))?;
+
+        let group_pubkey = self.get_group_pubkey_at(cache.group_index, cache.group_epoch)?;
+
+        Ok(RandomnessProof {
+            index: signature_index,
+            requester: signature_task.requester.clone(),
+            seed: chain_seed(signature_task.previous_output, &signature_task.seed),
+            nonce: signature_task.nonce,
+            randomness: self.randomness_hash.randomness(&cache.signature),
+            hash: self.randomness_hash,
+            message: signature_task.message.clone(),
+            signature: cache.signature.clone(),
+            group_pubkey: group_pubkey.to_vec(),
+            group_index: cache.group_index,
+            epoch: cache.group_epoch,
+        })
+    }
+
+    fn verify_group_signature(&mut self, group: &Group, message: &[u8], signature: &[u8]) -> bool {
+        let scheme = self.scheme;
+
+        self.verification_cache
+            .get_or_verify(group.index, group.epoch, message, signature, || {
+                scheme.verify(&group.public_key, message, signature)
+            })
+    }
+
+    fn refund_fee(&mut self, signature_task: SignatureTask) -> u64 {
+        // the fee may have been paid out partly if the pool ran short for other tasks
+        let refund = signature_task.fee.min(self.fee_pool);
+
+        self.fee_pool -= refund;
+
+        let balance = self
+            .requester_balances
+            .entry(signature_task.requester)
+            .or_insert(0);
+
+        *balance += refund;
+
+        refund
+    }
+
+    fn fulfill(
+        &mut self,
+        id_address: String,
+        signature_index: usize,
+        mut signature: Vec<u8>,
+        mut partial_signatures: HashMap<String, Vec<u8>>,
+    ) -> Result<(), ControllerError> {
+        // the signature has been fulfilled by another committer, so the caller can only confirm it
+        if let Some(cache) = self.signature_result_caches.get(&signature_index) {
+            let group = self.groups.get(&cache.group_index).unwrap();
+
+            if !group.committers.contains(&id_address) {
+                return Err(ControllerError::NotCommitter(id_address, group.index));
+            }
+
+            if cache.signature != signature {
+                warn!(
+                    "event=conflicting_signature signature_index={} id_address={}",
+                    signature_index, id_address
+                );
+
+                return Err(ControllerError::ConflictingSignature(signature_index));
+            }
+
+            // a majority of the committers settles the result
+            if cache.confirmations.contains(&id_address)
+                || cache.confirmations.len() >= group.committers.len() / 2 + 1
+            {
+                return Err(ControllerError::TaskAlreadyFulfilled(signature_index));
+            }
+
+            let cache = self
+                .signature_result_caches
+                .get_mut(&signature_index)
+                .unwrap();
+
+            cache.confirmations.push(id_address);
+
+            return Ok(());
+        }
+
+        let signature_task = self
+            .signature_tasks
+            .get(signature_index)
+            .ok_or(ControllerError::TaskNotFound(signature_index))?
+            .clone();
+
+        let group = self
+            .groups
+            .get(&signature_task.group_index)
+            .ok_or(ControllerError::GroupNotFound(signature_task.group_index))?
+            .clone();
+
+        // the group may have started to regroup since the task was assigned
+        if group.state != GroupState::Active {
+            return Err(ControllerError::GroupNotReady(group.index));
+        }
+
+        if !group.committers.contains(&id_address) {
+            return Err(ControllerError::NotCommitter(id_address, group.index));
+        }
+
+        // the committer has to hold a member index of the group, e.g. it can't have left it
+        if !group.members.contains_key(&id_address) || !self.nodes.contains_key(&id_address) {
+            return Err(ControllerError::NotGroupMember(id_address, group.index));
+        }
+
+        let message = &signature_task.message;
+
+        let faulty_committer = !self.verify_group_signature(&group, message, &signature);
+
+        // the task is still fulfilled if enough valid partial signatures are at hand
+        if faulty_committer {
+            let mut stored_partials = self
+                .partial_signature_caches
+                .get(&signature_index)
+                .cloned()
+                .unwrap_or_default();
+
+            // the stored partial signatures have been verified already
+            for (member_id_address, partial_signature) in partial_signatures {
+                stored_partials
+                    .entry(member_id_address)
+                    .or_insert(partial_signature);
+            }
+
+            let partials = self.trim_partials(group.index, message, &stored_partials);
+
+            signature = match self.aggregate_partials(group.index, &partials) {
+                Ok(signature) if self.verify_group_signature(&group, message, &signature) => {
+                    signature
+                }
+                _ => return Err(ControllerError::InvalidSignature(signature_index)),
+            };
+
+            partial_signatures = stored_partials;
+        }
+
+        // only the members whose partial signature verifies against their committed share get rewarded
+        let submitted_count = partial_signatures.len();
+
+        let partial_signatures = partial_signatures
+            .into_iter()
+            .filter(|(member_id_address, partial_signature)| {
+                match group.members.get(member_id_address) {
+                    Some(member) => self.scheme.partial_verify(
+                        &member.partial_public_key,
+                        member.share_index(),
+                        message,
+                        partial_signature,
+                    ),
+                    None => false,
+                }
+            })
+            .collect::<HashMap<_, _>>();
+
+        if submitted_count > 0 && partial_signatures.len() < group.threshold {
+            return Err(ControllerError::NotEnoughValidPartials(signature_index));
+        }
+
+        // all the checks passed, the state is only changed from here on so that a rejected
+        // call leaves no trace
+        let committer_address = self.nodes.get(&id_address).unwrap().id_address.clone();
+
+        // the committer of an invalid aggregated signature is recorded for slashing instead of rewarded
+        let committer_reward = if faulty_committer {
+            self.faulty_committers
+                .entry(signature_index)
+                .or_insert_with(Vec::new)
+                .push(committer_address.clone());
+
+            0
+        } else {
+            self.reward_config.committer_reward
+        };
+
+        *self.rewards.entry(committer_address.clone()).or_insert(0) += committer_reward;
+
+        for member_id_address in partial_signatures.keys() {
+            let member_reward = self.rewards.entry(member_id_address.clone()).or_insert(0);
+
+            *member_reward += self.reward_config.signature_reward;
+        }
+
+        let paid_rewards = committer_reward
+            + self.reward_config.signature_reward * partial_signatures.len() as u64;
+
+        // mock: the rewards are still paid once the fees in the pool run out
+        self.fee_pool = self.fee_pool.saturating_sub(paid_rewards);
+
+        self.last_output = self.randomness_hash.output(&signature);
+
+        self.last_signature_index = Some(signature_index);
+
+        self.last_output_groups = vec![group.index];
+
+        let signature_result_cache = SignatureResultCache {
+            group_index: group.index,
+            group_epoch: group.epoch,
+            signature,
+            // the other committers still have to confirm a replaced signature
+            confirmations: if faulty_committer {
+                vec![]
+            } else {
+                vec![committer_address.clone()]
+            },
+            fulfilled_at: self.clock.now(),
+        };
+
+        let event = SignatureReadyEvent {
+            signature_index,
+            group_index: group.index,
+            group_epoch: group.epoch,
+            signature: signature_result_cache.signature.clone(),
+        };
+
+        self.signature_result_caches
+            .insert(signature_index, signature_result_cache);
+
+        let signature_reward = SignatureReward {
+            signature_task,
+            committer: committer_address,
+            group,
+            partial_signatures,
+        };
+
+        self.verifiable_signature_rewards
+            .insert(signature_index, signature_reward);
+
+        self.signature_tasks.complete(signature_index);
+
+        self.partial_signature_caches.remove(&signature_index);
+
+        self.finalize_multigroup_request(signature_index);
+
+        info!(
+            "event=signature_fulfilled signature_index={} group_index={} epoch={} committer={} output={}",
+            signature_index, event.group_index, event.group_epoch, id_address, self.last_output
+        );
+
+        for listener in &self.signature_ready_listeners {
+            listener(&event);
+        }
+
+        Ok(())
+    }
+
+    fn calculate_hash<T: Hash>(t: &T) -> u64 {
+        let mut s = DefaultHasher::new();
+        t.hash(&mut s);
+        s.finish()
+    }
+}
+
+impl MockHelper for Controller {
+    fn emit_dkg_task(&self) -> Vec<&DKGTask> {
+        // groups which are already grouped don't need to run their DKG again
+        let mut dkg_tasks = self
+            .dkg_tasks
+            .values()
+            .filter(|dkg_task| {
+                self.groups.get(&dkg_task.group_index).unwrap().state != GroupState::Active
+            })
+            .collect::<Vec<_>>();
+
+        dkg_tasks.sort_by_key(|dkg_task| dkg_task.group_index);
+
+        dkg_tasks
+    }
+
+    fn emit_signature_task(&mut self) -> Option<SignatureTask> {
+        self.signature_tasks.pop()
+    }
+
+    fn mine(&mut self, block_number: usize) {
+        self.block_height += block_number;
+    }
+
+    fn tick(&mut self, block_height: usize) -> TickReport {
+        self.block_height = max(self.block_height, block_height);
+
+        let mut report = TickReport::default();
+
+        let mut overdue_group_indices = self
+            .dkg_tasks
+            .values()
+            .filter(|dkg_task| self.block_height > dkg_task.deadline_block_height)
+            .map(|dkg_task| dkg_task.group_index)
+            .collect::<Vec<_>>();
+
+        overdue_group_indices.sort();
+
+        for group_index in overdue_group_indices {
+            // a DKG which reached its public key in time is only finalized
+            match self.retry_dkg(group_index, true) {
+                Ok(_) => report.retried_dkgs.push(group_index),
+                Err(_) if self.groups[&group_index].state == GroupState::Disabled => {
+                    report.disabled_groups.push(group_index)
+                }
+                Err(_) => {}
+            }
+        }
+
+        let overdue_signature_tasks = self
+            .signature_tasks
+            .pending()
+            .iter()
+            .filter(|signature_task| self.block_height > signature_task.deadline_block_height)
+            .map(|signature_task| (signature_task.index, signature_task.rotation_count))
+            .collect::<Vec<_>>();
+
+        for (signature_index, rotation_count) in overdue_signature_tasks {
+            if rotation_count < MAX_SIGNATURE_TASK_ROTATIONS {
+                if self.rotate_signature_committers(signature_index).is_ok() {
+                    report.rotated_tasks.push(signature_index);
+                }
+            } else if self.cancel_signature_task(signature_index).is_ok() {
+                report.refunded_tasks.push(signature_index);
+            }
+        }
+
+        report
+    }
+}
+
+impl Transactions for Controller {
+    fn node_register(
+        &mut self,
+        id_address: String,
+        id_public_key: Vec<u8>,
+        rpc_endpoint: String,
+        reward_address: String,
+        proof_of_possession: Vec<u8>,
+    ) -> Result<usize, ControllerError> {
+        self.register_node(
+            id_address,
+            id_public_key,
+            rpc_endpoint,
+            reward_address,
+            proof_of_possession,
+            DEFAULT_STAKING,
+        )
+    }
+
+    fn register_from_config(
+        &mut self,
+        nodes: Vec<NodeConfig>,
+    ) -> Vec<Result<usize, ControllerError>> {
+        nodes
+            .into_iter()
+            .map(|node| {
+                let id_public_key = decode_hex(&node.public_key_hex).ok_or_else(|| {
+                    ControllerError::InvalidNodeConfig(
+                        node.id_address.clone(),
+                        String::from("public_key_hex"),
+                    )
+                })?;
+
+                let proof_of_possession =
+                    decode_hex(&node.proof_of_possession_hex).ok_or_else(|| {
+                        ControllerError::InvalidNodeConfig(
+                            node.id_address.clone(),
+                            String::from("proof_of_possession_hex"),
+                        )
+                    })?;
+
+                self.register_node(
+                    node.id_address.clone(),
+                    id_public_key,
+                    node.rpc_endpoint,
+                    node.id_address,
+                    proof_of_possession,
+                    node.stake,
+                )
+            })
+            .collect()
+    }
+
+    fn node_deregister(&mut self, id_address: String) -> DeregisterResult {
+        if self.nodes.remove(&id_address).is_none() {
+            return DeregisterResult::NodeNotFound;
+        }
+
+        let group_index = match self
+            .groups
+            .values()
+            .find(|g| g.members.contains_key(&id_address))
+        {
+            Some(group) => group.index,
+            None => return DeregisterResult::Removed,
+        };
+
+        let group = self.groups.get_mut(&group_index).unwrap();
+
+        group.members.remove(&id_address);
+
+        group.commit_cache.remove(&id_address);
+
+        group.size -= 1;
+
+        fit_committer_count(group);
+
+        if group.size < group.threshold {
+            // the remaining members can't sign anymore, so they have to regroup
+            group.state = if group.pubkey_history.is_empty() {
+                GroupState::Registering
+            } else {
+                GroupState::Disabled
+            };
+
+            group.committers.clear();
+
+            group.commit_cache.clear();
+
+            group.dissenters.clear();
+
+            group.threshold = max(DEFAULT_MINIMUM_THRESHOLD, minimum_threshold(group.size));
+
+            if group.size >= self.minimum_group_size {
+                self.create_dkg_task(group_index);
+            }
+
+            self.reassign_requests(group_index);
+
+            return DeregisterResult::RegroupRequired;
+        }
+
+        if group.committers.contains(&id_address) {
+            // the committer count was fitted to the members left
+            self.select_committers(group_index).unwrap();
+        }
+
+        DeregisterResult::Removed
+    }
+
+    fn node_quit(&mut self, _id_address: String) {
+        todo!()
+    }
+
+    fn node_activate(&mut self, _id_address: String) {
+        todo!()
+    }
+
+    fn redeem(&mut self, _id_address: String) {
+        todo!()
+    }
+
+    fn claim(&mut self, _id_address: String) {
+        todo!()
+    }
+
+    fn commit_dkg(
+        &mut self,
+        id_address: String,
+        group_index: usize,
+        group_epoch: usize,
+        public_poly: Vec<u8>,
+        partial_public_key: Vec<u8>,
+        disqualified_nodes: Vec<String>,
+    ) -> Result<(), ControllerError> {
+        let group = self
+            .groups
+            .get_mut(&group_index)
+            .ok_or(ControllerError::GroupNotFound(group_index))?;
+
+        if !self.nodes.contains_key(&id_address) {
+            return Err(ControllerError::NodeNotRegistered(id_address));
+        }
+
+        if !group.members.contains_key(&id_address) {
+            return Err(ControllerError::NotGroupMember(id_address, group_index));
+        }
+
+        if group.epoch != group_epoch {
+            return Err(ControllerError::EpochMismatch(
+                group_index,
+                group.epoch,
+                group_epoch,
+            ));
+        }
+
+        // commits after the deadline of the DKG task are too late to be accepted
+        if let Some(dkg_task) = self.dkg_tasks.get(&group_index) {
+            if dkg_task.epoch == group_epoch && self.block_height > dkg_task.deadline_block_height {
+                return Err(ControllerError::DKGDeadlinePassed(group_index));
+            }
+        }
+
+        // the keys come from outside, malformed ones are rejected before being stored
+        let degree = match self.scheme.check_public_poly(&public_poly) {
+            Ok(degree) => degree,
+            Err(err) => {
+                return Err(ControllerError::DeserializationError(
+                    String::from("public polynomial"),
+                    id_address,
+                    err,
+                ))
+            }
+        };
+
+        // any other degree changes the number of shares it takes to sign
+        if degree + 1 != group.threshold {
+            return Err(ControllerError::WrongPolynomialDegree(
+                id_address,
+                degree,
+                group_index,
+                group.threshold - 1,
+            ));
+        }
+
+        let public_key = self.scheme.public_key_of_poly(&public_poly).unwrap();
+
+        // an empty partial public key means the node doesn't disclose its share
+        if !partial_public_key.is_empty() {
+            if let Err(err) = self.scheme.check_public_key(&partial_public_key) {
+                return Err(ControllerError::DeserializationError(
+                    String::from("partial public key"),
+                    id_address,
+                    err,
+                ));
+            }
+        }
+
+        let commit_result = CommitResult {
+            group_epoch,
+            public_key,
+            public_poly,
+            disqualified_nodes,
+        };
+
+        let commit_cache = CommitCache {
+            commit_result,
+            partial_public_key: partial_public_key.clone(),
+        };
+
+        // TODO when next group epoch increments, clean commit_cache, committers
+        if group.commit_cache.contains_key(&id_address) {
+            return Err(ControllerError::AlreadyCommitted(id_address, group_index));
+        }
+
+        group.commit_cache.insert(id_address.clone(), commit_cache);
+
+        info!(
+            "event=dkg_committed id_address={} group_index={} epoch={}",
+            id_address, group_index, group_epoch
+        );
+
+        enum Majority {
+            Reached(CommitCache),
+            Pending,
+            // no result can reach the threshold even if all the remaining members agree with it
+            Impossible,
+        }
+
+        fn get_identical_over_threshold_commitment(
+            controller: &Controller,
+            group_index: usize,
+        ) -> Majority {
+            let group = controller.groups.get(&group_index).unwrap();
+
+            let mut map: HashMap<u64, usize> = HashMap::new();
+
+            for commit_cache in group.commit_cache.values() {
+                let count = map
+                    .entry(Controller::calculate_hash(&commit_cache.commit_result))
+                    .or_insert(0);
+
+                *count += 1;
+
+                if *count >= group.threshold {
+                    return Majority::Reached(commit_cache.clone());
+                }
+            }
+
+            let max_count = map.values().max().copied().unwrap_or(0);
+
+            let remaining = group.size.saturating_sub(group.commit_cache.len());
+
+            if max_count + remaining < group.threshold {
+                return Majority::Impossible;
+            }
+
+            Majority::Pending
+        }
+
+        if group.state == GroupState::Active {
+            // a public polynomial which disagrees with the finalized one is flagged
+            if group.commit_cache[&id_address].commit_result.public_poly != group.public_poly {
+                group.dissenters.push(id_address.clone());
+            } else if !is_consistent_partial_key(&group.members[&id_address], &partial_public_key) {
+                disqualify_inconsistent_member(group, &id_address);
+
+                // the member may have been one of the committers
+                self.select_committers(group_index)?;
+            }
+        } else {
+            group.state = GroupState::DkgInProgress;
+
+            match get_identical_over_threshold_commitment(self, group_index) {
+                Majority::Pending => {}
+                // the commit stays recorded, so that its node counts as a participant, and the
+                // DKG fails once its deadline passes
+                Majority::Impossible => {
+                    warn!(
+                        "event=no_majority_public_key group_index={} epoch={} commits={}",
+                        group_index,
+                        group_epoch,
+                        self.groups[&group_index].commit_cache.len()
+                    );
+                }
+                Majority::Reached(commit_cache) => {
+                    let group = self.groups.get_mut(&group_index).unwrap();
+
+                    group.state = GroupState::Active;
+
+                    group.dkg_retries = 0;
+
+                    group.size -= commit_cache.commit_result.disqualified_nodes.len();
+
+                    group.public_key = commit_cache.commit_result.public_key.clone();
+
+                    group.public_poly = commit_cache.commit_result.public_poly.clone();
+
+                    group
+                        .pubkey_history
+                        .push((group.epoch, group.public_key.clone()));
+
+                    // the members in the minority committed a different public polynomial
+                    let mut dissenters = group
+                        .commit_cache
+                        .iter()
+                        .filter(|(_, cache)| cache.commit_result.public_poly != group.public_poly)
+                        .map(|(id_address, _)| id_address.clone())
+                        .collect::<Vec<_>>();
+
+                    dissenters.sort();
+
+                    group.dissenters = dissenters;
+
+                    commit_cache
+                        .commit_result
+                        .disqualified_nodes
+                        .iter()
+                        .for_each(|disqualified_id_address| {
+                            group.members.remove(disqualified_id_address);
+                        });
+
+                    // the public keys of the shares are evaluated from the polynomial, so any
+                    // threshold of them interpolates to the public key of the group
+                    let scheme = self.scheme;
+
+                    let evaluated_poly = self
+                        .evaluated_polys
+                        .entry(group_index)
+                        .or_insert_with(|| EvaluatedPoly::new(scheme, group.public_poly.clone()));
+
+                    evaluated_poly.update(&group.public_poly);
+
+                    for member in group.members.values_mut() {
+                        member.partial_public_key =
+                            evaluated_poly.eval(member.share_index()).unwrap();
+                    }
+
+                    // the members which agree on the polynomial but committed another public
+                    // key for their share are disqualified
+                    let mut inconsistent = group
+                        .commit_cache
+                        .iter()
+                        .filter(|(_, cache)| cache.commit_result.public_poly == group.public_poly)
+                        .filter(|(id_address, cache)| match group.members.get(*id_address) {
+                            Some(member) => {
+                                !is_consistent_partial_key(member, &cache.partial_public_key)
+                            }
+                            None => false,
+                        })
+                        .map(|(id_address, _)| id_address.clone())
+                        .collect::<Vec<_>>();
+
+                    inconsistent.sort();
+
+                    for id_address in inconsistent.iter() {
+                        disqualify_inconsistent_member(group, id_address);
+                    }
+
+                    group.dissenters.sort();
+
+                    // the group may have activated with fewer members than its committer count
+                    fit_committer_count(group);
+
+                    self.select_committers(group_index)?;
+
+                    let group = self.groups.get(&group_index).unwrap();
+
+                    info!(
+                        "event=group_activated group_index={} epoch={} public_key={} committers={}",
+                        group_index,
+                        group.epoch,
+                        hex_bytes(&group.public_key),
+                        group.committers.join(",")
+                    );
+
+                    let mut members = group.members.values().collect::<Vec<_>>();
+
+                    members.sort_by_key(|member| member.index);
+
+                    let event = GroupFinalizedEvent {
+                        group_index,
+                        epoch: group.epoch,
+                        public_key: group.public_key.clone(),
+                        members: members
+                            .into_iter()
+                            .map(|member| member.id_address.clone())
+                            .collect(),
+                        committers: group.committers.clone(),
+                    };
+
+                    for listener in &self.group_finalized_listeners {
+                        listener(&event);
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn finalize_dkg(&mut self, group_index: usize, group_epoch: usize) -> bool {
+        let dkg_task = match self.dkg_tasks.get(&group_index) {
+            Some(dkg_task) if dkg_task.epoch == group_epoch => dkg_task.clone(),
+            _ => return false,
+        };
+
+        if self.block_height <= dkg_task.deadline_block_height {
+            return false;
+        }
+
+        let group = self.groups.get(&group_index).unwrap();
+
+        let mut participants = vec![];
+
+        let mut slashed_nodes = vec![];
+
+        for (_, id_address) in dkg_task.members.iter() {
+            if !self.nodes.contains_key(id_address) {
+                continue;
+            }
+
+            match group.commit_cache.get(id_address) {
+                // committing a result which disagrees with the majority is as bad as not committing
+                Some(_)
+                    if group.state != GroupState::Active
+                        || !group.dissenters.contains(id_address) =>
+                {
+                    participants.push(id_address.clone());
+                }
+                _ => slashed_nodes.push(id_address.clone()),
+            }
+        }
+
+        for id_address in participants.iter() {
+            let node = self.nodes.get_mut(id_address).unwrap();
+
+            node.participation_count += 1;
+        }
+
+        slashed_nodes.sort();
+
+        for id_address in slashed_nodes.iter() {
+            self.slash_node(id_address, DKG_PENALTY);
+        }
+
+        let group = self.groups.get_mut(&group_index).unwrap();
+
+        // no public key reached the threshold in time
+        if group.state != GroupState::Active {
+            group.state = GroupState::Disabled;
+        }
+
+        self.dkg_tasks.remove(&group_index);
+
+        true
+    }
+
+    fn retry_dkg(
+        &mut self,
+        group_index: usize,
+        drop_failed_members: bool,
+    ) -> Result<DKGTask, ControllerError> {
+        if let Some(epoch) = self.dkg_tasks.get(&group_index).map(|task| task.epoch) {
+            self.finalize_dkg(group_index, epoch);
+        }
+
+        let group = self
+            .groups
+            .get_mut(&group_index)
+            .ok_or(ControllerError::GroupNotFound(group_index))?;
+
+        // groups emptied or shrunk below the minimum size are disabled as well, but can't retry
+        if group.state != GroupState::Disabled || group.size < self.minimum_group_size {
+            return Err(ControllerError::DKGNotFailed(group_index));
+        }
+
+        if group.dkg_retries >= MAX_DKG_RETRIES {
+            return Err(ControllerError::DKGRetryLimitReached(group_index));
+        }
+
+        if drop_failed_members {
+            let remaining = group.size - group.slashed_members.len();
+
+            if remaining < self.minimum_group_size {
+                return Err(ControllerError::NotEnoughMembers(group_index, remaining));
+            }
+
+            for id_address in group.slashed_members.iter() {
+                group.members.remove(id_address);
+            }
+
+            group.size = group.members.len();
+
+            group.threshold = max(DEFAULT_MINIMUM_THRESHOLD, minimum_threshold(group.size));
+        }
+
+        group.dkg_retries += 1;
+
+        let dkg_retries = group.dkg_retries;
+
+        self.create_dkg_task(group_index);
+
+        let dkg_task = self.dkg_tasks.get_mut(&group_index).unwrap();
+
+        dkg_task.deadline_block_height = self.block_height + (DKG_DEADLINE_BLOCKS << dkg_retries);
+
+        Ok(dkg_task.clone())
+    }
+
+    fn set_committer_count(
+        &mut self,
+        group_index: usize,
+        committer_count: usize,
+    ) -> Result<(), ControllerError> {
+        let group = self
+            .groups
+            .get_mut(&group_index)
+            .ok_or(ControllerError::GroupNotFound(group_index))?;
+
+        if committer_count == 0 || committer_count > group.size {
+            return Err(ControllerError::InvalidCommitterCount(
+                committer_count,
+                group.size,
+            ));
+        }
+
+        group.committer_count = committer_count;
+
+        if group.state == GroupState::Active {
+            self.select_committers(group_index)?;
+        }
+
+        Ok(())
+    }
+
+    fn request(
+        &mut self,
+        requester: String,
+        seed: String,
+        fee: u64,
+        priority: u8,
+    ) -> Result<usize, ControllerError> {
+        self.request_bytes(requester, seed.into_bytes(), fee, priority)
+    }
+
+    fn request_bytes(
+        &mut self,
+        requester: String,
+        seed: Vec<u8>,
+        fee: u64,
+        priority: u8,
+    ) -> Result<usize, ControllerError> {
+        let assignment_group_index = self.choose_group(&[])?;
+
+        let signature_index =
+            self.create_signature_task(requester, fee, seed, assignment_group_index, priority);
+
+        self.last_group_index = assignment_group_index;
+
+        Ok(signature_index)
+    }
+
+    fn request_from_group(
+        &mut self,
+        requester: String,
+        seed: String,
+        fee: u64,
+        group_index: usize,
+    ) -> Result<usize, ControllerError> {
+        let group = self.get_group(group_index)?;
+
+        if group.state != GroupState::Active {
+            return Err(ControllerError::GroupNotReady(group_index));
+        }
+
+        self.check_signers(group_index)?;
+
+        // the round-robin assignment of the other requests is not affected
+        Ok(self.create_signature_task(
+            requester,
+            fee,
+            seed.into_bytes(),
+            group_index,
+            DEFAULT_PRIORITY,
+        ))
+    }
+
+    fn request_multigroup(
+        &mut self,
+        requester: String,
+        seed: String,
+        fee: u64,
+        group_indices: Vec<usize>,
+    ) -> Result<usize, ControllerError> {
+        if group_indices.len() < 2 {
+            return Err(ControllerError::NotEnoughGroups(group_indices.len()));
+        }
+
+        for (i, group_index) in group_indices.iter().enumerate() {
+            if group_indices[..i].contains(group_index) {
+                return Err(ControllerError::DuplicateGroup(*group_index));
+            }
+
+            if self.get_group(*group_index)?.state != GroupState::Active {
+                return Err(ControllerError::GroupNotReady(*group_index));
+            }
+
+            self.check_signers(*group_index)?;
+        }
+
+        self.signature_count += 1;
+
+        // mock: payment for request
+        self.fee_pool += fee;
+
+        let seed = seed.into_bytes();
+
+        let previous_output = self.last_output;
+
+        let (message, nonce) = self.next_request_message(&requester, &seed);
+
+        // the first group also gets the remainder of the fee
+        let group_fee = fee / group_indices.len() as u64;
+
+        let signature_indices = group_indices
+            .iter()
+            .enumerate()
+            .map(|(i, group_index)| {
+                let fee = if i == 0 {
+                    fee - group_fee * (group_indices.len() as u64 - 1)
+                } else {
+                    group_fee
+                };
+
+                self.signature_tasks.push(
+                    requester.clone(),
+                    fee,
+                    seed.clone(),
+                    previous_output,
+                    message.clone(),
+                    nonce,
+                    *group_index,
+                    self.block_height,
+                    DEFAULT_PRIORITY,
+                    self.clock.now(),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        self.last_multigroup_index += 1;
+
+        for (signature_index, group_index) in signature_indices.iter().zip(group_indices.iter()) {
+            info!(
+                "event=randomness_requested signature_index={} requester={} group_index={} total_fee={} priority={} multigroup_index={}",
+                signature_index, requester, group_index, fee, DEFAULT_PRIORITY, self.last_multigroup_index
+            );
+        }
+
+        let request = MultiGroupRequest {
+            index: self.last_multigroup_index,
+            requester,
+            message,
+            group_indices,
+            signature_indices,
+            output: None,
+        };
+
+        self.multigroup_requests.insert(request.index, request);
+
+        Ok(self.last_multigroup_index)
+    }
+
+    fn submit_partial(
+        &mut self,
+        signature_index: usize,
+        id_address: String,
+        partial_signature: Vec<u8>,
+    ) -> Result<bool, ControllerError> {
+        if self.signature_result_caches.contains_key(&signature_index) {
+            return Err(ControllerError::TaskAlreadyFulfilled(signature_index));
+        }
+
+        let signature_task = self
+            .signature_tasks
+            .get(signature_index)
+            .ok_or(ControllerError::TaskNotFound(signature_index))?;
+
+        let group = self
+            .groups
+            .get(&signature_task.group_index)
+            .ok_or(ControllerError::GroupNotFound(signature_task.group_index))?;
+
+        if group.state != GroupState::Active {
+            return Err(ControllerError::GroupNotReady(group.index));
+        }
+
+        let member = group
+            .members
+            .get(&id_address)
+            .ok_or_else(|| ControllerError::NotGroupMember(id_address.clone(), group.index))?;
+
+        let partial_signatures = self
+            .partial_signature_caches
+            .entry(signature_index)
+            .or_insert_with(HashMap::new);
+
+        if partial_signatures.contains_key(&id_address) {
+            return Err(ControllerError::PartialAlreadySubmitted(
+                id_address,
+                signature_index,
+            ));
+        }
+
+        if !self.scheme.partial_verify(
+            &member.partial_public_key,
+            member.share_index(),
+            &signature_task.message,
+            &partial_signature,
+        ) {
+            return Err(ControllerError::InvalidPartialSignature(
+                id_address,
+                signature_index,
+            ));
+        }
+
+        partial_signatures.insert(id_address.clone(), partial_signature);
+
+        debug!(
+            "event=partial_signature_submitted signature_index={} id_address={} count={} threshold={}",
+            signature_index,
+            id_address,
+            partial_signatures.len(),
+            group.threshold
+        );
+
+        if partial_signatures.len() < group.threshold {
+            return Ok(false);
+        }
+
+        let partials = self.trim_partials(
+            group.index,
+            &signature_task.message,
+            &self.partial_signature_caches[&signature_index],
+        );
+
+        let signature = match self.aggregate_partials(group.index, &partials) {
+            Ok(signature) => signature,
+            // some of the cached partial signatures don't count anymore, e.g. those of a
+            // member which left the group, so the task waits for more of them
+            Err(ControllerError::NotEnoughPartials(_, _)) => return Ok(false),
+            Err(err) => return Err(err),
+        };
+
+        let group = group.clone();
+
+        let message = signature_task.message.clone();
+
+        // the share keys are evaluated from the public polynomial, so the aggregate of
+        // partials which verify against them verifies as well
+        if !self.verify_group_signature(&group, &message, &signature) {
+            return Err(ControllerError::InvalidAggregatedSignature(group.index));
+        }
+
+        // the aggregation is settled on behalf of the first committer of the group
+        let committer = group
+            .committers
+            .first()
+            .cloned()
+            .ok_or(ControllerError::NoCommitter(group.index))?;
+
+        // the cache is only dropped by a successful fulfillment, so a failed one loses none
+        // of the partials
+        let partial_signatures = self.partial_signature_caches[&signature_index].clone();
+
+        self.fulfill(committer, signature_index, signature, partial_signatures)?;
+
+        Ok(true)
+    }
+
+    fn submit_signature(
+        &mut self,
+        id_address: String,
+        signature_index: usize,
+        signature: Vec<u8>,
+        partial_signatures: HashMap<String, Vec<u8>>,
+    ) -> Result<(), ControllerError> {
+        self.fulfill(id_address, signature_index, signature, partial_signatures)
+    }
+
+    fn rotate_signature_committers(
+        &mut self,
+        signature_index: usize,
+    ) -> Result<Vec<String>, ControllerError> {
+        let signature_task = self
+            .signature_tasks
+            .get(signature_index)
+            .ok_or(ControllerError::TaskNotFound(signature_index))?
+            .clone();
+
+        if self.block_height <= signature_task.deadline_block_height {
+            return Err(ControllerError::SignatureDeadlineNotPassed(signature_index));
+        }
+
+        if signature_task.rotation_count >= MAX_SIGNATURE_TASK_ROTATIONS {
+            return Err(ControllerError::RotationLimitReached(signature_index));
+        }
+
+        let group = self
+            .groups
+            .get_mut(&signature_task.group_index)
+            .ok_or(ControllerError::GroupNotFound(signature_task.group_index))?;
+
+        if group.state != GroupState::Active {
+            return Err(ControllerError::GroupNotReady(group.index));
+        }
+
+        let previous_committers = std::mem::take(&mut group.committers);
+
+        let mut members = group
+            .members
+            .values()
+            .filter(|member| !previous_committers.contains(&member.id_address))
+            .collect::<Vec<_>>();
+
+        // every member has let a task stall already, so anyone can be picked again
+        if members.is_empty() {
+            members = group.members.values().collect::<Vec<_>>();
+        }
+
+        // the new committers depend on the task, so that each rotation picks a different subset
+        let salt = (signature_index as u64).to_be_bytes();
+
+        group.committers = rank_members(&group.public_key, &salt, members)
+            .into_iter()
+            .take(group.committer_count)
+            .map(|member| member.id_address.clone())
+            .collect::<Vec<_>>();
+
+        let committers = group.committers.clone();
+
+        // the previous committers are kept so that they can be slashed later
+        self.stalled_committers
+            .entry(signature_index)
+            .or_insert_with(Vec::new)
+            .extend(previous_committers);
+
+        self.signature_tasks.reassign(
+            signature_index,
+            self.block_height + SIGNATURE_TASK_DEADLINE_BLOCKS,
+        );
+
+        Ok(committers)
+    }
+
+    fn reassign_request(&mut self, signature_index: usize) -> Result<usize, ControllerError> {
+        let previous_group_index = self
+            .signature_tasks
+            .get(signature_index)
+            .ok_or(ControllerError::TaskNotFound(signature_index))?
+            .group_index;
+
+        if self.get_group(previous_group_index)?.state == GroupState::Active {
+            return Err(ControllerError::GroupStillActive(previous_group_index));
+        }
+
+        let multigroup_index = self
+            .multigroup_requests
+            .values()
+            .find(|request| request.signature_indices.contains(&signature_index))
+            .map(|request| request.index);
+
+        // a group signs a multi-group request only once
+        let excluded = match multigroup_index {
+            Some(index) => self.multigroup_requests[&index].group_indices.clone(),
+            None => vec![],
+        };
+
+        let group_index = self.choose_group(&excluded)?;
+
+        self.signature_tasks
+            .migrate(signature_index, group_index, self.block_height);
+
+        // the partial signatures of the previous group don't verify against the new one
+        self.partial_signature_caches.remove(&signature_index);
+
+        if let Some(index) = multigroup_index {
+            let request = self.multigroup_requests.get_mut(&index).unwrap();
+
+            for request_group_index in request.group_indices.iter_mut() {
+                if *request_group_index == previous_group_index {
+                    *request_group_index = group_index;
+                }
+            }
+        }
+
+        self.last_group_index = group_index;
+
+        info!(
+            "event=request_reassigned signature_index={} previous_group_index={} group_index={}",
+            signature_index, previous_group_index, group_index
+        );
+
+        Ok(group_index)
+    }
+
+    fn cancel_signature_task(&mut self, signature_index: usize) -> Result<u64, ControllerError> {
+        let signature_task = self
+            .signature_tasks
+            .get(signature_index)
+            .ok_or(ControllerError::TaskNotFound(signature_index))?;
+
+        if self.block_height <= signature_task.deadline_block_height {
+            return Err(ControllerError::SignatureDeadlineNotPassed(signature_index));
+        }
+
+        if signature_task.rotation_count < MAX_SIGNATURE_TASK_ROTATIONS {
+            return Err(ControllerError::RotationsNotExhausted(signature_index));
+        }
+
+        let signature_task = self.signature_tasks.cancel(signature_index).unwrap();
+
+        Ok(self.refund_fee(signature_task))
+    }
+
+    fn cancel_request(
+        &mut self,
+        requester: String,
+        signature_index: usize,
+    ) -> Result<u64, ControllerError> {
+        if self.signature_result_caches.contains_key(&signature_index) {
+            return Err(ControllerError::TaskAlreadyFulfilled(signature_index));
+        }
+
+        let signature_task = self
+            .signature_tasks
+            .get(signature_index)
+            .ok_or(ControllerError::TaskNotFound(signature_index))?;
+
+        if signature_task.requester != requester {
+            return Err(ControllerError::NotRequester(requester, signature_index));
+        }
+
+        // the committers may already be working on an emitted task
+        if self.signature_tasks.is_assigned(signature_index) {
+            return Err(ControllerError::TaskAlreadyAssigned(signature_index));
+        }
+
+        let signature_task = self.signature_tasks.cancel(signature_index).unwrap();
+
+        Ok(self.refund_fee(signature_task))
+    }
+
+    fn challenge_reward(&mut self, id_address: String, signature_index: usize) -> bool {
+        if !self
+            .verifiable_signature_rewards
+            .contains_key(&signature_index)
+        {
+            return false;
+        }
+
+        let signature_reward = self
+            .verifiable_signature_rewards
+            .get(&signature_index)
+            .unwrap();
+
+        let group = &signature_reward.group;
+
+        let committer = self.nodes.get(&signature_reward.committer).unwrap();
+
+        let committer_address = &committer.id_address.clone();
+
+        let message = &signature_reward.signature_task.message;
+
+        // TODO need a BLS-Aggregation Verification instead of loop to save computational fee
+        for (member_id_address, partial_signature) in signature_reward.partial_signatures.iter() {
+            let member = group.members.get(member_id_address).unwrap();
+
+            if !self.scheme.partial_verify(
+                &member.partial_public_key,
+                member.share_index(),
+                message,
+                partial_signature,
+            ) {
+                self.slash_node(committer_address, COMMITTER_PENALTY_PER_SIGNATURE);
+
+                self.freeze_node(committer_address, 0);
+
+                if !self.rewards.contains_key(&id_address) {
+                    self.rewards.insert(id_address.clone(), 0);
+                }
+
+                let challenger_reward = self.rewards.get_mut(&id_address).unwrap();
+
+                *challenger_reward += CHALLENGE_REWARD_PER_SIGNATURE;
+
+                self.verifiable_signature_rewards.remove(&signature_index);
+
+                return true;
+            }
+        }
+
+        self.verifiable_signature_rewards.remove(&signature_index);
+
+        false
+    }
+}
+
+impl Views for Controller {
+    fn get_last_output(&self) -> u64 {
+        self.last_output
+    }
+
+    fn get_last_output_transformed<T: RandomnessTransform>(&self, transform: &T) -> T::Output {
+        let entropy = match self.get_last_output_with_proof() {
+            Some(proof) => proof.randomness,
+            None => {
+                let mut entropy = [0; 32];
+
+                entropy.copy_from_slice(&Sha256::digest(&self.last_output.to_be_bytes()));
+
+                entropy
+            }
+        };
+
+        transform.apply(&entropy)
+    }
+
+    fn get_last_output_in_range(&self, min: u128, max: u128) -> u128 {
+        self.get_last_output_transformed(&RangeTransform::new(min, max))
+    }
+
+    fn get_last_output_with_proof(&self) -> Option<RandomnessProof> {
+        self.output_proof(self.last_signature_index?).ok()
+    }
+
+    fn get_outputs(
+        &self,
+        from_index: usize,
+        to_index: usize,
+    ) -> Result<Vec<(u64, RandomnessProof)>, ControllerError> {
+        (from_index..=to_index)
+            .map(|index| Ok((self.get_randomness(index)?, self.output_proof(index)?)))
+            .collect()
+    }
+
+    fn get_last_output_json(&self) -> Option<serde_json::Value> {
+        self.get_request_json(self.last_signature_index?).ok()
+    }
+
+    fn get_request_json(&self, index: usize) -> Result<serde_json::Value, ControllerError> {
+        let cache = self.signature_result_caches.get(&index);
+
+        let signature_task = match cache {
+            Some(_) => self.signature_tasks.get_fulfilled(index),
+            None => self.signature_tasks.get(index),
+        }
+        .ok_or(ControllerError::TaskNotFound(index))?;
+
+        let mut json = serde_json::json!({
+            "index": signature_task.index,
+            "requester": signature_task.requester,
+            "seed": hex_bytes(&signature_task.seed),
+            "message": hex_bytes(&signature_task.message),
+            "group_index": signature_task.group_index,
+            "requested_at": format_timestamp(signature_task.requested_at),
+            "priority": signature_task.priority,
+            "fulfilled": cache.is_some(),
+        });
+
+        if let Some(cache) = cache {
+            let group_pubkey = self.get_group_pubkey_at(cache.group_index, cache.group_epoch)?;
+
+            json["epoch"] = cache.group_epoch.into();
+            json["randomness"] =
+                hex_bytes(&self.randomness_hash.randomness(&cache.signature)).into();
+            json["randomness_hash"] = serde_json::to_value(self.randomness_hash).unwrap();
+            json["signature"] = hex_bytes(&cache.signature).into();
+            json["group_pubkey"] = hex_bytes(group_pubkey).into();
+            json["fulfilled_at"] = format_timestamp(cache.fulfilled_at).into();
+        }
+
+        Ok(json)
+    }
+
+    fn get_last_output_groups(&self) -> &[usize] {
+        &self.last_output_groups
+    }
+
+    fn get_multigroup_request(&self, index: usize) -> Result<&MultiGroupRequest, ControllerError> {
+        self.multigroup_requests
+            .get(&index)
+            .ok_or(ControllerError::MultiGroupRequestNotFound(index))
+    }
+
+    fn get_node(&self, id_address: String) -> &Node {
+        self.nodes.get(&id_address).unwrap()
+    }
+
+    fn get_node_endpoint(&self, id_address: String) -> Result<SocketAddr, ControllerError> {
+        self.nodes
+            .get(&id_address)
+            .map(|node| node.rpc_endpoint)
+            .ok_or(ControllerError::NodeNotRegistered(id_address))
+    }
+
+    fn get_group(&self, index: usize) -> Result<&Group, ControllerError> {
+        self.groups
+            .get(&index)
+            .ok_or(ControllerError::GroupNotFound(index))
+    }
+
+    fn get_group_stake(&self, group_index: usize) -> Result<usize, ControllerError> {
+        let group = self.get_group(group_index)?;
+
+        Ok(group
+            .members
+            .keys()
+            .filter_map(|id_address| self.nodes.get(id_address))
+            .map(|node| node.staking)
+            .sum())
+    }
+
+    fn get_group_address(&self, group_index: usize) -> Result<[u8; 20], ControllerError> {
+        let group = self.get_group(group_index)?;
+
+        if group.public_key.is_empty() {
+            return Err(ControllerError::GroupNotReady(group_index));
+        }
+
+        Ok(group_address_from_pubkey(&group.public_key))
+    }
+
+    fn get_group_snapshot(&self, index: usize) -> Result<GroupSnapshot, ControllerError> {
+        let group = self.get_group(index)?;
+
+        let mut members = group
+            .members
+            .values()
+            .map(|member| MemberSnapshot {
+                index: member.index,
+                id_address: member.id_address.clone(),
+                id_public_key: self
+                    .nodes
+                    .get(&member.id_address)
+                    .map(|node| hex::encode(&node.id_public_key))
+                    .unwrap_or_default(),
+                partial_public_key: hex::encode(&member.partial_public_key),
+            })
+            .collect::<Vec<_>>();
+
+        members.sort_by_key(|member| member.index);
+
+        Ok(GroupSnapshot {
+            index: group.index,
+            epoch: group.epoch,
+            size: group.size,
+            threshold: group.threshold,
+            state: group.state,
+            public_key: hex::encode(&group.public_key),
+            members,
+            committers: group.committers.clone(),
+        })
+    }
+
+    fn health_report(&self) -> HealthReport {
+        let count_groups = |state: GroupState| {
+            self.groups
+                .values()
+                .filter(|group| group.state == state)
+                .count()
+        };
+
+        let mut slashed = self.slashed.iter().collect::<Vec<_>>();
+
+        slashed.sort();
+
+        slashed.dedup();
+
+        HealthReport {
+            node_count: self.nodes.len(),
+            active_group_count: count_groups(GroupState::Active),
+            disabled_group_count: count_groups(GroupState::Disabled),
+            pending_request_count: self.signature_tasks.pending().len(),
+            fulfilled_request_count: self.signature_tasks.fulfilled().len(),
+            slashed_node_count: slashed.len(),
+            last_signature_index: self.last_signature_index,
+        }
+    }
+
+    fn get_group_pubkey_at(
+        &self,
+        group_index: usize,
+        epoch: usize,
+    ) -> Result<&[u8], ControllerError> {
+        self.get_group(group_index)?
+            .pubkey_history
+            .iter()
+            .find(|(key_epoch, _)| *key_epoch == epoch)
+            .map(|(_, public_key)| public_key.as_slice())
+            .ok_or(ControllerError::PublicKeyNotFound(group_index, epoch))
+    }
+
+    fn get_member_public_shares(
+        &self,
+        group_index: usize,
+    ) -> Result<Vec<(Idx, Vec<u8>)>, ControllerError> {
+        let group = self.get_group(group_index)?;
+
+        if group.state != GroupState::Active {
+            return Err(ControllerError::GroupNotReady(group_index));
+        }
+
+        let mut public_shares = group
+            .members
+            .values()
+            .map(|member| {
+                let idx = member.share_index();
+
+                // the evaluations aren't cached anymore after `load`
+                let public_share = match self
+                    .evaluated_polys
+                    .get(&group_index)
+                    .and_then(|evaluated_poly| evaluated_poly.get(idx))
+                {
+                    Some(public_share) => public_share.to_vec(),
+                    None => self
+                        .scheme
+                        .eval_public_poly(&group.public_poly, idx)
+                        .unwrap(),
+                };
+
+                (idx, public_share)
+            })
+            .collect::<Vec<_>>();
+
+        public_shares.sort_by_key(|(index, _)| *index);
+
+        Ok(public_shares)
+    }
+
+    fn valid_group_indices(&self) -> Vec<usize> {
+        self.groups
+            .values()
+            .filter(|g| g.state == GroupState::Active)
+            .map(|g| g.index)
+            .collect::<Vec<_>>()
+    }
+
+    fn get_active_groups(&self) -> Vec<&Group> {
+        let mut groups = self
+            .groups
+            .values()
+            .filter(|g| g.state == GroupState::Active)
+            .collect::<Vec<_>>();
+
+        groups.sort_by_key(|g| g.index);
+
+        groups
+    }
+
+    fn pending_signature_tasks(&self) -> Vec<&SignatureTask> {
+        self.signature_tasks.pending()
+    }
+
+    fn verifiable_signature_rewards(&self) -> Vec<&SignatureReward> {
+        self.verifiable_signature_rewards
+            .values()
+            .collect::<Vec<_>>()
+    }
+
+    fn get_signature_confirmations(&self, index: usize) -> Result<&[String], ControllerError> {
+        self.signature_result_caches
+            .get(&index)
+            .map(|cache| cache.confirmations.as_slice())
+            .ok_or(ControllerError::SignatureResultNotFound(index))
+    }
+
+    fn get_node_reward(&self, id_address: String) -> u64 {
+        self.rewards.get(&id_address).copied().unwrap_or(0)
+    }
+
+    fn get_fee_pool(&self) -> u64 {
+        self.fee_pool
+    }
+
+    fn get_requester_balance(&self, requester: String) -> u64 {
+        self.requester_balances
+            .get(&requester)
+            .copied()
+            .unwrap_or(0)
+    }
+
+    fn get_requester_nonce(&self, requester: String) -> u64 {
+        self.requester_nonces.get(&requester).copied().unwrap_or(0)
+    }
+
+    fn verify_partials(
+        &self,
+        group_index: usize,
+        message: &[u8],
+        partials: &[(usize, Vec<u8>)],
+    ) -> Result<Vec<usize>, ControllerError> {
+        let public_shares = public_shares_by_index(self.get_group(group_index)?);
+
+        let mut invalid = partials
+            .iter()
+            .filter(|(index, partial)| {
+                !is_valid_partial(self.scheme, &public_shares, *index, message, partial)
+            })
+            .map(|(index, _)| *index)
+            .collect::<Vec<_>>();
+
+        invalid.sort();
+
+        Ok(invalid)
+    }
+
+    #[cfg(feature = "parallel")]
+    fn verify_partials_parallel(
+        &self,
+        group_index: usize,
+        message: &[u8],
+        partials: &[(usize, Vec<u8>)],
+    ) -> Result<Vec<usize>, ControllerError> {
+        let public_shares = public_shares_by_index(self.get_group(group_index)?);
+
+        let scheme = self.scheme;
+
+        let mut invalid = partials
+            .par_iter()
+            .filter(|(index, partial)| {
+                !is_valid_partial(scheme, &public_shares, *index, message, partial)
+            })
+            .map(|(index, _)| *index)
+            .collect::<Vec<_>>();
+
+        // the threads finish in any order
+        invalid.sort();
+
+        Ok(invalid)
+    }
+
+    fn aggregate_subset(
+        &self,
+        group_index: usize,
+        threshold: usize,
+        message: &[u8],
+        partials: &[(Idx, Vec<u8>)],
+    ) -> Result<Vec<u8>, ControllerError> {
+        let group = self.get_group(group_index)?;
+
+        let mut indices = vec![];
+
+        let mut selected = vec![];
+
+        for (index, partial) in partials.iter() {
+            if selected.len() == threshold {
+                break;
+            }
+
+            // the same share can't count twice towards the threshold
+            if !indices.contains(index) {
+                indices.push(*index);
+
+                selected.push(partial.clone());
+            }
+        }
+
+        if selected.len() < threshold {
+            return Err(ControllerError::NotEnoughPartials(
+                selected.len(),
+                threshold,
+            ));
+        }
+
+        let signature = self
+            .scheme
+            .aggregate(threshold, &selected)
+            .ok_or(ControllerError::InvalidAggregatedSignature(group_index))?;
+
+        if !self.scheme.verify(&group.public_key, message, &signature) {
+            return Err(ControllerError::InvalidAggregatedSignature(group_index));
+        }
+
+        Ok(signature)
+    }
+
+    fn get_randomness(&self, index: usize) -> Result<u64, ControllerError> {
+        // the randomness of a task is derived from its first fulfilled signature
+        self.signature_result_caches
+            .get(&index)
+            .map(|cache| self.randomness_hash.output(&cache.signature))
+            .ok_or(ControllerError::SignatureResultNotFound(index))
+    }
+
+    fn get_stalled_committers(&self, signature_index: usize) -> &[String] {
+        self.stalled_committers
+            .get(&signature_index)
+            .map(|committers| committers.as_slice())
+            .unwrap_or(&[])
+    }
+
+    fn get_faulty_committers(&self, signature_index: usize) -> &[String] {
+        self.faulty_committers
+            .get(&signature_index)
+            .map(|committers| committers.as_slice())
+            .unwrap_or(&[])
+    }
+
+    fn get_pending_requests(&self) -> Vec<(usize, Vec<u8>, usize, usize)> {
+        self.signature_tasks
+            .pending()
+            .into_iter()
+            .map(|task| {
+                (
+                    task.index,
+                    task.seed.clone(),
+                    task.group_index,
+                    task.assignment_block_height,
+                )
+            })
+            .collect()
+    }
+
+    fn get_fulfilled_requests(&self) -> Vec<(usize, Vec<u8>, usize, usize, u64)> {
+        self.signature_tasks
+            .fulfilled()
+            .into_iter()
+            .map(|task| {
+                (
+                    task.index,
+                    task.seed.clone(),
+                    task.group_index,
+                    task.assignment_block_height,
+                    self.get_randomness(task.index).unwrap(),
+                )
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::actions::{run_dkg, setup};
+    use crate::fixtures::{dkg_outputs, FIXTURE_SEED};
+    use crate::transform::ShuffleTransform;
+    use crate::verify::{
+        randomness_from_signature, randomness_output, verify_randomness, ProofError,
+    };
+    use dkg_core::primitives::types::DKGOutput;
+    use rand::{rngs::StdRng, RngCore, SeedableRng};
+    use sha2::{Digest, Sha256};
+    use std::sync::{Arc, Mutex};
+    use threshold_bls::{
+        curve::bls12381,
+        group::{Curve, Element},
+        poly::PublicPoly,
+        sig::{Scheme, ThresholdScheme},
+    };
+
+    fn id_keypair(i: usize) -> (<G1Scheme as Scheme>::Private, Vec<u8>) {
+        let (private, public) = G1Scheme::keypair(&mut StdRng::seed_from_u64(i as u64));
+
+        (private, bincode::serialize(&public).unwrap())
+    }
+
+    fn id_public_key(i: usize) -> Vec<u8> {
+        id_keypair(i).1
+    }
+
+    fn proof_of(i: usize) -> Vec<u8> {
+        let (private, public) = id_keypair(i);
+
+        proof_of_possession::<G1Scheme>(&private, &public).unwrap()
+    }
+
+    fn register_nodes(controller: &mut Controller, n: usize) {
+        (0..n).for_each(|i| {
+            controller
+                .node_register(
+                    String::from("0x") + &i.to_string(),
+                    id_public_key(i),
+                    endpoint_of(i),
+                    String::from("0x") + &i.to_string(),
+                    proof_of(i),
+                )
+                .unwrap();
+        });
+    }
+
+    fn endpoint_of(i: usize) -> String {
+        format!("127.0.0.1:{}", 8000 + i)
+    }
+
+    // a valid serialized public key, always the same for the same seed
+    fn fake_public_key(seed: u64) -> Vec<u8> {
+        let (_, public) = G1Scheme::keypair(&mut StdRng::seed_from_u64(seed));
+
+        bincode::serialize(&public).unwrap()
+    }
+
+    // a serialized public polynomial of the degree group `group_index` expects, whose
+    // constant term is the serialized `public_key`
+    fn public_poly_of(controller: &Controller, group_index: usize, public_key: &[u8]) -> Vec<u8> {
+        let threshold = controller.get_group(group_index).unwrap().threshold;
+
+        let rng = &mut StdRng::seed_from_u64(threshold as u64);
+
+        let mut coefficients = vec![bincode::deserialize::<bls12381::G1>(public_key).unwrap()];
+
+        coefficients.extend((1..threshold).map(|_| bls12381::G1::rand(rng)));
+
+        bincode::serialize(&PublicPoly::<bls12381::Curve>::from(coefficients)).unwrap()
+    }
+
+    // a public polynomial whose public key is `fake_public_key(seed)`
+    fn fake_public_poly(controller: &Controller, group_index: usize, seed: u64) -> Vec<u8> {
+        public_poly_of(controller, group_index, &fake_public_key(seed))
+    }
+
+    fn finalize_group(controller: &mut Controller, group_index: usize) {
+        let group = controller.get_group(group_index).unwrap().clone();
+
+        let public_poly = fake_public_poly(controller, group_index, 1969);
+
+        group.members.keys().for_each(|id_address| {
+            controller
+                .commit_dkg(
+                    id_address.clone(),
+                    group_index,
+                    group.epoch,
+                    public_poly.clone(),
+                    vec![],
+                    vec![],
+                )
+                .unwrap();
+        });
+    }
+
+    #[test]
+    fn register_returns_member_index() {
+        let mut controller = Controller::new(0x1234, RewardConfig::default());
+
+        register_nodes(&mut controller, 2);
+
+        let index = controller
+            .node_register(
+                String::from("0x2"),
+                id_public_key(2),
+                endpoint_of(2),
+                String::from("0x2"),
+                proof_of(2),
+            )
+            .unwrap();
+
+        assert_eq!(index, 3);
+    }
+
+    #[test]
+    fn dkg_task_waits_for_minimum_group_size() {
+        let mut controller = Controller::new(0x1234, RewardConfig::default());
+
+        controller.set_minimum_group_size(5);
+
+        register_nodes(&mut controller, 4);
+
+        assert!(controller.emit_dkg_task().is_empty());
+        assert_eq!(
+            controller.get_group(1).unwrap().state,
+            GroupState::Registering
+        );
+
+        controller
+            .node_register(
+                String::from("0x4"),
+                id_public_key(4),
+                endpoint_of(4),
+                String::from("0x4"),
+                proof_of(4),
+            )
+            .unwrap();
+
+        let dkg_tasks = controller.emit_dkg_task();
+
+        assert_eq!(dkg_tasks.len(), 1);
+        assert_eq!(dkg_tasks[0].size, 5);
+        assert_eq!(dkg_tasks[0].epoch, controller.get_group(1).unwrap().epoch);
+    }
+
+    #[test]
+    fn minimum_group_size_is_at_least_the_threshold() {
+        let mut controller = Controller::new(0x1234, RewardConfig::default());
+
+        controller.set_minimum_group_size(1);
+
+        assert_eq!(controller.minimum_group_size(), DEFAULT_MINIMUM_THRESHOLD);
+
+        register_nodes(&mut controller, DEFAULT_MINIMUM_THRESHOLD - 1);
+
+        assert!(controller.emit_dkg_task().is_empty());
+
+        let i = DEFAULT_MINIMUM_THRESHOLD - 1;
+
+        controller
+            .node_register(
+                format!("0x{}", i),
+                id_public_key(i),
+                endpoint_of(i),
+                format!("0x{}", i),
+                proof_of(i),
+            )
+            .unwrap();
+
+        let dkg_tasks = controller.emit_dkg_task();
+
+        assert_eq!(dkg_tasks.len(), 1);
+        assert!(dkg_tasks[0].threshold <= dkg_tasks[0].size);
+    }
+
+    #[test]
+    fn registered_endpoints_can_be_discovered() {
+        let mut controller = Controller::new(0x1234, RewardConfig::default());
+
+        controller.mine(3);
+
+        register_nodes(&mut controller, 3);
+
+        for i in 0..3 {
+            assert_eq!(
+                controller.get_node_endpoint(format!("0x{}", i)),
+                Ok(endpoint_of(i).parse().unwrap())
+            );
+        }
+
+        assert_eq!(controller.get_node(String::from("0x0")).registered_at, 3);
+
+        assert_eq!(
+            controller.get_node_endpoint(String::from("0x3")),
+            Err(ControllerError::NodeNotRegistered(String::from("0x3")))
+        );
+
+        assert_eq!(
+            controller.node_register(
+                String::from("0x3"),
+                id_public_key(3),
+                String::from("localhost"),
+                String::from("0x3"),
+                proof_of(3),
+            ),
+            Err(ControllerError::InvalidEndpoint(
+                String::from("0x3"),
+                String::from("localhost")
+            ))
+        );
+
+        // the node didn't join any group
+        assert_eq!(controller.get_group(1).unwrap().size, 3);
+    }
+
+    #[test]
+    fn group_address_is_derived_from_public_key() {
+        let mut controller = Controller::new(0x1234, RewardConfig::default());
+
+        register_nodes(&mut controller, 3);
+
+        assert_eq!(
+            controller.get_group_address(1),
+            Err(ControllerError::GroupNotReady(1))
+        );
+
+        finalize_group(&mut controller, 1);
+
+        let address = controller.get_group_address(1).unwrap();
+
+        assert_eq!(address, group_address_from_pubkey(&fake_public_key(1969)));
+
+        // the canonical serialization of the key gives the same address
+        let public_key: <G1Scheme as Scheme>::Public =
+            bincode::deserialize(&fake_public_key(1969)).unwrap();
+
+        assert_eq!(
+            group_address_from_pubkey(&bincode::serialize(&public_key).unwrap()),
+            address
+        );
+
+        assert_ne!(group_address_from_pubkey(&fake_public_key(1970)), address);
+    }
+
+    #[test]
+    fn group_snapshot_serializes_to_json() {
+        let mut controller = Controller::new(0x1234, RewardConfig::default());
+
+        register_nodes(&mut controller, 3);
+
+        finalize_group(&mut controller, 1);
+
+        let snapshot = controller.get_group_snapshot(1).unwrap();
+
+        let json = serde_json::to_value(&snapshot).unwrap();
+
+        assert_eq!(json["index"], 1);
+        assert_eq!(json["epoch"], 1);
+        assert_eq!(json["threshold"], 3);
+        assert_eq!(json["state"], "Active");
+        assert_eq!(json["public_key"], hex::encode(fake_public_key(1969)));
+        assert_eq!(json["committers"].as_array().unwrap().len(), 3);
+
+        let members = json["members"].as_array().unwrap();
+
+        assert_eq!(members.len(), 3);
+
+        for (i, member) in members.iter().enumerate() {
+            assert_eq!(member["index"], i + 1);
+            assert_eq!(member["id_address"], format!("0x{}", i));
+            assert_eq!(member["id_public_key"], hex::encode(id_public_key(i)));
+        }
+
+        assert_eq!(
+            serde_json::from_value::<GroupSnapshot>(json).unwrap(),
+            snapshot
+        );
+
+        assert_eq!(
+            controller.get_group_snapshot(2),
+            Err(ControllerError::GroupNotFound(2))
+        );
+    }
+
+    #[test]
+    fn register_same_address_twice() {
+        let mut controller = Controller::new(0x1234, RewardConfig::default());
+
+        register_nodes(&mut controller, 1);
+
+        let res = controller.node_register(
+            String::from("0x0"),
+            id_public_key(9),
+            endpoint_of(0),
+            String::from("0x0"),
+            proof_of(9),
+        );
+
+        assert_eq!(
+            res,
+            Err(ControllerError::NodeAlreadyRegistered(String::from("0x0")))
+        );
+
+        assert_eq!(controller.get_group(1).unwrap().size, 1);
+    }
+
+    #[test]
+    fn register_same_public_key_twice() {
+        let mut controller = Controller::new(0x1234, RewardConfig::default());
+
+        register_nodes(&mut controller, 1);
+
+        let res = controller.node_register(
+            String::from("0x1"),
+            id_public_key(0),
+            endpoint_of(1),
+            String::from("0x1"),
+            proof_of(0),
+        );
+
+        assert_eq!(
+            res,
+            Err(ControllerError::PublicKeyAlreadyRegistered(
+                String::from("0x1"),
+                String::from("0x0")
+            ))
+        );
+
+        assert_eq!(controller.get_group(1).unwrap().size, 1);
+    }
+
+    #[test]
+    fn register_requires_proof_of_possession() {
+        let mut controller = Controller::new(0x1234, RewardConfig::default());
+
+        let (private, public) = id_keypair(0);
+
+        let (other_private, _) = id_keypair(1);
+
+        let bad_proofs = vec![
+            // no proof at all
+            vec![],
+            // a signature of the key by another private key
+            proof_of_possession::<G1Scheme>(&other_private, &public).unwrap(),
+            // a signature of another message
+            proof_of_possession::<G1Scheme>(&private, b"0x0").unwrap(),
+        ];
+
+        for proof in bad_proofs {
+            assert_eq!(
+                controller.node_register(
+                    String::from("0x0"),
+                    public.clone(),
+                    endpoint_of(0),
+                    String::from("0x0"),
+                    proof,
+                ),
+                Err(ControllerError::InvalidProofOfPossession(String::from(
+                    "0x0"
+                )))
+            );
+        }
+
+        assert_eq!(
+            controller.node_register(
+                String::from("0x0"),
+                public.clone(),
+                endpoint_of(0),
+                String::from("0x0"),
+                proof_of(0),
+            ),
+            Ok(1)
+        );
+
+        assert_eq!(
+            controller.get_node(String::from("0x0")).id_public_key,
+            public
+        );
+    }
+
+    #[test]
+    fn register_from_config_reports_each_node() {
+        let mut controller = Controller::new(0x1234, RewardConfig::default());
+
+        let config_of = |i: usize, id_address: &str| NodeConfig {
+            id_address: String::from(id_address),
+            public_key_hex: format!("0x{}", hex::encode(id_public_key(i))),
+            proof_of_possession_hex: hex::encode(proof_of(i)),
+            rpc_endpoint: endpoint_of(i),
+            stake: 1000 + i,
+        };
+
+        let mut bad_hex = config_of(4, "0x4");
+
+        bad_hex.public_key_hex = String::from("0xnothex");
+
+        let nodes = vec![
+            config_of(0, "0x0"),
+            config_of(1, "0x1"),
+            // the same address again
+            config_of(2, "0x1"),
+            // the key of 0x0 under another address
+            config_of(0, "0x3"),
+            bad_hex,
+            config_of(5, "0x5"),
+        ];
+
+        assert_eq!(
+            controller.register_from_config(nodes),
+            vec![
+                Ok(1),
+                Ok(2),
+                Err(ControllerError::NodeAlreadyRegistered(String::from("0x1"))),
+                Err(ControllerError::PublicKeyAlreadyRegistered(
+                    String::from("0x3"),
+                    String::from("0x0")
+                )),
+                Err(ControllerError::InvalidNodeConfig(
+                    String::from("0x4"),
+                    String::from("public_key_hex")
+                )),
+                Ok(3),
+            ]
+        );
+
+        assert_eq!(controller.get_group(1).unwrap().size, 3);
+
+        let node = controller.get_node(String::from("0x5"));
+
+        assert_eq!(node.staking, 1005);
+        assert_eq!(node.id_public_key, id_public_key(5));
+        assert_eq!(node.reward_address, "0x5");
+
+        // the config can be read from JSON
+        let json = serde_json::to_string(&vec![config_of(6, "0x6")]).unwrap();
+
+        let nodes: Vec<NodeConfig> = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(controller.register_from_config(nodes), vec![Ok(4)]);
+    }
+
+    #[test]
+    fn registration_requires_minimum_stake() {
+        let mut controller = Controller::new(0x1234, RewardConfig::default());
+
+        controller.min_stake = 2000;
+
+        let config_of = |i: usize, stake: usize| NodeConfig {
+            id_address: format!("0x{}", i),
+            public_key_hex: hex::encode(id_public_key(i)),
+            proof_of_possession_hex: hex::encode(proof_of(i)),
+            rpc_endpoint: endpoint_of(i),
+            stake,
+        };
+
+        assert_eq!(
+            controller.register_from_config(vec![
+                config_of(0, 1999),
+                config_of(1, 2000),
+                config_of(2, 3000),
+            ]),
+            vec![
+                Err(ControllerError::InsufficientStake(
+                    String::from("0x0"),
+                    1999,
+                    2000
+                )),
+                Ok(1),
+                Ok(2),
+            ]
+        );
+
+        assert_eq!(controller.get_group(1).unwrap().size, 2);
+
+        assert_eq!(controller.get_group_stake(1), Ok(5000));
+
+        // the penalties count against the stake of the group
+        controller.slash_node("0x2", 500);
+
+        assert_eq!(controller.get_group_stake(1), Ok(4500));
+
+        assert_eq!(
+            controller.get_group_stake(2),
+            Err(ControllerError::GroupNotFound(2))
+        );
+    }
+
+    #[test]
+    fn deregister_unknown_node() {
+        let mut controller = Controller::new(0x1234, RewardConfig::default());
+
+        let res = controller.node_deregister(String::from("0x0"));
+
+        assert_eq!(res, DeregisterResult::NodeNotFound);
+    }
+
+    #[test]
+    fn deregister_committer_reselects_committers() {
+        let mut controller = Controller::new(0x1234, RewardConfig::default());
+
+        register_nodes(&mut controller, 5);
+
+        finalize_group(&mut controller, 1);
+
+        let committers = controller.get_group(1).unwrap().committers.clone();
+
+        assert_eq!(committers.len(), 3);
+
+        let res = controller.node_deregister(committers[0].clone());
+
+        assert_eq!(res, DeregisterResult::Removed);
+
+        let group = controller.get_group(1).unwrap();
+
+        assert_eq!(group.state, GroupState::Active);
+        assert_eq!(group.size, 4);
+        assert_eq!(group.committers.len(), 3);
+        assert!(!group.committers.contains(&committers[0]));
+    }
+
+    #[test]
+    fn regroup_compacts_member_indices() {
+        let mut controller = Controller::new(0x1234, RewardConfig::default());
+
+        register_nodes(&mut controller, 5);
+
+        finalize_group(&mut controller, 1);
+
+        // the member of index 3 leaves, the group can still sign
+        assert_eq!(
+            controller.node_deregister(String::from("0x2")),
+            DeregisterResult::Removed
+        );
+
+        // a new member makes the group regroup, and takes the last index
+        let index = controller
+            .node_register(
+                String::from("0x5"),
+                id_public_key(5),
+                endpoint_of(5),
+                String::from("0x5"),
+                proof_of(5),
+            )
+            .unwrap();
+
+        assert_eq!(index, 5);
+
+        let group = controller.get_group(1).unwrap();
+
+        assert_eq!(group.state, GroupState::Regrouping);
+
+        let mut indices = group
+            .members
+            .values()
+            .map(|member| (member.index, member.id_address.clone()))
+            .collect::<Vec<_>>();
+
+        indices.sort();
+
+        assert_eq!(
+            indices,
+            vec![
+                (1, String::from("0x0")),
+                (2, String::from("0x1")),
+                (3, String::from("0x3")),
+                (4, String::from("0x4")),
+                (5, String::from("0x5")),
+            ]
+        );
+
+        // the DKG task uses the new indices
+        assert_eq!(controller.emit_dkg_task()[0].members, indices);
+
+        // the members who moved are kept track of
+        assert_eq!(
+            group.index_reassignments,
+            vec![
+                IndexReassignment {
+                    epoch: group.epoch,
+                    id_address: String::from("0x3"),
+                    old_index: 4,
+                    new_index: 3,
+                },
+                IndexReassignment {
+                    epoch: group.epoch,
+                    id_address: String::from("0x4"),
+                    old_index: 5,
+                    new_index: 4,
+                },
+                IndexReassignment {
+                    epoch: group.epoch,
+                    id_address: String::from("0x5"),
+                    old_index: 6,
+                    new_index: 5,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn deregister_below_threshold_requires_regroup() {
+        let mut controller = Controller::new(0x1234, RewardConfig::default());
+
+        register_nodes(&mut controller, 6);
+
+        finalize_group(&mut controller, 1);
+
+        let epoch = controller.get_group(1).unwrap().epoch;
+
+        assert_eq!(controller.get_group(1).unwrap().threshold, 4);
+
+        assert_eq!(
+            controller.node_deregister(String::from("0x0")),
+            DeregisterResult::Removed
+        );
+        assert_eq!(
+            controller.node_deregister(String::from("0x1")),
+            DeregisterResult::Removed
+        );
+        assert_eq!(
+            controller.node_deregister(String::from("0x2")),
+            DeregisterResult::RegroupRequired
+        );
+
+        let group = controller.get_group(1).unwrap();
+
+        assert_eq!(group.state, GroupState::Regrouping);
+        assert!(group.committers.is_empty());
+
+        // the remaining members are asked to run a new DKG
+        let dkg_task = controller.emit_dkg_task()[0];
+
+        assert_eq!(dkg_task.epoch, epoch + 1);
+        assert_eq!(dkg_task.size, 3);
+        assert_eq!(dkg_task.threshold, 3);
+        assert_eq!(dkg_task.index_of("0x0"), None);
+    }
+
+    #[test]
+    fn group_state_follows_lifecycle() {
+        let mut controller = Controller::new(0x1234, RewardConfig::default());
+
+        register_nodes(&mut controller, 2);
+
+        assert_eq!(
+            controller.get_group(1).unwrap().state,
+            GroupState::Registering
+        );
+
+        // a registering group can't be asked for randomness
+        assert_eq!(
+            controller.request_from_group(String::from("user"), String::from("seed"), 0, 1),
+            Err(ControllerError::GroupNotReady(1))
+        );
+
+        assert_eq!(
+            controller.request(
+                String::from("user"),
+                String::from("seed"),
+                0,
+                DEFAULT_PRIORITY
+            ),
+            Err(ControllerError::NoAvailableGroup)
+        );
+
+        controller
+            .node_register(
+                String::from("0x2"),
+                id_public_key(2),
+                endpoint_of(2),
+                String::from("0x2"),
+                proof_of(2),
+            )
+            .unwrap();
+
+        assert_eq!(
+            controller.get_group(1).unwrap().state,
+            GroupState::WaitingForDkg
+        );
+
+        let epoch = controller.get_group(1).unwrap().epoch;
+
+        for i in 0..3 {
+            controller
+                .commit_dkg(
+                    String::from("0x") + &i.to_string(),
+                    1,
+                    epoch,
+                    fake_public_poly(&controller, 1, 1969),
+                    vec![],
+                    vec![],
+                )
+                .unwrap();
+
+            let expected = if i < 2 {
+                GroupState::DkgInProgress
+            } else {
+                GroupState::Active
+            };
+
+            assert_eq!(controller.get_group(1).unwrap().state, expected);
+        }
+
+        // a new member starts a new epoch
+        controller
+            .node_register(
+                String::from("0x3"),
+                id_public_key(3),
+                endpoint_of(3),
+                String::from("0x3"),
+                proof_of(3),
+            )
+            .unwrap();
+
+        assert_eq!(
+            controller.get_group(1).unwrap().state,
+            GroupState::Regrouping
+        );
+
+        assert!(controller.valid_group_indices().is_empty());
+
+        // nobody commits before the deadline
+        controller.mine(DKG_DEADLINE_BLOCKS + 1);
+
+        assert!(controller.finalize_dkg(1, epoch + 1));
+
+        assert_eq!(controller.get_group(1).unwrap().state, GroupState::Disabled);
+    }
+
+    #[test]
+    fn regrouping_group_rejects_fulfill() {
+        let mut controller = Controller::new(0x1234, RewardConfig::default());
+
+        register_nodes(&mut controller, 5);
+
+        let (private, public) = G1Scheme::keypair(&mut rand::thread_rng());
+
+        let epoch = controller.get_group(1).unwrap().epoch;
+
+        (0..5).for_each(|i| {
+            controller
+                .commit_dkg(
+                    String::from("0x") + &i.to_string(),
+                    1,
+                    epoch,
+                    public_poly_of(&controller, 1, &bincode::serialize(&public).unwrap()),
+                    vec![],
+                    vec![],
+                )
+                .unwrap();
+        });
+
+        let index = controller
+            .request(
+                String::from("user"),
+                String::from("seed"),
+                0,
+                DEFAULT_PRIORITY,
+            )
+            .unwrap();
+
+        let signature_task = controller.emit_signature_task().unwrap();
+
+        let committer = controller.get_group(1).unwrap().committers[0].clone();
+
+        controller
+            .node_register(
+                String::from("0x5"),
+                id_public_key(5),
+                endpoint_of(5),
+                String::from("0x5"),
+                proof_of(5),
+            )
+            .unwrap();
+
+        let signature = G1Scheme::sign(&private, &signature_task.message).unwrap();
+
+        assert_eq!(
+            controller.submit_signature(committer, index, signature, HashMap::new()),
+            Err(ControllerError::GroupNotReady(1))
+        );
+    }
+
+    #[test]
+    fn dkg_task_lists_members_and_coordinator() {
+        let mut controller = Controller::new(0x1234, RewardConfig::default());
+
+        register_nodes(&mut controller, 3);
+
+        let dkg_task = controller.emit_dkg_task()[0].clone();
+
+        assert_eq!(
+            dkg_task.members,
+            vec![
+                (1, String::from("0x0")),
+                (2, String::from("0x1")),
+                (3, String::from("0x2"))
+            ]
+        );
+
+        assert_eq!(dkg_task.coordinator, "0x0");
+        assert!(dkg_task.is_coordinator("0x0"));
+        assert!(!dkg_task.is_coordinator("0x1"));
+        assert_eq!(dkg_task.index_of("0x2"), Some(3));
+
+        // a new member joins at the end and the coordinator stays the same
+        controller
+            .node_register(
+                String::from("0x3"),
+                id_public_key(3),
+                endpoint_of(3),
+                String::from("0x3"),
+                proof_of(3),
+            )
+            .unwrap();
+
+        let dkg_task = controller.emit_dkg_task()[0];
+
+        assert_eq!(dkg_task.members.len(), 4);
+        assert_eq!(dkg_task.members[3], (4, String::from("0x3")));
+        assert_eq!(dkg_task.coordinator, "0x0");
+    }
+
+    #[test]
+    fn finalize_dkg_slashes_silent_nodes() {
+        let mut controller = Controller::new(0x1234, RewardConfig::default());
+
+        register_nodes(&mut controller, 5);
+
+        let epoch = controller.emit_dkg_task()[0].epoch;
+
+        (0..4).for_each(|i| {
+            assert!(controller
+                .commit_dkg(
+                    String::from("0x") + &i.to_string(),
+                    1,
+                    epoch,
+                    fake_public_poly(&controller, 1, 1969),
+                    vec![],
+                    vec![],
+                )
+                .is_ok());
+        });
+
+        // too early, the silent node may still commit
+        assert!(!controller.finalize_dkg(1, epoch));
+
+        controller.mine(DKG_DEADLINE_BLOCKS + 1);
+
+        // the deadline has passed
+        assert_eq!(
+            controller.commit_dkg(
+                String::from("0x4"),
+                1,
+                epoch,
+                fake_public_poly(&controller, 1, 1969),
+                vec![],
+                vec![],
+            ),
+            Err(ControllerError::DKGDeadlinePassed(1))
+        );
+
+        assert!(controller.finalize_dkg(1, epoch));
+
+        assert_eq!(controller.slashed, vec![String::from("0x4")]);
+
+        let node = controller.get_node(String::from("0x4"));
+
+        assert_eq!(node.slash_count, 1);
+        assert_eq!(node.participation_count, 0);
+
+        let node = controller.get_node(String::from("0x0"));
+
+        assert_eq!(node.slash_count, 0);
+        assert_eq!(node.participation_count, 1);
+
+        // a DKG task can only be finalized once
+        assert!(!controller.finalize_dkg(1, epoch));
+    }
+
+    #[test]
+    fn failed_dkg_is_retried_in_next_epoch() {
+        let mut controller = Controller::new(0x1234, RewardConfig::default());
+
+        register_nodes(&mut controller, 5);
+
+        let epoch = controller.emit_dkg_task()[0].epoch;
+
+        assert_eq!(
+            controller.retry_dkg(1, true),
+            Err(ControllerError::DKGNotFailed(1))
+        );
+
+        // only 2 members commit before the deadline
+        for i in 0..2 {
+            controller
+                .commit_dkg(
+                    format!("0x{}", i),
+                    1,
+                    epoch,
+                    fake_public_poly(&controller, 1, 1969),
+                    vec![],
+                    vec![],
+                )
+                .unwrap();
+        }
+
+        controller.mine(DKG_DEADLINE_BLOCKS + 1);
+
+        // the members which didn't commit can't be dropped, too few would be left
+        assert_eq!(
+            controller.retry_dkg(1, true),
+            Err(ControllerError::NotEnoughMembers(1, 2))
+        );
+
+        assert_eq!(controller.get_group(1).unwrap().state, GroupState::Disabled);
+
+        let dkg_task = controller.retry_dkg(1, false).unwrap();
+
+        assert_eq!(dkg_task.epoch, epoch + 1);
+        assert_eq!(dkg_task.members.len(), 5);
+        assert_eq!(
+            dkg_task.deadline_block_height,
+            controller.block_height + 2 * DKG_DEADLINE_BLOCKS
+        );
+
+        finalize_group(&mut controller, 1);
+
+        let group = controller.get_group(1).unwrap();
+
+        assert_eq!(group.state, GroupState::Active);
+        assert_eq!(group.epoch, epoch + 1);
+        assert_eq!(group.dkg_retries, 0);
+    }
+
+    #[test]
+    fn dkg_retries_are_capped() {
+        let mut controller = Controller::new(0x1234, RewardConfig::default());
+
+        register_nodes(&mut controller, 5);
+
+        for retries in 0..MAX_DKG_RETRIES {
+            // nobody commits, the pending DKG is finalized by the retry
+            controller.mine((DKG_DEADLINE_BLOCKS << retries) + 1);
+
+            controller.retry_dkg(1, false).unwrap();
+        }
+
+        controller.mine((DKG_DEADLINE_BLOCKS << MAX_DKG_RETRIES) + 1);
+
+        assert_eq!(
+            controller.retry_dkg(1, false),
+            Err(ControllerError::DKGRetryLimitReached(1))
+        );
+
+        assert_eq!(controller.get_group(1).unwrap().state, GroupState::Disabled);
+
+        assert!(controller.emit_dkg_task().is_empty());
+    }
+
+    #[test]
+    fn finalize_dkg_slashes_dissenting_nodes() {
+        let mut controller = Controller::new(0x1234, RewardConfig::default());
+
+        register_nodes(&mut controller, 5);
+
+        let epoch = controller.emit_dkg_task()[0].epoch;
+
+        (0..5).for_each(|i| {
+            let public_poly = if i == 2 {
+                fake_public_poly(&controller, 1, 666)
+            } else {
+                fake_public_poly(&controller, 1, 1969)
+            };
+
+            controller
+                .commit_dkg(
+                    String::from("0x") + &i.to_string(),
+                    1,
+                    epoch,
+                    public_poly,
+                    vec![],
+                    vec![],
+                )
+                .unwrap();
+        });
+
+        assert_eq!(controller.get_group(1).unwrap().state, GroupState::Active);
+
+        controller.mine(DKG_DEADLINE_BLOCKS + 1);
+
+        assert!(controller.finalize_dkg(1, epoch));
+
+        assert_eq!(controller.slashed, vec![String::from("0x2")]);
+
+        assert_eq!(
+            controller.get_group(1).unwrap().dissenters,
+            vec![String::from("0x2")]
+        );
+
+        assert_eq!(controller.get_node(String::from("0x2")).slash_count, 1);
+    }
+
+    #[test]
+    fn commit_dkg_finalizes_with_majority_public_key() {
+        let mut controller = Controller::new(0x1234, RewardConfig::default());
+
+        register_nodes(&mut controller, 5);
+
+        let epoch = controller.emit_dkg_task()[0].epoch;
+
+        // the dissenter commits before the majority is reached
+        controller
+            .commit_dkg(
+                String::from("0x0"),
+                1,
+                epoch,
+                fake_public_poly(&controller, 1, 666),
+                vec![],
+                vec![],
+            )
+            .unwrap();
+
+        (1..5).for_each(|i| {
+            controller
+                .commit_dkg(
+                    String::from("0x") + &i.to_string(),
+                    1,
+                    epoch,
+                    fake_public_poly(&controller, 1, 1969),
+                    vec![],
+                    vec![],
+                )
+                .unwrap();
+        });
+
+        let group = controller.get_group(1).unwrap();
+
+        assert_eq!(group.state, GroupState::Active);
+        assert_eq!(group.public_key, fake_public_key(1969));
+        assert_eq!(group.dissenters, vec![String::from("0x0")]);
+    }
+
+    #[test]
+    fn commit_dkg_without_majority_public_key() {
+        let mut controller = Controller::new(0x1234, RewardConfig::default());
+
+        register_nodes(&mut controller, 5);
+
+        let epoch = controller.emit_dkg_task()[0].epoch;
+
+        let public_keys = vec![1, 1, 2, 2];
+
+        for (i, public_key) in public_keys.into_iter().enumerate() {
+            controller
+                .commit_dkg(
+                    String::from("0x") + &i.to_string(),
+                    1,
+                    epoch,
+                    fake_public_poly(&controller, 1, public_key),
+                    vec![],
+                    vec![],
+                )
+                .unwrap();
+        }
+
+        // the last commit can't make any public key reach the threshold of 3, it is
+        // recorded all the same
+        assert_eq!(
+            controller.commit_dkg(
+                String::from("0x4"),
+                1,
+                epoch,
+                fake_public_poly(&controller, 1, 3),
+                vec![],
+                vec![]
+            ),
+            Ok(())
+        );
+
+        let group = controller.get_group(1).unwrap();
+
+        assert_eq!(group.state, GroupState::DkgInProgress);
+        assert_eq!(group.commit_cache.len(), 5);
+
+        // the DKG fails at its deadline, without slashing any of the nodes which committed
+        controller.mine(DKG_DEADLINE_BLOCKS + 1);
+
+        assert!(controller.finalize_dkg(1, epoch));
+
+        assert_eq!(controller.get_group(1).unwrap().state, GroupState::Disabled);
+        assert!(controller.slashed.is_empty());
+    }
+
+    #[test]
+    fn commit_dkg_rejects_stale_epoch() {
+        let mut controller = Controller::new(0x1234, RewardConfig::default());
+
+        register_nodes(&mut controller, 5);
+
+        let stale_epoch = controller.emit_dkg_task()[0].epoch;
+
+        for i in 0..2 {
+            controller
+                .commit_dkg(
+                    String::from("0x") + &i.to_string(),
+                    1,
+                    stale_epoch,
+                    fake_public_poly(&controller, 1, 1),
+                    vec![],
+                    vec![],
+                )
+                .unwrap();
+        }
+
+        // a new member joins, so the group starts over with a new epoch
+        controller
+            .node_register(
+                String::from("0x5"),
+                id_public_key(5),
+                endpoint_of(5),
+                String::from("0x5"),
+                proof_of(5),
+            )
+            .unwrap();
+
+        let epoch = controller.emit_dkg_task()[0].epoch;
+
+        assert_eq!(epoch, stale_epoch + 1);
+
+        // a straggler of the previous round commits too late
+        assert_eq!(
+            controller.commit_dkg(
+                String::from("0x2"),
+                1,
+                stale_epoch,
+                fake_public_poly(&controller, 1, 1),
+                vec![],
+                vec![]
+            ),
+            Err(ControllerError::EpochMismatch(1, epoch, stale_epoch))
+        );
+
+        let group = controller.get_group(1).unwrap();
+
+        assert!(group.commit_cache.is_empty());
+        assert_eq!(group.state, GroupState::WaitingForDkg);
+
+        for i in 0..6 {
+            controller
+                .commit_dkg(
+                    String::from("0x") + &i.to_string(),
+                    1,
+                    epoch,
+                    fake_public_poly(&controller, 1, 2),
+                    vec![],
+                    vec![],
+                )
+                .unwrap();
+        }
+
+        let group = controller.get_group(1).unwrap();
+
+        assert_eq!(group.state, GroupState::Active);
+        assert_eq!(group.public_key, fake_public_key(2));
+    }
+
+    #[test]
+    fn commit_dkg_rejects_malformed_keys() {
+        let mut controller = Controller::new(0x1234, RewardConfig::default());
+
+        register_nodes(&mut controller, 5);
+
+        let epoch = controller.emit_dkg_task()[0].epoch;
+
+        let public_poly = fake_public_poly(&controller, 1, 1969);
+
+        let public_key = fake_public_key(1969);
+
+        // truncated, garbage and padded bytes are all rejected without panicking
+        let malformed = |bytes: &[u8]| {
+            let mut trailing = bytes.to_vec();
+
+            trailing.push(0);
+
+            vec![
+                bytes[..bytes.len() - 1].to_vec(),
+                vec![0xff; bytes.len()],
+                trailing,
+            ]
+        };
+
+        for bytes in malformed(&public_poly) {
+            assert!(matches!(
+                controller.commit_dkg(String::from("0x0"), 1, epoch, bytes, vec![], vec![]),
+                Err(ControllerError::DeserializationError(..))
+            ));
+        }
+
+        for bytes in malformed(&public_key) {
+            assert!(matches!(
+                controller.commit_dkg(
+                    String::from("0x0"),
+                    1,
+                    epoch,
+                    public_poly.clone(),
+                    bytes,
+                    vec![]
+                ),
+                Err(ControllerError::DeserializationError(..))
+            ));
+        }
+
+        assert!(matches!(
+            controller.commit_dkg(String::from("0x0"), 1, epoch, vec![], vec![], vec![]),
+            Err(ControllerError::DeserializationError(..))
+        ));
+
+        // the keys of another scheme are not points of the group's curve
+        let (_, g2_public) = G2Scheme::keypair(&mut rand::thread_rng());
+
+        let g2_poly = PublicPoly::<bls12381::G2Curve>::from(vec![g2_public; 3]);
+
+        assert!(matches!(
+            controller.commit_dkg(
+                String::from("0x0"),
+                1,
+                epoch,
+                bincode::serialize(&g2_poly).unwrap(),
+                vec![],
+                vec![]
+            ),
+            Err(ControllerError::DeserializationError(..))
+        ));
+
+        assert!(controller.get_group(1).unwrap().commit_cache.is_empty());
+
+        controller
+            .commit_dkg(String::from("0x0"), 1, epoch, public_poly, vec![], vec![])
+            .unwrap();
+    }
+
+    #[test]
+    fn commit_dkg_rejects_polynomials_of_the_wrong_degree() {
+        let mut controller = Controller::new(0x1234, RewardConfig::default());
+
+        register_nodes(&mut controller, 5);
+
+        let epoch = controller.emit_dkg_task()[0].epoch;
+
+        let t = controller.get_group(1).unwrap().threshold;
+
+        let rng = &mut StdRng::seed_from_u64(1969);
+
+        for degree in vec![t, t - 2] {
+            let public_poly = PublicPoly::<bls12381::Curve>::new_from(degree, rng);
+
+            assert_eq!(
+                controller.commit_dkg(
+                    String::from("0x0"),
+                    1,
+                    epoch,
+                    bincode::serialize(&public_poly).unwrap(),
+                    vec![],
+                    vec![]
+                ),
+                Err(ControllerError::WrongPolynomialDegree(
+                    String::from("0x0"),
+                    degree,
+                    1,
+                    t - 1
+                ))
+            );
+        }
+
+        assert!(controller.get_group(1).unwrap().commit_cache.is_empty());
+
+        // the DKG run with the threshold of the group commits to a polynomial of degree t - 1
+        let outputs = dkg_outputs::<bls12381::Curve, G1Scheme>(5, t, FIXTURE_SEED);
+
+        let public_poly = &outputs[0].public;
+
+        assert_eq!(public_poly.degree(), t - 1);
+
+        controller
+            .commit_dkg(
+                String::from("0x0"),
+                1,
+                epoch,
+                bincode::serialize(public_poly).unwrap(),
+                vec![],
+                vec![],
+            )
+            .unwrap();
+    }
+
+    fn select_committers_by_public_key(public_key: Vec<u8>, committer_count: usize) -> Vec<String> {
+        let mut controller = Controller::new(0x1234, RewardConfig::default());
+
+        controller
+            .set_default_committer_count(committer_count)
+            .unwrap();
+
+        register_nodes(&mut controller, 10);
+
+        let epoch = controller.emit_dkg_task()[0].epoch;
+
+        let public_poly = public_poly_of(&controller, 1, &public_key);
+
+        (0..10).for_each(|i| {
+            controller
+                .commit_dkg(
+                    String::from("0x") + &i.to_string(),
+                    1,
+                    epoch,
+                    public_poly.clone(),
+                    vec![],
+                    vec![],
+                )
+                .unwrap();
+        });
+
+        controller.get_group(1).unwrap().committers.clone()
+    }
+
+    #[test]
+    fn committers_are_seeded_by_public_key() {
+        let committers = select_committers_by_public_key(fake_public_key(1969), 3);
+
+        assert_eq!(committers.len(), 3);
+
+        // the same DKG output always yields the same committers
+        assert_eq!(
+            select_committers_by_public_key(fake_public_key(1969), 3),
+            committers
+        );
+
+        assert_ne!(
+            select_committers_by_public_key(fake_public_key(2020), 3),
+            committers
+        );
+
+        assert_eq!(
+            select_committers_by_public_key(fake_public_key(1969), 5).len(),
+            5
+        );
+    }
+
+    #[test]
+    fn member_ranking_matches_golden_vector() {
+        let members = (1..=10)
+            .map(|index| Member {
+                index,
+                id_address: format!("0x{}", index),
+                partial_public_key: vec![],
+            })
+            .collect::<Vec<_>>();
+
+        // the same members inserted in opposite orders
+        let forward = members
+            .iter()
+            .map(|member| (member.index, member.clone()))
+            .collect::<HashMap<_, _>>();
+
+        let backward = members
+            .iter()
+            .rev()
+            .map(|member| (member.index, member.clone()))
+            .collect::<HashMap<_, _>>();
+
+        let rank = |members: &HashMap<usize, Member>, salt: &[u8]| {
+            rank_members(b"randcast", salt, members.values())
+                .iter()
+                .map(|member| member.index)
+                .collect::<Vec<_>>()
+        };
+
+        // SHA-256("randcast" || salt || index as a big-endian u64), by ascending hash
+        assert_eq!(rank(&forward, &[]), vec![5, 2, 4, 7, 9, 3, 10, 8, 6, 1]);
+        assert_eq!(rank(&backward, &[]), rank(&forward, &[]));
+
+        let salt = 7u64.to_be_bytes();
+
+        assert_eq!(rank(&forward, &salt), vec![3, 10, 7, 9, 8, 6, 2, 4, 5, 1]);
+        assert_eq!(rank(&backward, &salt), rank(&forward, &salt));
+    }
+
+    #[test]
+    fn committer_count_is_configured_per_group() {
+        let mut controller = Controller::new(0x1234, RewardConfig::default());
+
+        controller.group_capacity = 5;
+
+        register_nodes(&mut controller, 10);
+
+        assert_eq!(
+            controller.set_committer_count(1, 0),
+            Err(ControllerError::InvalidCommitterCount(0, 5))
+        );
+
+        assert_eq!(
+            controller.set_committer_count(1, 6),
+            Err(ControllerError::InvalidCommitterCount(6, 5))
+        );
+
+        controller.set_committer_count(1, 1).unwrap();
+
+        finalize_group(&mut controller, 1);
+
+        finalize_group(&mut controller, 2);
+
+        let group = controller.get_group(1).unwrap();
+
+        assert_eq!(group.committer_count, 1);
+        assert_eq!(group.committers.len(), 1);
+
+        let group = controller.get_group(2).unwrap();
+
+        assert_eq!(group.committer_count, DEFAULT_COMMITTER_COUNT);
+        assert_eq!(group.committers.len(), 3);
+
+        // the committers of an active group are selected again
+        controller.set_committer_count(2, 5).unwrap();
+
+        assert_eq!(controller.get_group(2).unwrap().committers.len(), 5);
+    }
+
+    #[test]
+    fn committer_counts_are_validated() {
+        let mut controller = Controller::new(0x1234, RewardConfig::default());
+
+        controller.group_capacity = 5;
+
+        assert_eq!(
+            controller.set_default_committer_count(0),
+            Err(ControllerError::InvalidCommitterCount(0, 5))
+        );
+
+        assert_eq!(
+            controller.set_default_committer_count(6),
+            Err(ControllerError::InvalidCommitterCount(6, 5))
+        );
+
+        assert_eq!(
+            controller.default_committer_count(),
+            DEFAULT_COMMITTER_COUNT
+        );
+
+        controller.set_default_committer_count(5).unwrap();
+
+        register_nodes(&mut controller, 5);
+
+        finalize_group(&mut controller, 1);
+
+        assert_eq!(controller.get_group(1).unwrap().committers.len(), 5);
+
+        // a group which loses a member lowers its committer count instead of keeping more
+        // committers than members
+        let res = controller.node_deregister(String::from("0x0"));
+
+        assert_eq!(res, DeregisterResult::Removed);
+
+        let group = controller.get_group(1).unwrap();
+
+        assert_eq!(group.size, 4);
+        assert_eq!(group.committer_count, 4);
+        assert_eq!(group.committers.len(), 4);
+    }
+
+    #[test]
+    fn register_nodes_into_multiple_groups() {
+        let mut controller = Controller::new(0x1234, RewardConfig::default());
+
+        controller.group_capacity = 5;
+
+        register_nodes(&mut controller, 12);
+
+        let sizes = (1..=3)
+            .map(|i| controller.get_group(i).unwrap().size)
+            .collect::<Vec<_>>();
+
+        assert_eq!(sizes, vec![5, 5, 2]);
+
+        assert_eq!(
+            controller.get_group(4).err(),
+            Some(ControllerError::GroupNotFound(4))
+        );
+
+        // the third group is too small to run a DKG yet
+        let group_indices = controller
+            .emit_dkg_task()
+            .iter()
+            .map(|dkg_task| dkg_task.group_index)
+            .collect::<Vec<_>>();
+
+        assert_eq!(group_indices, vec![1, 2]);
+
+        // the groups run their DKG independently of each other
+        finalize_group(&mut controller, 2);
+
+        assert_eq!(controller.get_group(2).unwrap().state, GroupState::Active);
+        assert_eq!(
+            controller.get_group(1).unwrap().state,
+            GroupState::WaitingForDkg
+        );
+        assert_eq!(controller.emit_dkg_task()[0].group_index, 1);
+    }
+
+    #[test]
+    fn slashed_members_disable_group() {
+        let mut controller = Controller::new(0x1234, RewardConfig::default());
+
+        controller.group_capacity = 6;
+
+        register_nodes(&mut controller, 12);
+
+        finalize_group(&mut controller, 1);
+
+        finalize_group(&mut controller, 2);
+
+        assert_eq!(controller.get_active_groups().len(), 2);
+
+        let group = controller.get_group(1).unwrap().clone();
+
+        assert_eq!(group.threshold, 4);
+
+        let mut members = group.members.keys().cloned().collect::<Vec<_>>();
+
+        members.sort();
+
+        // the healthy members can still reach the threshold
+        for id_address in members[..2].iter() {
+            controller.slash_node(id_address, DKG_PENALTY);
+        }
+
+        assert_eq!(controller.get_group(1).unwrap().state, GroupState::Active);
+
+        controller.slash_node(&members[2], DKG_PENALTY);
+
+        let group = controller.get_group(1).unwrap();
+
+        assert_eq!(group.state, GroupState::Disabled);
+        assert_eq!(group.size, 0);
+
+        let active_group_indices = controller
+            .get_active_groups()
+            .iter()
+            .map(|g| g.index)
+            .collect::<Vec<_>>();
+
+        assert_eq!(active_group_indices, vec![2]);
+
+        // the requests are only routed to the remaining group
+        for _ in 0..3 {
+            controller
+                .request(
+                    String::from("user"),
+                    String::from("seed"),
+                    0,
+                    DEFAULT_PRIORITY,
+                )
+                .unwrap();
+        }
+
+        assert!(controller
+            .pending_signature_tasks()
+            .iter()
+            .all(|signature_task| signature_task.group_index == 2));
+
+        // the healthy members form a replacement group which runs a new DKG
+        let replacement = controller.get_group(3).unwrap();
+
+        let mut replacement_members = replacement.members.keys().cloned().collect::<Vec<_>>();
+
+        replacement_members.sort();
+
+        assert_eq!(replacement_members, members[3..].to_vec());
+        assert_eq!(replacement.state, GroupState::WaitingForDkg);
+        assert!(controller
+            .emit_dkg_task()
+            .iter()
+            .any(|dkg_task| dkg_task.group_index == 3));
+    }
+
+    #[test]
+    fn disabled_group_hands_its_requests_over() {
+        let mut controller = Controller::new(0x1234, RewardConfig::default());
+
+        controller.group_capacity = 6;
+
+        register_nodes(&mut controller, 12);
+
+        finalize_group(&mut controller, 1);
+
+        finalize_group(&mut controller, 2);
+
+        let signature_index = controller
+            .request(
+                String::from("user"),
+                String::from("seed"),
+                100,
+                DEFAULT_PRIORITY,
+            )
+            .unwrap();
+
+        let signature_task = controller.emit_signature_task().unwrap();
+
+        assert_eq!(signature_task.group_index, 1);
+
+        // the task can't move while its group is still able to sign
+        assert_eq!(
+            controller.reassign_request(signature_index),
+            Err(ControllerError::GroupStillActive(1))
+        );
+
+        controller.mine(SIGNATURE_TASK_DEADLINE_BLOCKS);
+
+        let mut members = controller
+            .get_group(1)
+            .unwrap()
+            .members
+            .keys()
+            .cloned()
+            .collect::<Vec<_>>();
+
+        members.sort();
+
+        for id_address in members[..3].iter() {
+            controller.slash_node(id_address, DKG_PENALTY);
+        }
+
+        assert_eq!(controller.get_group(1).unwrap().state, GroupState::Disabled);
+
+        // the task is emitted again to group 2 with a new deadline
+        let signature_task = controller.emit_signature_task().unwrap();
+
+        assert_eq!(signature_task.index, signature_index);
+        assert_eq!(signature_task.group_index, 2);
+        assert_eq!(signature_task.rotation_count, 0);
+        assert_eq!(
+            signature_task.deadline_block_height,
+            controller.block_height + SIGNATURE_TASK_DEADLINE_BLOCKS
+        );
+
+        let (private, _) = G1Scheme::keypair(&mut StdRng::seed_from_u64(1969));
+
+        let committer = controller.get_group(2).unwrap().committers[0].clone();
+
+        controller
+            .submit_signature(
+                committer,
+                signature_index,
+                G1Scheme::sign(&private, &signature_task.message).unwrap(),
+                HashMap::new(),
+            )
+            .unwrap();
+
+        assert!(controller.pending_signature_tasks().is_empty());
+        assert_eq!(controller.get_last_output_groups(), &[2]);
+    }
+
+    #[test]
+    fn requests_skip_groups_below_threshold() {
+        let mut controller = Controller::new(0x1234, RewardConfig::default());
+
+        controller.group_capacity = 5;
+
+        register_nodes(&mut controller, 10);
+
+        finalize_group(&mut controller, 1);
+
+        finalize_group(&mut controller, 2);
+
+        // 3 members of the first group are frozen, only 2 of them can still sign
+        let frozen_members = controller
+            .get_group(1)
+            .unwrap()
+            .members
+            .keys()
+            .take(3)
+            .cloned()
+            .collect::<Vec<_>>();
+
+        for id_address in frozen_members.iter() {
+            controller.nodes.get_mut(id_address).unwrap().state = false;
+        }
+
+        assert_eq!(
+            controller.request_from_group(String::from("user"), String::from("seed"), 0, 1),
+            Err(ControllerError::InsufficientSigners(1, 2, 3))
+        );
+
+        assert_eq!(
+            controller.request_multigroup(
+                String::from("user"),
+                String::from("seed"),
+                0,
+                vec![2, 1]
+            ),
+            Err(ControllerError::InsufficientSigners(1, 2, 3))
+        );
+
+        // the requests are routed to the healthy group only
+        for _ in 0..3 {
+            let signature_index = controller
+                .request(
+                    String::from("user"),
+                    String::from("seed"),
+                    0,
+                    DEFAULT_PRIORITY,
+                )
+                .unwrap();
+
+            assert_eq!(
+                controller
+                    .signature_tasks
+                    .get(signature_index)
+                    .unwrap()
+                    .group_index,
+                2
+            );
+        }
+
+        controller.nodes.get_mut(&frozen_members[0]).unwrap().state = true;
+
+        let signature_index = controller
+            .request(
+                String::from("user"),
+                String::from("seed"),
+                0,
+                DEFAULT_PRIORITY,
+            )
+            .unwrap();
+
+        assert_eq!(
+            controller
+                .signature_tasks
+                .get(signature_index)
+                .unwrap()
+                .group_index,
+            1
+        );
+    }
+
+    #[test]
+    fn request_from_group_routes_to_group_key() {
+        let mut controller = Controller::new(0x1234, RewardConfig::default());
+
+        controller.group_capacity = 5;
+
+        register_nodes(&mut controller, 12);
+
+        let keypairs = (1..=2)
+            .map(|group_index| {
+                let (private, public) = G1Scheme::keypair(&mut rand::thread_rng());
+
+                let group = controller.get_group(group_index).unwrap().clone();
+
+                group.members.keys().for_each(|id_address| {
+                    controller
+                        .commit_dkg(
+                            id_address.clone(),
+                            group_index,
+                            group.epoch,
+                            public_poly_of(
+                                &controller,
+                                group_index,
+                                &bincode::serialize(&public).unwrap(),
+                            ),
+                            vec![],
+                            vec![],
+                        )
+                        .unwrap();
+                });
+
+                private
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            controller.request_from_group(String::from("user"), String::from("seed"), 0, 3),
+            Err(ControllerError::GroupNotReady(3))
+        );
+        assert_eq!(
+            controller.request_from_group(String::from("user"), String::from("seed"), 0, 4),
+            Err(ControllerError::GroupNotFound(4))
+        );
+
+        for &group_index in [2, 1, 2].iter() {
+            let signature_index = controller
+                .request_from_group(String::from("user"), String::from("seed"), 0, group_index)
+                .unwrap();
+
+            let signature_task = controller.emit_signature_task().unwrap();
+
+            assert_eq!(signature_task.group_index, group_index);
+
+            let message = &signature_task.message;
+
+            let committer = controller.get_group(group_index).unwrap().committers[0].clone();
+
+            // the key of the other group can't fulfill the task
+            let other_signature = G1Scheme::sign(&keypairs[2 - group_index], message).unwrap();
+
+            assert_eq!(
+                controller.submit_signature(
+                    committer.clone(),
+                    signature_index,
+                    other_signature,
+                    HashMap::new()
+                ),
+                Err(ControllerError::InvalidSignature(signature_index))
+            );
+
+            let signature = G1Scheme::sign(&keypairs[group_index - 1], message).unwrap();
+
+            controller
+                .submit_signature(committer, signature_index, signature, HashMap::new())
+                .unwrap();
+        }
+
+        // requests without a group are assigned round-robin
+        let group_indices = (0..4)
+            .map(|_| {
+                controller
+                    .request(
+                        String::from("user"),
+                        String::from("seed"),
+                        0,
+                        DEFAULT_PRIORITY,
+                    )
+                    .unwrap();
+
+                controller.emit_signature_task().unwrap().group_index
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(group_indices, vec![1, 2, 1, 2]);
+    }
+
+    #[test]
+    fn request_chains_last_output() {
+        let mut controller = Controller::new(0x1234, RewardConfig::default());
+
+        register_nodes(&mut controller, 5);
+
+        let (private, public) = G1Scheme::keypair(&mut rand::thread_rng());
+
+        let epoch = controller.get_group(1).unwrap().epoch;
+
+        (0..5).for_each(|i| {
+            controller
+                .commit_dkg(
+                    String::from("0x") + &i.to_string(),
+                    1,
+                    epoch,
+                    public_poly_of(&controller, 1, &bincode::serialize(&public).unwrap()),
+                    vec![],
+                    vec![],
+                )
+                .unwrap();
+        });
+
+        controller
+            .request(
+                String::from("user"),
+                String::from("seed"),
+                0,
+                DEFAULT_PRIORITY,
+            )
+            .unwrap();
+
+        let first_task = controller.emit_signature_task().unwrap();
+
+        assert!(first_task
+            .message
+            .starts_with(format!("{:016x}", 0x1234).as_bytes()));
+
+        let signature = G1Scheme::sign(&private, &first_task.message).unwrap();
+
+        let committer = controller.get_group(1).unwrap().committers[0].clone();
+
+        controller
+            .submit_signature(
+                committer,
+                first_task.index,
+                signature.clone(),
+                HashMap::new(),
+            )
+            .unwrap();
+
+        let last_output = controller.get_last_output();
+
+        assert_eq!(last_output, randomness_output(&signature));
+
+        // the same seed at the same block results in a new message
+        controller
+            .request(
+                String::from("user"),
+                String::from("seed"),
+                0,
+                DEFAULT_PRIORITY,
+            )
+            .unwrap();
+
+        let second_task = controller.emit_signature_task().unwrap();
+
+        assert_ne!(second_task.message, first_task.message);
+        assert!(second_task
+            .message
+            .starts_with(format!("{:016x}", last_output).as_bytes()));
+    }
+
+    #[test]
+    fn last_output_proof_verifies() {
+        let mut controller = Controller::new(0x1234, RewardConfig::default());
+
+        register_nodes(&mut controller, 5);
+
+        let (private, public) = G1Scheme::keypair(&mut rand::thread_rng());
+
+        let epoch = controller.get_group(1).unwrap().epoch;
+
+        (0..5).for_each(|i| {
+            controller
+                .commit_dkg(
+                    String::from("0x") + &i.to_string(),
+                    1,
+                    epoch,
+                    public_poly_of(&controller, 1, &bincode::serialize(&public).unwrap()),
+                    vec![],
+                    vec![],
+                )
+                .unwrap();
+        });
+
+        assert_eq!(controller.get_last_output_with_proof(), None);
+
+        let signature_index = controller
+            .request(
+                String::from("user"),
+                String::from("seed"),
+                0,
+                DEFAULT_PRIORITY,
+            )
+            .unwrap();
+
+        let message = controller.emit_signature_task().unwrap().message;
+
+        let signature = G1Scheme::sign(&private, &message).unwrap();
+
+        let committer = controller.get_group(1).unwrap().committers[0].clone();
+
+        controller
+            .submit_signature(
+                committer,
+                signature_index,
+                signature.clone(),
+                HashMap::new(),
+            )
+            .unwrap();
+
+        let proof = controller.get_last_output_with_proof().unwrap();
+
+        assert_eq!(proof.signature, signature);
+        assert_eq!((proof.group_index, proof.epoch), (1, epoch));
+
+        verify_randomness(&proof.group_pubkey, &proof.message, &proof.signature).unwrap();
+
+        assert_eq!(
+            proof.randomness,
+            randomness_from_signature(&proof.signature)
+        );
+        assert_eq!(
+            controller.get_last_output(),
+            randomness_output(&proof.signature)
+        );
+    }
+
+    #[test]
+    fn randomness_proof_detects_tampering() {
+        let mut controller = Controller::new(0x1234, RewardConfig::default());
+
+        register_nodes(&mut controller, 5);
+
+        finalize_group(&mut controller, 1);
+
+        let (private, _) = G1Scheme::keypair(&mut StdRng::seed_from_u64(1969));
+
+        let committer = controller.get_group(1).unwrap().committers[0].clone();
+
+        // the second request is chained with the output of the first one
+        for _ in 0..2 {
+            let signature_index = controller
+                .request(
+                    String::from("user"),
+                    String::from("seed"),
+                    0,
+                    DEFAULT_PRIORITY,
+                )
+                .unwrap();
+
+            let message = controller.emit_signature_task().unwrap().message;
+
+            controller
+                .submit_signature(
+                    committer.clone(),
+                    signature_index,
+                    G1Scheme::sign(&private, &message).unwrap(),
+                    HashMap::new(),
+                )
+                .unwrap();
+        }
+
+        let proof = controller.get_last_output_with_proof().unwrap();
+
+        assert_eq!(proof.index, 2);
+        assert_eq!(proof.nonce, 1);
+        assert_eq!(
+            proof.seed,
+            chain_seed(controller.get_outputs(1, 1).unwrap()[0].0, b"seed")
+        );
+
+        proof.verify().unwrap();
+
+        let mut tampered = proof.clone();
+        tampered.seed = chain_seed(0x1234, b"seed");
+        assert!(matches!(
+            tampered.verify(),
+            Err(ProofError::MessageMismatch)
+        ));
+
+        let mut tampered = proof.clone();
+        tampered.signature = G1Scheme::sign(&private, b"other").unwrap();
+        assert!(matches!(
+            tampered.verify(),
+            Err(ProofError::InvalidSignature(_))
+        ));
+
+        let mut tampered = proof.clone();
+        tampered.randomness[0] ^= 1;
+        assert!(matches!(
+            tampered.verify(),
+            Err(ProofError::RandomnessMismatch)
+        ));
+
+        // the messages of a custom hasher are only checked by the same hasher
+        controller.request_hasher = Box::new(Sha256RequestHasher);
+
+        controller
+            .request(
+                String::from("user"),
+                String::from("seed"),
+                0,
+                DEFAULT_PRIORITY,
+            )
+            .unwrap();
+
+        let signature_task = controller.emit_signature_task().unwrap();
+
+        controller
+            .submit_signature(
+                committer,
+                signature_task.index,
+                G1Scheme::sign(&private, &signature_task.message).unwrap(),
+                HashMap::new(),
+            )
+            .unwrap();
+
+        let proof = controller.get_last_output_with_proof().unwrap();
+
+        assert!(matches!(proof.verify(), Err(ProofError::MessageMismatch)));
+
+        proof.verify_with(&Sha256RequestHasher).unwrap();
+    }
+
+    #[test]
+    fn randomness_uses_the_configured_hash() {
+        let mut controller = Controller::new(0x1234, RewardConfig::default());
+
+        controller.randomness_hash = RandomnessHash::Keccak256;
+
+        register_nodes(&mut controller, 5);
+
+        finalize_group(&mut controller, 1);
+
+        let (private, _) = G1Scheme::keypair(&mut StdRng::seed_from_u64(1969));
+
+        let signature_index = controller
+            .request(
+                String::from("user"),
+                String::from("seed"),
+                0,
+                DEFAULT_PRIORITY,
+            )
+            .unwrap();
+
+        let message = controller.emit_signature_task().unwrap().message;
+
+        let signature = G1Scheme::sign(&private, &message).unwrap();
+
+        let committer = controller.get_group(1).unwrap().committers[0].clone();
+
+        controller
+            .submit_signature(
+                committer,
+                signature_index,
+                signature.clone(),
+                HashMap::new(),
+            )
+            .unwrap();
+
+        assert_eq!(
+            controller.get_last_output(),
+            RandomnessHash::Keccak256.output(&signature)
+        );
+        assert_ne!(controller.get_last_output(), randomness_output(&signature));
+
+        let proof = controller.get_last_output_with_proof().unwrap();
+
+        assert_eq!(proof.hash, RandomnessHash::Keccak256);
+        assert_eq!(proof.randomness, proof.hash.randomness(&proof.signature));
+    }
+
+    #[test]
+    fn outputs_are_kept_for_every_fulfilled_task() {
+        let mut controller = Controller::new(0x1234, RewardConfig::default());
+
+        register_nodes(&mut controller, 5);
+
+        finalize_group(&mut controller, 1);
+
+        let (private, _) = G1Scheme::keypair(&mut StdRng::seed_from_u64(1969));
+
+        let committer = controller.get_group(1).unwrap().committers[0].clone();
+
+        let mut signatures = vec![];
+
+        for _ in 0..3 {
+            let signature_index = controller
+                .request(
+                    String::from("user"),
+                    String::from("seed"),
+                    0,
+                    DEFAULT_PRIORITY,
+                )
+                .unwrap();
+
+            let message = controller.emit_signature_task().unwrap().message;
+
+            let signature = G1Scheme::sign(&private, &message).unwrap();
+
+            controller
+                .submit_signature(
+                    committer.clone(),
+                    signature_index,
+                    signature.clone(),
+                    HashMap::new(),
+                )
+                .unwrap();
+
+            signatures.push(signature);
+        }
+
+        // the signature tasks are numbered from 1
+        let outputs = controller.get_outputs(1, 3).unwrap();
+
+        assert_eq!(outputs.len(), 3);
+
+        for (i, (output, proof)) in outputs.iter().enumerate() {
+            assert_eq!(*output, randomness_output(&signatures[i]));
+            assert_eq!(proof.signature, signatures[i]);
+            assert_eq!(proof.group_pubkey, fake_public_key(1969));
+
+            verify_randomness(&proof.group_pubkey, &proof.message, &proof.signature).unwrap();
+        }
+
+        // the last one is the current output
+        assert_eq!(controller.get_last_output(), outputs[2].0);
+        assert_eq!(
+            controller.get_last_output_with_proof().as_ref(),
+            Some(&outputs[2].1)
+        );
+
+        assert_eq!(
+            controller.get_outputs(2, 2).unwrap(),
+            outputs[1..2].to_vec()
+        );
+
+        assert_eq!(
+            controller.get_outputs(0, 3),
+            Err(ControllerError::SignatureResultNotFound(0))
+        );
+
+        // a pending task has no output yet
+        controller
+            .request(
+                String::from("user"),
+                String::from("seed"),
+                0,
+                DEFAULT_PRIORITY,
+            )
+            .unwrap();
+
+        assert_eq!(
+            controller.get_outputs(1, 4),
+            Err(ControllerError::SignatureResultNotFound(4))
+        );
+    }
+
+    #[test]
+    fn fulfill_rejects_forged_signatures() {
+        let mut controller = Controller::new(0x1234, RewardConfig::default());
+
+        register_nodes(&mut controller, 5);
+
+        let rng = &mut rand::thread_rng();
+
+        let (private, public) = G1Scheme::keypair(rng);
+
+        let epoch = controller.get_group(1).unwrap().epoch;
+
+        (0..5).for_each(|i| {
+            controller
+                .commit_dkg(
+                    String::from("0x") + &i.to_string(),
+                    1,
+                    epoch,
+                    public_poly_of(&controller, 1, &bincode::serialize(&public).unwrap()),
+                    vec![],
+                    vec![],
+                )
+                .unwrap();
+        });
+
+        let signature_index = controller
+            .request(
+                String::from("user"),
+                String::from("seed"),
+                0,
+                DEFAULT_PRIORITY,
+            )
+            .unwrap();
+
+        let message = controller.emit_signature_task().unwrap().message;
+
+        let committers = controller.get_group(1).unwrap().committers.clone();
+
+        let outsider = (0..5)
+            .map(|i| String::from("0x") + &i.to_string())
+            .find(|id_address| !committers.contains(id_address))
+            .unwrap();
+
+        let (forger_private, _) = G1Scheme::keypair(rng);
+
+        let signature = G1Scheme::sign(&private, &message).unwrap();
+
+        let mut random_bytes = signature.clone();
+
+        rng.fill_bytes(&mut random_bytes);
+
+        let forged_signatures = vec![
+            // random bytes of the right length
+            random_bytes,
+            // a valid signature of the message under another key
+            G1Scheme::sign(&forger_private, &message).unwrap(),
+            // a valid signature of the group key on another message
+            G1Scheme::sign(&private, b"another message").unwrap(),
+        ];
+
+        for forged_signature in forged_signatures {
+            assert_eq!(
+                controller.submit_signature(
+                    outsider.clone(),
+                    signature_index,
+                    forged_signature.clone(),
+                    HashMap::new()
+                ),
+                Err(ControllerError::NotCommitter(outsider.clone(), 1))
+            );
+
+            assert_eq!(
+                controller.submit_signature(
+                    committers[0].clone(),
+                    signature_index,
+                    forged_signature,
+                    HashMap::new()
+                ),
+                Err(ControllerError::InvalidSignature(signature_index))
+            );
+
+            assert_eq!(controller.get_last_output(), 0x1234);
+            assert_eq!(
+                controller.get_randomness(signature_index),
+                Err(ControllerError::SignatureResultNotFound(signature_index))
+            );
+        }
+
+        // the task can still be fulfilled with the genuine signature
+        controller
+            .submit_signature(
+                committers[0].clone(),
+                signature_index,
+                signature.clone(),
+                HashMap::new(),
+            )
+            .unwrap();
+
+        assert_eq!(controller.get_last_output(), randomness_output(&signature));
+    }
+
+    /// Hashes the fields of a request, the way a contract would with keccak256
+    struct Sha256RequestHasher;
+
+    impl RequestHasher for Sha256RequestHasher {
+        fn hash(&self, seed: &[u8], requester: &str, nonce: u64) -> Vec<u8> {
+            let mut hasher = Sha256::new();
+
+            hasher.input(seed);
+            hasher.input(requester.as_bytes());
+            hasher.input(&nonce.to_be_bytes());
+
+            hasher.result().to_vec()
+        }
+    }
+
+    #[test]
+    fn request_signs_hashed_message() {
+        let mut controller = Controller::new(0x1234, RewardConfig::default());
+
+        controller.request_hasher = Box::new(Sha256RequestHasher);
+
+        register_nodes(&mut controller, 5);
+
+        let (private, public) = G1Scheme::keypair(&mut rand::thread_rng());
+
+        let epoch = controller.get_group(1).unwrap().epoch;
+
+        (0..5).for_each(|i| {
+            controller
+                .commit_dkg(
+                    String::from("0x") + &i.to_string(),
+                    1,
+                    epoch,
+                    public_poly_of(&controller, 1, &bincode::serialize(&public).unwrap()),
+                    vec![],
+                    vec![],
+                )
+                .unwrap();
+        });
+
+        controller
+            .request(
+                String::from("user"),
+                String::from("seed"),
+                0,
+                DEFAULT_PRIORITY,
+            )
+            .unwrap();
+
+        let signature_task = controller.emit_signature_task().unwrap();
+
+        let chained_seed = format!("{:016x}seed", 0x1234);
+
+        let message = Sha256RequestHasher.hash(chained_seed.as_bytes(), "user", 0);
+
+        assert_eq!(signature_task.message, message);
+
+        let committer = controller.get_group(1).unwrap().committers[0].clone();
+
+        // a signature of the plain message doesn't fulfill the task
+        let plain_message = PlainRequestHasher.hash(chained_seed.as_bytes(), "user", 0);
+
+        let plain_signature = G1Scheme::sign(&private, &plain_message).unwrap();
+
+        assert_eq!(
+            controller.submit_signature(
+                committer.clone(),
+                signature_task.index,
+                plain_signature,
+                HashMap::new()
+            ),
+            Err(ControllerError::InvalidSignature(signature_task.index))
+        );
+
+        let signature = G1Scheme::sign(&private, &message).unwrap();
+
+        controller
+            .submit_signature(committer, signature_task.index, signature, HashMap::new())
+            .unwrap();
+    }
+
+    #[test]
+    fn same_seed_requests_sign_distinct_messages() {
+        let mut controller = Controller::new(0x1234, RewardConfig::default());
+
+        register_nodes(&mut controller, 5);
+
+        finalize_group(&mut controller, 1);
+
+        assert_eq!(controller.get_requester_nonce(String::from("user")), 0);
+
+        let first = controller
+            .request(
+                String::from("user"),
+                String::from("seed"),
+                0,
+                DEFAULT_PRIORITY,
+            )
+            .unwrap();
+
+        let second = controller
+            .request(
+                String::from("user"),
+                String::from("seed"),
+                0,
+                DEFAULT_PRIORITY,
+            )
+            .unwrap();
+
+        assert_ne!(first, second);
+
+        assert_eq!(controller.get_requester_nonce(String::from("user")), 2);
+
+        let first_task = controller.emit_signature_task().unwrap();
+
+        let second_task = controller.emit_signature_task().unwrap();
+
+        assert_eq!((first_task.nonce, second_task.nonce), (0, 1));
+
+        assert_ne!(first_task.message, second_task.message);
+
+        // the nonces are counted per requester
+        controller
+            .request(
+                String::from("other"),
+                String::from("seed"),
+                0,
+                DEFAULT_PRIORITY,
+            )
+            .unwrap();
+
+        assert_eq!(controller.emit_signature_task().unwrap().nonce, 0);
+
+        assert_eq!(controller.get_requester_nonce(String::from("user")), 2);
+    }
+
+    #[test]
+    fn multigroup_output_combines_every_group() {
+        let mut controller = Controller::new(0x1234, RewardConfig::default());
+
+        controller.group_capacity = 5;
+
+        register_nodes(&mut controller, 10);
+
+        let keypairs = (1..=2)
+            .map(|group_index| {
+                let (private, public) = G1Scheme::keypair(&mut rand::thread_rng());
+
+                let group = controller.get_group(group_index).unwrap().clone();
+
+                group.members.keys().for_each(|id_address| {
+                    controller
+                        .commit_dkg(
+                            id_address.clone(),
+                            group_index,
+                            group.epoch,
+                            public_poly_of(
+                                &controller,
+                                group_index,
+                                &bincode::serialize(&public).unwrap(),
+                            ),
+                            vec![],
+                            vec![],
+                        )
+                        .unwrap();
+                });
+
+                (private, bincode::serialize(&public).unwrap())
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            controller.request_multigroup(String::from("user"), String::from("seed"), 10, vec![1]),
+            Err(ControllerError::NotEnoughGroups(1))
+        );
+
+        assert_eq!(
+            controller.request_multigroup(
+                String::from("user"),
+                String::from("seed"),
+                10,
+                vec![1, 1]
+            ),
+            Err(ControllerError::DuplicateGroup(1))
+        );
+
+        let index = controller
+            .request_multigroup(String::from("user"), String::from("seed"), 10, vec![1, 2])
+            .unwrap();
+
+        let request = controller.get_multigroup_request(index).unwrap().clone();
+
+        assert_eq!(request.output, None);
+
+        let signatures = request
+            .signature_indices
+            .iter()
+            .zip(&keypairs)
+            .map(|(signature_index, (private, public_key))| {
+                let signature_task = controller.emit_signature_task().unwrap();
+
+                // every group signs the same message
+                assert_eq!(signature_task.index, *signature_index);
+                assert_eq!(signature_task.message, request.message);
+
+                let signature = G1Scheme::sign(private, &request.message).unwrap();
+
+                assert!(verify_randomness(public_key, &request.message, &signature).is_ok());
+
+                let committer = controller
+                    .get_group(signature_task.group_index)
+                    .unwrap()
+                    .committers[0]
+                    .clone();
+
+                controller
+                    .submit_signature(
+                        committer,
+                        *signature_index,
+                        signature.clone(),
+                        HashMap::new(),
+                    )
+                    .unwrap();
+
+                signature
+            })
+            .collect::<Vec<_>>();
+
+        let output = randomness_output(&signatures[0]) ^ randomness_output(&signatures[1]);
+
+        assert_eq!(
+            controller.get_multigroup_request(index).unwrap().output,
+            Some(output)
+        );
+
+        assert_eq!(controller.get_last_output(), output);
+
+        assert_eq!(controller.get_last_output_groups(), &[1, 2]);
+
+        assert_eq!(controller.get_last_output_with_proof(), None);
+    }
+
+    #[test]
+    fn group_signatures_are_verified_once() {
+        let mut controller = Controller::new(0x1234, RewardConfig::default());
+
+        register_nodes(&mut controller, 3);
+
+        finalize_group(&mut controller, 1);
+
+        let (private, _) = G1Scheme::keypair(&mut StdRng::seed_from_u64(1969));
+
+        let signature = G1Scheme::sign(&private, b"gossip").unwrap();
+
+        let group = controller.get_group(1).unwrap().clone();
+
+        for _ in 0..2 {
+            assert!(controller.verify_group_signature(&group, b"gossip", &signature));
+            assert!(!controller.verify_group_signature(&group, b"other", &signature));
+        }
+
+        assert_eq!(controller.verification_cache.misses(), 2);
+        assert_eq!(controller.verification_cache.hits(), 2);
+        assert_eq!(controller.verification_cache.len(), 2);
+
+        // a new member starts a new epoch with a new key
+        controller
+            .node_register(
+                String::from("0x3"),
+                id_public_key(3),
+                endpoint_of(3),
+                String::from("0x3"),
+                proof_of(3),
+            )
+            .unwrap();
+
+        assert!(controller.verification_cache.is_empty());
+    }
+
+    #[test]
+    fn verification_cache_evicts_least_recently_used() {
+        let mut cache = VerificationCache::new(2);
+
+        let verify = |cache: &mut VerificationCache, message: &[u8]| {
+            cache.get_or_verify(1, 1, message, b"signature", || true)
+        };
+
+        verify(&mut cache, b"a");
+        verify(&mut cache, b"b");
+        // "a" becomes the most recently used one, so "b" is evicted
+        verify(&mut cache, b"a");
+        verify(&mut cache, b"c");
+
+        assert_eq!(cache.len(), 2);
+        assert_eq!((cache.hits(), cache.misses()), (1, 3));
+
+        verify(&mut cache, b"a");
+        verify(&mut cache, b"b");
+
+        assert_eq!((cache.hits(), cache.misses()), (2, 4));
+    }
+
+    #[test]
+    fn fulfill_deduplicates_committed_signatures() {
+        let mut controller = Controller::new(0x1234, RewardConfig::default());
+
+        register_nodes(&mut controller, 5);
+
+        let (private, public) = G1Scheme::keypair(&mut rand::thread_rng());
+
+        let epoch = controller.get_group(1).unwrap().epoch;
+
+        (0..5).for_each(|i| {
+            controller
+                .commit_dkg(
+                    String::from("0x") + &i.to_string(),
+                    1,
+                    epoch,
+                    public_poly_of(&controller, 1, &bincode::serialize(&public).unwrap()),
+                    vec![],
+                    vec![],
+                )
+                .unwrap();
+        });
+
+        controller
+            .request(
+                String::from("user"),
+                String::from("seed"),
+                0,
+                DEFAULT_PRIORITY,
+            )
+            .unwrap();
+
+        let signature_task = controller.emit_signature_task().unwrap();
+
+        let signature = G1Scheme::sign(&private, &signature_task.message).unwrap();
+
+        let committers = controller.get_group(1).unwrap().committers.clone();
+
+        assert_eq!(
+            controller.get_signature_confirmations(signature_task.index),
+            Err(ControllerError::SignatureResultNotFound(
+                signature_task.index
+            ))
+        );
+
+        controller
+            .submit_signature(
+                committers[0].clone(),
+                signature_task.index,
+                signature.clone(),
+                HashMap::new(),
+            )
+            .unwrap();
+
+        let last_output = controller.get_last_output();
+
+        let committer_reward = controller.get_node_reward(committers[0].clone());
+
+        // the same committer can't confirm twice
+        assert_eq!(
+            controller.submit_signature(
+                committers[0].clone(),
+                signature_task.index,
+                signature.clone(),
+                HashMap::new()
+            ),
+            Err(ControllerError::TaskAlreadyFulfilled(signature_task.index))
+        );
+
+        // a disagreeing signature is rejected
+        let (other_private, _) = G1Scheme::keypair(&mut rand::thread_rng());
+
+        let other_signature = G1Scheme::sign(&other_private, &signature_task.message).unwrap();
+
+        assert_eq!(
+            controller.submit_signature(
+                committers[1].clone(),
+                signature_task.index,
+                other_signature,
+                HashMap::new()
+            ),
+            Err(ControllerError::ConflictingSignature(signature_task.index))
+        );
+
+        controller
+            .submit_signature(
+                committers[1].clone(),
+                signature_task.index,
+                signature.clone(),
+                HashMap::new(),
+            )
+            .unwrap();
+
+        // a quorum of 2 out of 3 committers has agreed
+        assert_eq!(
+            controller.submit_signature(
+                committers[2].clone(),
+                signature_task.index,
+                signature,
+                HashMap::new()
+            ),
+            Err(ControllerError::TaskAlreadyFulfilled(signature_task.index))
+        );
+
+        assert_eq!(
+            controller
+                .get_signature_confirmations(signature_task.index)
+                .unwrap(),
+            &committers[..2]
+        );
+
+        // confirmations don't write the result again
+        assert_eq!(controller.get_last_output(), last_output);
+        assert_eq!(
+            controller.get_node_reward(committers[0].clone()),
+            committer_reward
+        );
+    }
+
+    #[test]
+    fn only_committers_holding_a_member_index_fulfill() {
+        let mut controller = Controller::new(0x1234, RewardConfig::default());
+
+        register_nodes(&mut controller, 5);
+
+        let (private, public) = G1Scheme::keypair(&mut rand::thread_rng());
+
+        let epoch = controller.get_group(1).unwrap().epoch;
+
+        (0..5).for_each(|i| {
+            controller
+                .commit_dkg(
+                    String::from("0x") + &i.to_string(),
+                    1,
+                    epoch,
+                    public_poly_of(&controller, 1, &bincode::serialize(&public).unwrap()),
+                    vec![],
+                    vec![],
+                )
+                .unwrap();
+        });
+
+        let signature_index = controller
+            .request(
+                String::from("user"),
+                String::from("seed"),
+                0,
+                DEFAULT_PRIORITY,
+            )
+            .unwrap();
+
+        let message = controller.emit_signature_task().unwrap().message;
+
+        let signature = G1Scheme::sign(&private, &message).unwrap();
+
+        let committers = controller.get_group(1).unwrap().committers.clone();
+
+        let outsider = (0..5)
+            .map(|i| String::from("0x") + &i.to_string())
+            .find(|id_address| !committers.contains(id_address))
+            .unwrap();
+
+        // a registered member which wasn't selected
+        assert_eq!(
+            controller.submit_signature(
+                outsider.clone(),
+                signature_index,
+                signature.clone(),
+                HashMap::new()
+            ),
+            Err(ControllerError::NotCommitter(outsider, 1))
+        );
+
+        // a committer which doesn't map to a member of the group
+        let ghost = String::from("0xghost");
+
+        controller
+            .groups
+            .get_mut(&1)
+            .unwrap()
+            .committers
+            .push(ghost.clone());
+
+        assert_eq!(
+            controller.submit_signature(
+                ghost.clone(),
+                signature_index,
+                signature.clone(),
+                HashMap::new()
+            ),
+            Err(ControllerError::NotGroupMember(ghost, 1))
+        );
+
+        controller
+            .submit_signature(
+                committers[0].clone(),
+                signature_index,
+                signature,
+                HashMap::new(),
+            )
+            .unwrap();
+
+        assert_eq!(
+            controller.get_randomness(signature_index),
+            Ok(controller.get_last_output())
+        );
+    }
+
+    #[test]
+    fn health_report_counts_the_network() {
+        let mut controller = Controller::new(0x1234, RewardConfig::default());
+
+        assert_eq!(controller.health_report(), HealthReport::default());
+
+        controller.group_capacity = 5;
+
+        // 0x0 to 0x4 make group 1, 0x5 to 0x9 group 2
+        register_nodes(&mut controller, 10);
+
+        finalize_group(&mut controller, 1);
+
+        finalize_group(&mut controller, 2);
+
+        // the requests alternate between the groups
+        let signature_indices = (0..3)
+            .map(|_| {
+                controller
+                    .request(
+                        String::from("user"),
+                        String::from("seed"),
+                        0,
+                        DEFAULT_PRIORITY,
+                    )
+                    .unwrap()
+            })
+            .collect::<Vec<_>>();
+
+        let signature_task = controller.emit_signature_task().unwrap();
+
+        assert_eq!(signature_task.group_index, 1);
+
+        let (private, _) = G1Scheme::keypair(&mut StdRng::seed_from_u64(1969));
+
+        let committer = controller.get_group(1).unwrap().committers[0].clone();
+
+        controller
+            .submit_signature(
+                committer,
+                signature_task.index,
+                G1Scheme::sign(&private, &signature_task.message).unwrap(),
+                HashMap::new(),
+            )
+            .unwrap();
+
+        // group 2 can't reach its threshold with 3 slashed members, 0x5 counts once
+        for id_address in ["0x5", "0x5", "0x6", "0x7"].iter() {
+            controller.slash_node(id_address, DKG_PENALTY);
+        }
+
+        assert_eq!(controller.get_group(2).unwrap().state, GroupState::Disabled);
+
+        assert_eq!(
+            controller.health_report(),
+            HealthReport {
+                node_count: 10,
+                active_group_count: 1,
+                disabled_group_count: 1,
+                pending_request_count: 2,
+                fulfilled_request_count: 1,
+                slashed_node_count: 3,
+                last_signature_index: Some(signature_indices[0]),
+            }
+        );
+    }
+
+    #[test]
+    fn rejected_fulfill_has_no_side_effects() {
+        let mut controller = Controller::new(0x1234, RewardConfig::default());
+
+        register_nodes(&mut controller, 5);
+
+        let (private, public) = G1Scheme::keypair(&mut rand::thread_rng());
+
+        let epoch = controller.get_group(1).unwrap().epoch;
+
+        (0..5).for_each(|i| {
+            controller
+                .commit_dkg(
+                    String::from("0x") + &i.to_string(),
+                    1,
+                    epoch,
+                    public_poly_of(&controller, 1, &bincode::serialize(&public).unwrap()),
+                    vec![],
+                    vec![],
+                )
+                .unwrap();
+        });
+
+        let signature_index = controller
+            .request(
+                String::from("user"),
+                String::from("seed"),
+                100,
+                DEFAULT_PRIORITY,
+            )
+            .unwrap();
+
+        let message = controller.emit_signature_task().unwrap().message;
+
+        let signature = G1Scheme::sign(&private, &message).unwrap();
+
+        let committers = controller.get_group(1).unwrap().committers.clone();
+
+        let outsider = (0..5)
+            .map(|i| String::from("0x") + &i.to_string())
+            .find(|id_address| !committers.contains(id_address))
+            .unwrap();
+
+        // no partial signature verifies against the empty partial public keys
+        let invalid_partials = (0..3)
+            .map(|i| (String::from("0x") + &i.to_string(), signature.clone()))
+            .collect::<HashMap<_, _>>();
+
+        let rejected_calls = vec![
+            (
+                committers[0].clone(),
+                G1Scheme::sign(&private, b"another message").unwrap(),
+                HashMap::new(),
+                ControllerError::InvalidSignature(signature_index),
+            ),
+            (
+                outsider.clone(),
+                signature.clone(),
+                HashMap::new(),
+                ControllerError::NotCommitter(outsider, 1),
+            ),
+            (
+                committers[0].clone(),
+                signature.clone(),
+                invalid_partials,
+                ControllerError::NotEnoughValidPartials(signature_index),
+            ),
+        ];
+
+        let rewards = controller.rewards.clone();
+
+        for (id_address, signature, partial_signatures, err) in rejected_calls {
+            assert_eq!(
+                controller.submit_signature(
+                    id_address,
+                    signature_index,
+                    signature,
+                    partial_signatures
+                ),
+                Err(err)
+            );
+
+            assert_eq!(controller.get_last_output(), 0x1234);
+            assert_eq!(controller.get_last_output_with_proof(), None);
+            assert_eq!(controller.rewards, rewards);
+            assert_eq!(controller.get_fee_pool(), 100);
+            assert!(controller.get_faulty_committers(signature_index).is_empty());
+            assert!(controller.verifiable_signature_rewards().is_empty());
+            assert!(controller.get_fulfilled_requests().is_empty());
+            assert_eq!(controller.get_pending_requests()[0].0, signature_index);
+            assert_eq!(
+                controller.get_randomness(signature_index),
+                Err(ControllerError::SignatureResultNotFound(signature_index))
+            );
+        }
+
+        controller
+            .submit_signature(
+                committers[0].clone(),
+                signature_index,
+                signature,
+                HashMap::new(),
+            )
+            .unwrap();
+
+        assert_ne!(controller.rewards, rewards);
+        assert!(controller.get_pending_requests().is_empty());
+    }
+
+    #[test]
+    fn queue_emits_higher_priority_tasks_first() {
+        let mut controller = Controller::new(0x1234, RewardConfig::default());
+
+        register_nodes(&mut controller, 5);
+
+        finalize_group(&mut controller, 1);
+
+        let requests = vec![("low", 1), ("high", 5), ("other low", 1), ("default", 0)];
+
+        for (seed, priority) in requests.iter() {
+            controller
+                .request(String::from("user"), String::from(*seed), 0, *priority)
+                .unwrap();
+        }
+
+        // first in first out among equal priorities
+        let emitted = (0..4)
+            .map(|_| controller.emit_signature_task().unwrap())
+            .map(|signature_task| (signature_task.index, signature_task.priority))
+            .collect::<Vec<_>>();
+
+        assert_eq!(emitted, vec![(2, 5), (1, 1), (3, 1), (4, 0)]);
+
+        assert!(controller.emit_signature_task().is_none());
+
+        assert_eq!(controller.get_request_json(2).unwrap()["priority"], 5);
+    }
+
+    #[test]
+    fn queue_fulfills_tasks_out_of_order() {
+        let mut controller = Controller::new(0x1234, RewardConfig::default());
+
+        register_nodes(&mut controller, 5);
+
+        let (private, public) = G1Scheme::keypair(&mut rand::thread_rng());
+
+        let epoch = controller.get_group(1).unwrap().epoch;
+
+        (0..5).for_each(|i| {
+            controller
+                .commit_dkg(
+                    String::from("0x") + &i.to_string(),
+                    1,
+                    epoch,
+                    public_poly_of(&controller, 1, &bincode::serialize(&public).unwrap()),
+                    vec![],
+                    vec![],
+                )
+                .unwrap();
+        });
+
+        let seeds = vec!["first", "second", "third"];
+
+        seeds.iter().for_each(|seed| {
+            controller
+                .request(
+                    String::from("user"),
+                    String::from(*seed),
+                    0,
+                    DEFAULT_PRIORITY,
+                )
+                .unwrap();
+        });
+
+        assert_eq!(controller.pending_signature_tasks().len(), 3);
+
+        assert_eq!(
+            controller
+                .get_pending_requests()
+                .into_iter()
+                .map(|request| request.1)
+                .collect::<Vec<_>>(),
+            seeds
+                .iter()
+                .map(|seed| seed.as_bytes().to_vec())
+                .collect::<Vec<_>>()
+        );
+
+        assert!(controller.get_fulfilled_requests().is_empty());
+
+        // tasks are emitted in request order
+        let signature_tasks = (0..3)
+            .map(|_| controller.emit_signature_task().unwrap())
+            .collect::<Vec<_>>();
+
+        assert!(controller.emit_signature_task().is_none());
+
+        for (i, signature_task) in signature_tasks.iter().enumerate() {
+            assert_eq!(signature_task.index, i + 1);
+            assert_eq!(signature_task.seed, seeds[i].as_bytes());
+            assert!(String::from_utf8_lossy(&signature_task.message).contains(seeds[i]));
+        }
+
+        let signatures = signature_tasks
+            .iter()
+            .map(|signature_task| G1Scheme::sign(&private, &signature_task.message).unwrap())
+            .collect::<Vec<_>>();
+
+        let committer = controller.get_group(1).unwrap().committers[0].clone();
+
+        // a signature only fulfills the task of its own message
+        assert_eq!(
+            controller.submit_signature(
+                committer.clone(),
+                signature_tasks[1].index,
+                signatures[0].clone(),
+                HashMap::new()
+            ),
+            Err(ControllerError::InvalidSignature(signature_tasks[1].index))
+        );
+
+        for &i in [2, 0, 1].iter() {
+            controller
+                .submit_signature(
+                    committer.clone(),
+                    signature_tasks[i].index,
+                    signatures[i].clone(),
+                    HashMap::new(),
+                )
+                .unwrap();
+
+            assert_eq!(
+                controller.get_last_output(),
+                randomness_output(&signatures[i])
+            );
+
+            let signature_reward = controller
+                .verifiable_signature_rewards()
+                .into_iter()
+                .find(|reward| reward.signature_task.index == signature_tasks[i].index)
+                .unwrap();
+
+            assert_eq!(
+                signature_reward.signature_task.message,
+                signature_tasks[i].message
+            );
+
+            // the fulfilled request leaves the pending ones along with its randomness
+            assert!(controller
+                .get_pending_requests()
+                .iter()
+                .all(|request| request.0 != signature_tasks[i].index));
+
+            let fulfilled = controller
+                .get_fulfilled_requests()
+                .into_iter()
+                .find(|request| request.0 == signature_tasks[i].index)
+                .unwrap();
+
+            assert_eq!(
+                fulfilled,
+                (
+                    signature_tasks[i].index,
+                    seeds[i].as_bytes().to_vec(),
+                    1,
+                    signature_tasks[i].assignment_block_height,
+                    randomness_output(&signatures[i])
+                )
+            );
+        }
+
+        assert!(controller.pending_signature_tasks().is_empty());
+
+        assert!(controller.get_pending_requests().is_empty());
+
+        assert_eq!(
+            controller
+                .get_fulfilled_requests()
+                .iter()
+                .map(|request| request.0)
+                .collect::<Vec<_>>(),
+            vec![1, 2, 3]
+        );
+    }
+
+    #[test]
+    fn rotate_committers_of_stalled_task() {
+        let mut controller = Controller::new(0x1234, RewardConfig::default());
+
+        register_nodes(&mut controller, 5);
+
+        let (private, public) = G1Scheme::keypair(&mut rand::thread_rng());
+
+        let epoch = controller.get_group(1).unwrap().epoch;
+
+        (0..5).for_each(|i| {
+            controller
+                .commit_dkg(
+                    String::from("0x") + &i.to_string(),
+                    1,
+                    epoch,
+                    public_poly_of(&controller, 1, &bincode::serialize(&public).unwrap()),
+                    vec![],
+                    vec![],
+                )
+                .unwrap();
+        });
+
+        let index = controller
+            .request(
+                String::from("user"),
+                String::from("seed"),
+                0,
+                DEFAULT_PRIORITY,
+            )
+            .unwrap();
+
+        let signature_task = controller.emit_signature_task().unwrap();
+
+        let previous_committers = controller.get_group(1).unwrap().committers.clone();
+
+        // the committers have until the deadline to fulfill the task
+        assert_eq!(
+            controller.rotate_signature_committers(index),
+            Err(ControllerError::SignatureDeadlineNotPassed(index))
+        );
+
+        controller.mine(SIGNATURE_TASK_DEADLINE_BLOCKS + 1);
+
+        let committers = controller.rotate_signature_committers(index).unwrap();
+
+        assert!(!committers.is_empty());
+
+        assert!(committers
+            .iter()
+            .all(|committer| !previous_committers.contains(committer)));
+
+        assert_eq!(
+            controller.get_stalled_committers(index),
+            &previous_committers[..]
+        );
+
+        // the task is emitted again for the new committers
+        assert_eq!(controller.emit_signature_task().unwrap().index, index);
+
+        let signature = G1Scheme::sign(&private, &signature_task.message).unwrap();
+
+        assert_eq!(
+            controller.submit_signature(
+                previous_committers[0].clone(),
+                index,
+                signature.clone(),
+                HashMap::new()
+            ),
+            Err(ControllerError::NotCommitter(
+                previous_committers[0].clone(),
+                1
+            ))
+        );
+
+        controller
+            .submit_signature(committers[0].clone(), index, signature, HashMap::new())
+            .unwrap();
+
+        assert!(controller.get_pending_requests().is_empty());
+    }
+
+    #[test]
+    fn tick_runs_every_overdue_transition() {
+        let mut controller = Controller::new(0x1234, RewardConfig::default());
+
+        controller.group_capacity = 6;
+
+        register_nodes(&mut controller, 18);
+
+        finalize_group(&mut controller, 1);
+
+        // group 2 falls short of its threshold of 4, group 3 doesn't commit at all
+        let epoch = controller.get_group(2).unwrap().epoch;
+
+        let mut members = controller
+            .get_group(2)
+            .unwrap()
+            .members
+            .keys()
+            .cloned()
+            .collect::<Vec<_>>();
+
+        members.sort();
+
+        for id_address in members[..3].iter() {
+            controller
+                .commit_dkg(
+                    id_address.clone(),
+                    2,
+                    epoch,
+                    fake_public_poly(&controller, 2, 1969),
+                    vec![],
+                    vec![],
+                )
+                .unwrap();
+        }
+
+        let signature_indices = (0..2)
+            .map(|_| {
+                controller
+                    .request(
+                        String::from("user"),
+                        String::from("seed"),
+                        100,
+                        DEFAULT_PRIORITY,
+                    )
+                    .unwrap()
+            })
+            .collect::<Vec<_>>();
+
+        // the second task has been rotated as often as it can be
+        controller
+            .signature_tasks
+            .pending
+            .get_mut(&signature_indices[1])
+            .unwrap()
+            .rotation_count = MAX_SIGNATURE_TASK_ROTATIONS;
+
+        // nothing is due before the deadlines
+        assert_eq!(controller.tick(DKG_DEADLINE_BLOCKS), TickReport::default());
+
+        let report = controller.tick(DKG_DEADLINE_BLOCKS + 1);
+
+        assert_eq!(
+            report,
+            TickReport {
+                retried_dkgs: vec![2],
+                disabled_groups: vec![3],
+                rotated_tasks: vec![signature_indices[0]],
+                refunded_tasks: vec![signature_indices[1]],
+            }
+        );
+
+        // the 3 silent members are dropped and the others run the DKG again
+        let group = controller.get_group(2).unwrap();
+
+        assert_eq!(group.size, 3);
+        assert_eq!(group.epoch, epoch + 1);
+        assert_eq!(controller.get_group(1).unwrap().state, GroupState::Active);
+        assert_eq!(controller.get_group(3).unwrap().state, GroupState::Disabled);
+
+        let signature_task = controller
+            .signature_tasks
+            .get(signature_indices[0])
+            .unwrap();
+
+        assert_eq!(signature_task.rotation_count, 1);
+        assert_eq!(
+            signature_task.deadline_block_height,
+            DKG_DEADLINE_BLOCKS + 1 + SIGNATURE_TASK_DEADLINE_BLOCKS
+        );
+
+        assert!(controller
+            .signature_tasks
+            .get(signature_indices[1])
+            .is_none());
+        assert_eq!(controller.get_requester_balance(String::from("user")), 100);
+
+        // the block height doesn't go back
+        assert_eq!(controller.tick(0), TickReport::default());
+        assert_eq!(controller.block_height, DKG_DEADLINE_BLOCKS + 1);
+    }
+
+    #[test]
+    fn cancelled_task_refunds_its_fee() {
+        let mut controller = Controller::new(0x1234, RewardConfig::default());
+
+        register_nodes(&mut controller, 5);
+
+        finalize_group(&mut controller, 1);
+
+        let fee = 1000;
+
+        let index = controller
+            .request(
+                String::from("user"),
+                String::from("seed"),
+                fee,
+                DEFAULT_PRIORITY,
+            )
+            .unwrap();
+
+        assert_eq!(controller.get_fee_pool(), fee);
+
+        // nobody fulfills the task, so its committers get rotated until the limit
+        for _ in 0..MAX_SIGNATURE_TASK_ROTATIONS {
+            assert_eq!(
+                controller.cancel_signature_task(index),
+                Err(ControllerError::SignatureDeadlineNotPassed(index))
+            );
+
+            controller.mine(SIGNATURE_TASK_DEADLINE_BLOCKS + 1);
+
+            assert_eq!(
+                controller.cancel_signature_task(index),
+                Err(ControllerError::RotationsNotExhausted(index))
+            );
+
+            controller.rotate_signature_committers(index).unwrap();
+        }
+
+        controller.mine(SIGNATURE_TASK_DEADLINE_BLOCKS + 1);
+
+        assert_eq!(
+            controller.rotate_signature_committers(index),
+            Err(ControllerError::RotationLimitReached(index))
+        );
+
+        assert_eq!(controller.cancel_signature_task(index), Ok(fee));
+
+        assert_eq!(controller.get_fee_pool(), 0);
+
+        assert_eq!(controller.get_requester_balance(String::from("user")), fee);
+
+        assert!(controller.get_pending_requests().is_empty());
+
+        assert_eq!(
+            controller.cancel_signature_task(index),
+            Err(ControllerError::TaskNotFound(index))
+        );
+    }
+
+    #[test]
+    fn cancel_pending_request() {
+        let mut controller = Controller::new(0x1234, RewardConfig::default());
+
+        register_nodes(&mut controller, 5);
+
+        finalize_group(&mut controller, 1);
+
+        let first = controller
+            .request(
+                String::from("user"),
+                String::from("first"),
+                100,
+                DEFAULT_PRIORITY,
+            )
+            .unwrap();
+
+        let second = controller
+            .request(
+                String::from("user"),
+                String::from("second"),
+                200,
+                DEFAULT_PRIORITY,
+            )
+            .unwrap();
+
+        assert_eq!(
+            controller.cancel_request(String::from("other"), first),
+            Err(ControllerError::NotRequester(String::from("other"), first))
+        );
+
+        assert_eq!(
+            controller.cancel_request(String::from("user"), first),
+            Ok(100)
+        );
+
+        assert_eq!(controller.get_requester_balance(String::from("user")), 100);
+
+        assert_eq!(controller.get_fee_pool(), 200);
+
+        // the cancelled request is skipped
+        assert_eq!(controller.emit_signature_task().unwrap().index, second);
+
+        assert!(controller.emit_signature_task().is_none());
+
+        assert_eq!(
+            controller.cancel_request(String::from("user"), second),
+            Err(ControllerError::TaskAlreadyAssigned(second))
+        );
+
+        assert_eq!(
+            controller.cancel_request(String::from("user"), first),
+            Err(ControllerError::TaskNotFound(first))
+        );
+    }
+
+    #[test]
+    fn cancel_fulfilled_request() {
+        let mut controller = Controller::new(0x1234, RewardConfig::default());
+
+        register_nodes(&mut controller, 5);
+
+        let (private, public) = G1Scheme::keypair(&mut rand::thread_rng());
+
+        let epoch = controller.get_group(1).unwrap().epoch;
+
+        (0..5).for_each(|i| {
+            controller
+                .commit_dkg(
+                    String::from("0x") + &i.to_string(),
+                    1,
+                    epoch,
+                    public_poly_of(&controller, 1, &bincode::serialize(&public).unwrap()),
+                    vec![],
+                    vec![],
+                )
+                .unwrap();
+        });
+
+        let index = controller
+            .request(
+                String::from("user"),
+                String::from("seed"),
+                100,
+                DEFAULT_PRIORITY,
+            )
+            .unwrap();
+
+        let signature_task = controller.emit_signature_task().unwrap();
+
+        let committer = controller.get_group(1).unwrap().committers[0].clone();
+
+        let signature = G1Scheme::sign(&private, &signature_task.message).unwrap();
+
+        controller
+            .submit_signature(committer, index, signature, HashMap::new())
+            .unwrap();
+
+        assert_eq!(
+            controller.cancel_request(String::from("user"), index),
+            Err(ControllerError::TaskAlreadyFulfilled(index))
+        );
+
+        assert_eq!(controller.get_requester_balance(String::from("user")), 0);
+    }
+
+    #[test]
+    fn reloaded_controller_continues_signature_tasks() {
+        let mut controller = Controller::new(0x1234, RewardConfig::default());
+
+        register_nodes(&mut controller, 5);
+
+        let (private, public) = G1Scheme::keypair(&mut rand::thread_rng());
+
+        let epoch = controller.get_group(1).unwrap().epoch;
+
+        (0..5).for_each(|i| {
+            controller
+                .commit_dkg(
+                    String::from("0x") + &i.to_string(),
+                    1,
+                    epoch,
+                    public_poly_of(&controller, 1, &bincode::serialize(&public).unwrap()),
+                    vec![],
+                    vec![],
+                )
+                .unwrap();
+        });
+
+        controller
+            .request(
+                String::from("user"),
+                String::from("first"),
+                0,
+                DEFAULT_PRIORITY,
+            )
+            .unwrap();
+        controller
+            .request(
+                String::from("user"),
+                String::from("second"),
+                0,
+                DEFAULT_PRIORITY,
+            )
+            .unwrap();
+
+        let first_task = controller.emit_signature_task().unwrap();
+
+        let path = std::env::temp_dir().join(format!("controller-{}.bin", rand::random::<u64>()));
+
+        controller.save(&path).unwrap();
+
+        let mut controller = Controller::load(&path).unwrap();
+
+        std::fs::remove_file(&path).unwrap();
+
+        // the first task was emitted before saving, so the second one comes next
+        let second_task = controller.emit_signature_task().unwrap();
+
+        assert_eq!(second_task.index, 2);
+        assert_eq!(second_task.seed, b"second");
+        assert!(controller.emit_signature_task().is_none());
+
+        let committer = controller.get_group(1).unwrap().committers[0].clone();
+
+        for signature_task in [first_task, second_task].iter() {
+            let signature = G1Scheme::sign(&private, &signature_task.message).unwrap();
+
+            // the group public key was restored from the file
+            controller
+                .submit_signature(
+                    committer.clone(),
+                    signature_task.index,
+                    signature.clone(),
+                    HashMap::new(),
+                )
+                .unwrap();
+
+            assert_eq!(controller.get_last_output(), randomness_output(&signature));
+        }
+
+        assert!(controller.pending_signature_tasks().is_empty());
+    }
+
+    async fn run_full_flow<C, S>(scheme: SchemeKind)
+    where
+        C: Curve,
+        S: Scheme<Public = <C as Curve>::Point, Private = <C as Curve>::Scalar>
+            + ThresholdScheme
+            + SignatureScheme,
+    {
+        let rng = &mut rand::thread_rng();
+        let (t, n) = (3, 5);
+
+        let mut controller = Controller::new(0x1234, RewardConfig::default());
+
+        controller.scheme = scheme;
+
+        register_nodes(&mut controller, n);
+
+        let epoch = controller.emit_dkg_task()[0].epoch;
+
+        let (mut board, phase0s) = setup::<C, S, _>(n, t, rng).unwrap();
+
+        let outputs = run_dkg::<C, S, _, _>(&mut board, phase0s, rng)
+            .await
+            .unwrap();
+
+        let public_key = bincode::serialize(outputs[0].public.public_key()).unwrap();
+
+        let public_poly = bincode::serialize(&outputs[0].public).unwrap();
+
+        (0..n).for_each(|i| {
+            controller
+                .commit_dkg(
+                    String::from("0x") + &i.to_string(),
+                    1,
+                    epoch,
+                    public_poly.clone(),
+                    vec![],
+                    vec![],
+                )
+                .unwrap();
+        });
+
+        controller
+            .request(
+                String::from("user"),
+                String::from("seed"),
+                0,
+                DEFAULT_PRIORITY,
+            )
+            .unwrap();
+
+        let signature_task = controller.emit_signature_task().unwrap();
+
+        let message = &signature_task.message;
+
+        let partials = outputs
+            .iter()
+            .map(|output| S::partial_sign(&output.share, message).unwrap())
+            .collect::<Vec<_>>();
+
+        let signature = S::aggregate(t, &partials).unwrap();
+
+        assert!(scheme.verify(&public_key, message, &signature));
+
+        let committer = controller.get_group(1).unwrap().committers[0].clone();
+
+        assert_eq!(
+            controller.get_randomness(signature_task.index),
+            Err(ControllerError::SignatureResultNotFound(
+                signature_task.index
+            ))
+        );
+
+        controller
+            .submit_signature(committer, signature_task.index, signature, HashMap::new())
+            .unwrap();
+
+        assert_eq!(
+            controller.get_randomness(signature_task.index),
+            Ok(controller.get_last_output())
+        );
+    }
+
+    /// Runs the DKG of the first group and commits its public polynomial and public shares
+    fn run_group_dkg(
+        controller: &mut Controller,
+        n: usize,
+        t: usize,
+    ) -> Vec<DKGOutput<bls12381::Curve>> {
+        register_nodes(controller, n);
+
+        let epoch = controller.emit_dkg_task()[0].epoch;
+
+        let outputs = dkg_outputs::<bls12381::Curve, G1Scheme>(n, t, FIXTURE_SEED);
+
+        let public_poly = &outputs[0].public;
+
+        let serialized_poly = bincode::serialize(public_poly).unwrap();
+
+        (0..n).for_each(|i| {
+            controller
+                .commit_dkg(
+                    String::from("0x") + &i.to_string(),
+                    1,
+                    epoch,
+                    serialized_poly.clone(),
+                    bincode::serialize(&public_poly.eval(i as u32).value).unwrap(),
+                    vec![],
+                )
+                .unwrap();
+        });
+
+        outputs
+    }
+
+    #[test]
+    fn aggregate_subsets_of_partials() {
+        let (t, n) = (3, 5);
+
+        let mut controller = Controller::new(0x1234, RewardConfig::default());
+
+        let outputs = run_group_dkg(&mut controller, n, t);
+
+        let message = b"fastest responders";
+
+        let partials = outputs
+            .iter()
+            .map(|output| {
+                (
+                    output.share.index,
+                    G1Scheme::partial_sign(&output.share, message).unwrap(),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let first = controller
+            .aggregate_subset(1, t, message, &partials[..3])
+            .unwrap();
+
+        let last = controller
+            .aggregate_subset(1, t, message, &partials[2..])
+            .unwrap();
+
+        // any subset recovers the same signature of the group key
+        assert_eq!(first, last);
+
+        let public_key = &controller.get_group(1).unwrap().public_key;
+
+        assert!(controller.scheme.verify(public_key, message, &first));
+
+        // a duplicated share doesn't count twice
+        let duplicated = vec![
+            partials[0].clone(),
+            partials[0].clone(),
+            partials[1].clone(),
+        ];
+
+        assert_eq!(
+            controller.aggregate_subset(1, t, message, &duplicated),
+            Err(ControllerError::NotEnoughPartials(2, t))
+        );
+    }
+
+    #[test]
+    fn fulfill_excludes_invalid_partial_signatures() {
+        let (t, n) = (3, 5);
+
+        let mut controller = Controller::new(0x1234, RewardConfig::default());
+
+        let outputs = run_group_dkg(&mut controller, n, t);
+
+        let signature_index = controller
+            .request(
+                String::from("user"),
+                String::from("seed"),
+                0,
+                DEFAULT_PRIORITY,
+            )
+            .unwrap();
+
+        let message = controller.emit_signature_task().unwrap().message;
+
+        // the first partial signature is made on another message
+        let partials = outputs
+            .iter()
+            .enumerate()
+            .map(|(i, output)| {
+                let message = if i == 0 { &b"wrong"[..] } else { &message[..] };
+                G1Scheme::partial_sign(&output.share, message).unwrap()
+            })
+            .collect::<Vec<_>>();
+
+        let signature = G1Scheme::aggregate(t, &partials[1..]).unwrap();
+
+        let partial_signatures = |bad_count: usize| {
+            partials
+                .iter()
+                .enumerate()
+                .map(|(i, partial)| {
+                    let partial = if i < bad_count {
+                        partials[0].clone()
+                    } else {
+                        partial.clone()
+                    };
+                    (String::from("0x") + &i.to_string(), partial)
+                })
+                .collect::<HashMap<_, _>>()
+        };
+
+        let committer = controller.get_group(1).unwrap().committers[0].clone();
+
+        // only 2 valid partial signatures remain
+        assert_eq!(
+            controller.submit_signature(
+                committer.clone(),
+                signature_index,
+                signature.clone(),
+                partial_signatures(3)
+            ),
+            Err(ControllerError::NotEnoughValidPartials(signature_index))
+        );
+
+        controller
+            .submit_signature(committer, signature_index, signature, partial_signatures(1))
+            .unwrap();
+
+        let signature_rewards = controller.verifiable_signature_rewards();
+
+        let signature_reward = signature_rewards[0];
+
+        assert_eq!(signature_reward.partial_signatures.len(), n - 1);
+        assert!(!signature_reward.partial_signatures.contains_key("0x0"));
+    }
+
+    #[test]
+    fn partial_signatures_are_bound_to_their_share_index() {
+        let (t, n) = (3, 5);
+
+        let mut controller = Controller::new(0x1234, RewardConfig::default());
+
+        let outputs = run_group_dkg(&mut controller, n, t);
+
+        let signature_index = controller
+            .request(
+                String::from("user"),
+                String::from("seed"),
+                0,
+                DEFAULT_PRIORITY,
+            )
+            .unwrap();
+
+        let message = controller.emit_signature_task().unwrap().message;
+
+        let partial = G1Scheme::partial_sign(&outputs[0].share, &message).unwrap();
+
+        // the signature is valid for the share of 0x0, but claims to be made by share 1
+        let mut relabeled: Eval<Vec<u8>> = bincode::deserialize(&partial).unwrap();
+
+        relabeled.index = 1;
+
+        assert_eq!(
+            controller.submit_partial(
+                signature_index,
+                String::from("0x0"),
+                bincode::serialize(&relabeled).unwrap()
+            ),
+            Err(ControllerError::InvalidPartialSignature(
+                String::from("0x0"),
+                signature_index
+            ))
+        );
+
+        assert_eq!(
+            controller.submit_partial(signature_index, String::from("0x1"), partial.clone()),
+            Err(ControllerError::InvalidPartialSignature(
+                String::from("0x1"),
+                signature_index
+            ))
+        );
+
+        assert_eq!(
+            controller.submit_partial(signature_index, String::from("0x0"), partial),
+            Ok(false)
+        );
+    }
+
+    #[test]
+    fn inconsistent_partial_public_keys_are_disqualified() {
+        let (t, n) = (3, 5);
+
+        let mut controller = Controller::new(0x1234, RewardConfig::default());
+
+        register_nodes(&mut controller, n);
+
+        let epoch = controller.emit_dkg_task()[0].epoch;
+
+        let outputs = dkg_outputs::<bls12381::Curve, G1Scheme>(n, t, FIXTURE_SEED);
+
+        let public_poly = &outputs[0].public;
+
+        let serialized_poly = bincode::serialize(public_poly).unwrap();
+
+        // 0x1 claims the share of 0x0 before the group is finalized, 0x4 the share of 0x3 after
+        for i in 0..n {
+            let share_index = if i == 1 || i == 4 { i - 1 } else { i };
+
+            controller
+                .commit_dkg(
+                    String::from("0x") + &i.to_string(),
+                    1,
+                    epoch,
+                    serialized_poly.clone(),
+                    bincode::serialize(&public_poly.eval(share_index as Idx).value).unwrap(),
+                    vec![],
+                )
+                .unwrap();
+        }
+
+        let group = controller.get_group(1).unwrap();
+
+        assert_eq!(group.state, GroupState::Active);
+        assert_eq!(group.size, n - 2);
+        assert_eq!(
+            group.dissenters,
+            vec![String::from("0x1"), String::from("0x4")]
+        );
+        assert!(!group.members.contains_key("0x1"));
+        assert!(!group.members.contains_key("0x4"));
+        assert!(!group.committers.contains(&String::from("0x4")));
+
+        // the public keys of the shares are those of the polynomial, so the threshold of
+        // them interpolates to the public key of the group
+        let public_shares = group
+            .members
+            .values()
+            .map(|member| Eval {
+                value: bincode::deserialize::<bls12381::G1>(&member.partial_public_key).unwrap(),
+                index: member.share_index(),
+            })
+            .collect::<Vec<_>>();
+
+        for public_share in public_shares.iter() {
+            assert_eq!(
+                public_share.value,
+                public_poly.eval(public_share.index).value
+            );
+        }
+
+        let public_key = PublicPoly::<bls12381::Curve>::recover(t, public_shares).unwrap();
+
+        assert_eq!(bincode::serialize(&public_key).unwrap(), group.public_key);
+
+        // the disqualified members get slashed like the other dissenters
+        controller.mine(DKG_DEADLINE_BLOCKS + 1);
+
+        assert!(controller.finalize_dkg(1, epoch));
+
+        assert_eq!(
+            controller.slashed,
+            vec![String::from("0x1"), String::from("0x4")]
+        );
+    }
+
+    #[test]
+    fn fulfill_replaces_invalid_aggregate_with_valid_partials() {
+        let (t, n) = (3, 5);
+
+        let mut controller = Controller::new(0x1234, RewardConfig::default());
+
+        let outputs = run_group_dkg(&mut controller, n, t);
+
+        let signature_index = controller
+            .request(
+                String::from("user"),
+                String::from("seed"),
+                0,
+                DEFAULT_PRIORITY,
+            )
+            .unwrap();
+
+        let message = controller.emit_signature_task().unwrap().message;
+
+        let partials = outputs
+            .iter()
+            .map(|output| G1Scheme::partial_sign(&output.share, &message).unwrap())
+            .collect::<Vec<_>>();
+
+        // the aggregate of the partial signatures on another message
+        let wrong_signature = G1Scheme::aggregate(
+            t,
+            &outputs
+                .iter()
+                .map(|output| G1Scheme::partial_sign(&output.share, b"wrong").unwrap())
+                .collect::<Vec<_>>(),
+        )
+        .unwrap();
+
+        for (i, partial) in partials.iter().enumerate().take(t - 1) {
+            assert_eq!(
+                controller.submit_partial(
+                    signature_index,
+                    String::from("0x") + &i.to_string(),
+                    partial.clone()
+                ),
+                Ok(false)
+            );
+        }
+
+        let committer = controller.get_group(1).unwrap().committers[0].clone();
+
+        // the stored partial signatures alone are below the threshold
+        assert_eq!(
+            controller.submit_signature(
+                committer.clone(),
+                signature_index,
+                wrong_signature.clone(),
+                HashMap::new()
+            ),
+            Err(ControllerError::InvalidSignature(signature_index))
+        );
+
+        assert!(controller.get_faulty_committers(signature_index).is_empty());
+
+        let mut partial_signatures = HashMap::new();
+
+        partial_signatures.insert(String::from("0x2"), partials[2].clone());
+
+        controller
+            .submit_signature(
+                committer.clone(),
+                signature_index,
+                wrong_signature,
+                partial_signatures,
+            )
+            .unwrap();
+
+        let signature = G1Scheme::aggregate(t, &partials[..t]).unwrap();
+
+        assert_eq!(
+            controller.get_randomness(signature_index),
+            Ok(randomness_output(&signature))
+        );
+
+        assert_eq!(
+            controller.get_faulty_committers(signature_index),
+            &[committer][..]
+        );
+
+        assert!(controller
+            .get_signature_confirmations(signature_index)
+            .unwrap()
+            .is_empty());
+
+        assert_eq!(
+            controller.verifiable_signature_rewards()[0]
+                .partial_signatures
+                .len(),
+            t
+        );
+    }
+
+    #[test]
+    fn randomness_results_serialize_to_json() {
+        let (t, n) = (3, 5);
+
+        let mut controller = Controller::new(0x1234, RewardConfig::default());
+
+        let outputs = run_group_dkg(&mut controller, n, t);
+
+        assert_eq!(controller.get_last_output_json(), None);
+
+        let signature_index = controller
+            .request(
+                String::from("user"),
+                String::from("seed"),
+                0,
+                DEFAULT_PRIORITY,
+            )
+            .unwrap();
+
+        let pending = controller.get_request_json(signature_index).unwrap();
+
+        assert_eq!(pending["index"], signature_index);
+        assert_eq!(pending["fulfilled"], false);
+        assert!(pending.get("signature").is_none());
+
+        let message = controller.emit_signature_task().unwrap().message;
+
+        for (i, output) in outputs.iter().enumerate().take(t) {
+            controller
+                .submit_partial(
+                    signature_index,
+                    String::from("0x") + &i.to_string(),
+                    G1Scheme::partial_sign(&output.share, &message).unwrap(),
+                )
+                .unwrap();
+        }
+
+        let json = controller.get_last_output_json().unwrap();
+
+        let json: serde_json::Value = serde_json::from_str(&json.to_string()).unwrap();
+
+        assert_eq!(json, controller.get_request_json(signature_index).unwrap());
+        assert_eq!(json["index"], signature_index);
+        assert_eq!(json["group_index"], 1);
+        assert_eq!(json["fulfilled"], true);
+        assert_eq!(json["message"], format!("0x{}", hex::encode(&message)));
+
+        let hex_field = |field: &str| {
+            let value = json[field].as_str().unwrap();
+
+            assert!(value.starts_with("0x"));
+
+            hex::decode(&value[2..]).unwrap()
+        };
+
+        let signature = hex_field("signature");
+
+        verify_randomness(&hex_field("group_pubkey"), &message, &signature).unwrap();
+
+        assert_eq!(
+            hex_field("randomness"),
+            randomness_from_signature(&signature)
+        );
+        assert_eq!(json["randomness_hash"], "Sha256");
+
+        for field in ["requested_at", "fulfilled_at"].iter() {
+            let timestamp = json[*field].as_str().unwrap();
+
+            assert_eq!(timestamp.len(), "1970-01-01T00:00:00Z".len());
+            assert!(timestamp.ends_with('Z'));
+        }
+
+        assert_eq!(format_timestamp(0), "1970-01-01T00:00:00Z");
+        assert_eq!(format_timestamp(951_782_400), "2000-02-29T00:00:00Z");
+        assert_eq!(format_timestamp(1_700_000_000), "2023-11-14T22:13:20Z");
+
+        assert_eq!(
+            controller.get_request_json(signature_index + 1),
+            Err(ControllerError::TaskNotFound(signature_index + 1))
+        );
+    }
+
+    #[test]
+    fn timestamps_follow_the_mock_clock() {
+        let (t, n) = (3, 5);
+
+        let clock = MockClock::new(951_782_400);
+
+        let mut controller =
+            Controller::new_with_clock(0x1234, RewardConfig::default(), clock.clone());
+
+        let outputs = run_group_dkg(&mut controller, n, t);
+
+        let signature_index = controller
+            .request(
+                String::from("user"),
+                String::from("seed"),
+                0,
+                DEFAULT_PRIORITY,
+            )
+            .unwrap();
+
+        assert_eq!(
+            controller.get_request_json(signature_index).unwrap()["requested_at"],
+            "2000-02-29T00:00:00Z"
+        );
+
+        // the task outlives its deadline, in blocks, while the clock moves on
+        controller.mine(SIGNATURE_TASK_DEADLINE_BLOCKS + 1);
+
+        clock.advance(90);
+
+        let rotated = controller
+            .rotate_signature_committers(signature_index)
+            .unwrap();
+
+        assert!(!rotated.is_empty());
+
+        let message = controller.emit_signature_task().unwrap().message;
+
+        for (i, output) in outputs.iter().enumerate().take(t) {
+            controller
+                .submit_partial(
+                    signature_index,
+                    String::from("0x") + &i.to_string(),
+                    G1Scheme::partial_sign(&output.share, &message).unwrap(),
+                )
+                .unwrap();
+        }
+
+        let json = controller.get_request_json(signature_index).unwrap();
+
+        assert_eq!(json["requested_at"], "2000-02-29T00:00:00Z");
+        assert_eq!(json["fulfilled_at"], "2000-02-29T00:01:30Z");
+    }
+
+    #[test]
+    fn request_binary_seed() {
+        let (t, n) = (3, 5);
+
+        let mut controller = Controller::new(0x1234, RewardConfig::default());
+
+        let outputs = run_group_dkg(&mut controller, n, t);
 
-    fn emit_signature_task(&self) -> &SignatureTask {
-        &self.signature_task.as_ref().unwrap()
-    }
+        // a block hash is not valid UTF-8
+        let seed = vec![0xff, 0xfe, 0x00, 0x80, 0xc3, 0x28];
 
-    fn mine(&mut self, block_number: usize) {
-        self.block_height += block_number;
-    }
-}
+        assert!(String::from_utf8(seed.clone()).is_err());
 
-impl Transactions for Controller {
-    fn node_register(
-        &mut self,
-        id_address: String,
-        id_public_key: Vec<u8>,
-        endpoint: String,
-        reward_address: String,
-    ) -> bool {
-        if self.nodes.contains_key(&id_address) {
-            return false;
-        }
+        let signature_index = controller
+            .request_bytes(String::from("user"), seed.clone(), 0, DEFAULT_PRIORITY)
+            .unwrap();
 
-        // mock: staking
+        let signature_task = controller.emit_signature_task().unwrap();
 
-        let node = Node {
-            id_address: id_address.clone(),
-            id_public_key,
-            endpoint,
-            reward_address,
-            state: true,
-            pending_until_block: 0,
-            staking: 50000,
-        };
+        assert_eq!(signature_task.seed, seed);
 
-        self.nodes.insert(id_address.clone(), node);
+        // the raw bytes are signed, chained with the last output
+        let mut chained_seed = format!("{:016x}", 0x1234).into_bytes();
 
-        self.rewards.insert(id_address.clone(), 0);
+        chained_seed.extend_from_slice(&seed);
 
-        // TODO: now supports single group only
-        if self.groups.is_empty() {
-            let group = Group {
-                index: 1,
-                epoch: 0,
-                capacity: 10,
-                size: 0,
-                threshold: DEFAULT_MINIMUM_THRESHOLD,
-                state: false,
-                public_key: vec![],
-                members: HashMap::new(),
-                committers: vec![],
-                commit_cache: HashMap::new(),
-            };
-            self.groups.insert(1, group);
+        assert_eq!(
+            signature_task.message,
+            PlainRequestHasher.hash(&chained_seed, "user", 0)
+        );
+
+        for (i, output) in outputs.iter().enumerate().take(t) {
+            controller
+                .submit_partial(
+                    signature_index,
+                    String::from("0x") + &i.to_string(),
+                    G1Scheme::partial_sign(&output.share, &signature_task.message).unwrap(),
+                )
+                .unwrap();
         }
 
-        let group = self.groups.get_mut(&1).unwrap();
+        let proof = controller.get_last_output_with_proof().unwrap();
 
-        group.size += 1;
+        assert_eq!(proof.message, signature_task.message);
 
-        let member = Member {
-            index: group.size,
-            id_address: id_address.clone(),
-            partial_public_key: vec![],
-        };
+        verify_randomness(&proof.group_pubkey, &proof.message, &proof.signature).unwrap();
 
-        group.members.insert(id_address, member);
+        assert_eq!(
+            controller.get_request_json(signature_index).unwrap()["seed"],
+            format!("0x{}", hex::encode(&seed))
+        );
+    }
 
-        let minimum = minimum_threshold(group.size);
+    #[test]
+    fn last_output_is_reduced_to_a_range() {
+        let (t, n) = (3, 5);
 
-        group.threshold = max(DEFAULT_MINIMUM_THRESHOLD, minimum);
+        let mut controller = Controller::new(0x1234, RewardConfig::default());
 
-        if group.size >= 3 {
-            group.epoch += 1;
+        let outputs = run_group_dkg(&mut controller, n, t);
 
-            let mut members = HashMap::new();
+        // the initial entropy is used until a task has been fulfilled
+        let initial = controller.get_last_output_in_range(1, 6);
 
-            for (member_id_address, member) in group.members.iter() {
-                members.insert(member_id_address.clone(), member.index.clone());
-            }
+        assert!((1..=6).contains(&initial));
 
-            let dkg_task = DKGTask {
-                group_index: group.index,
-                epoch: group.epoch,
-                size: group.size,
-                threshold: group.threshold,
-                members,
-                assignment_block_height: self.block_height,
-            };
+        let signature_index = controller
+            .request(
+                String::from("user"),
+                String::from("seed"),
+                0,
+                DEFAULT_PRIORITY,
+            )
+            .unwrap();
 
-            self.dkg_task = Some(dkg_task);
-            // self.emit_dkg_task(dkg_task);
+        let message = controller.emit_signature_task().unwrap().message;
+
+        for (i, output) in outputs.iter().enumerate().take(t) {
+            controller
+                .submit_partial(
+                    signature_index,
+                    String::from("0x") + &i.to_string(),
+                    G1Scheme::partial_sign(&output.share, &message).unwrap(),
+                )
+                .unwrap();
         }
 
-        true
-    }
+        let randomness = controller.get_last_output_with_proof().unwrap().randomness;
 
-    fn node_quit(&mut self, _id_address: String) {
-        todo!()
-    }
+        let value = controller.get_last_output_in_range(1, 6);
 
-    fn node_activate(&mut self, _id_address: String) {
-        todo!()
-    }
+        assert!((1..=6).contains(&value));
+        assert_eq!(value, RangeTransform::new(1, 6).apply(&randomness));
+        assert_eq!(controller.get_last_output_in_range(7, 7), 7);
 
-    fn redeem(&mut self, _id_address: String) {
-        todo!()
-    }
+        let permutation = controller.get_last_output_transformed(&ShuffleTransform::new(n));
 
-    fn claim(&mut self, _id_address: String) {
-        todo!()
+        assert_eq!(permutation, ShuffleTransform::new(n).apply(&randomness));
     }
 
-    fn commit_dkg(
-        &mut self,
-        id_address: String,
-        group_index: usize,
-        group_epoch: usize,
-        public_key: Vec<u8>,
-        partial_public_key: Vec<u8>,
-        disqualified_nodes: Vec<String>,
-    ) -> bool {
-        let group = self.groups.get_mut(&group_index).unwrap();
+    #[test]
+    fn submit_partials_one_by_one() {
+        let (t, n) = (3, 5);
 
-        if !group.members.contains_key(&id_address) || group.epoch != group_epoch {
-            return false;
-        }
+        let mut controller = Controller::new(0x1234, RewardConfig::default());
 
-        let commit_result = CommitResult {
-            group_epoch,
-            public_key,
-            disqualified_nodes: disqualified_nodes.clone(),
-        };
+        let outputs = run_group_dkg(&mut controller, n, t);
 
-        let commit_cache = CommitCache {
-            commit_result,
-            partial_public_key: partial_public_key.clone(),
-        };
+        let signature_index = controller
+            .request(
+                String::from("user"),
+                String::from("seed"),
+                0,
+                DEFAULT_PRIORITY,
+            )
+            .unwrap();
 
-        // TODO when next group epoch increments, clean commit_cache, committers
-        if group.commit_cache.contains_key(&id_address) {
-            return false;
-        }
+        let message = controller.emit_signature_task().unwrap().message;
+
+        let partials = outputs
+            .iter()
+            .map(|output| G1Scheme::partial_sign(&output.share, &message).unwrap())
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            controller.submit_partial(signature_index, String::from("0x0"), partials[0].clone()),
+            Ok(false)
+        );
+
+        // neither a duplicate nor an invalid partial signature counts towards the threshold
+        assert_eq!(
+            controller.submit_partial(signature_index, String::from("0x0"), partials[0].clone()),
+            Err(ControllerError::PartialAlreadySubmitted(
+                String::from("0x0"),
+                signature_index
+            ))
+        );
+
+        assert_eq!(
+            controller.submit_partial(signature_index, String::from("0x1"), partials[2].clone()),
+            Err(ControllerError::InvalidPartialSignature(
+                String::from("0x1"),
+                signature_index
+            ))
+        );
+
+        assert_eq!(
+            controller.submit_partial(signature_index, String::from("0xx"), partials[1].clone()),
+            Err(ControllerError::NotGroupMember(String::from("0xx"), 1))
+        );
+
+        assert_eq!(
+            controller.submit_partial(signature_index, String::from("0x1"), partials[1].clone()),
+            Ok(false)
+        );
+
+        assert!(controller.get_randomness(signature_index).is_err());
+
+        // the t-th valid partial signature fulfills the task
+        assert_eq!(
+            controller.submit_partial(signature_index, String::from("0x2"), partials[2].clone()),
+            Ok(true)
+        );
+
+        let signature = G1Scheme::aggregate(t, &partials[..t]).unwrap();
+
+        assert_eq!(controller.get_last_output(), randomness_output(&signature));
+
+        assert_eq!(
+            controller.get_randomness(signature_index),
+            Ok(randomness_output(&signature))
+        );
+
+        assert_eq!(
+            controller.submit_partial(signature_index, String::from("0x3"), partials[3].clone()),
+            Err(ControllerError::TaskAlreadyFulfilled(signature_index))
+        );
+    }
 
-        group.commit_cache.insert(id_address.clone(), commit_cache);
+    #[test]
+    fn too_few_valid_partials_keep_task_pending() {
+        let (t, n) = (3, 5);
 
-        fn get_identical_over_threshold_commitment(
-            controller: &Controller,
-            group_index: usize,
-        ) -> Option<CommitCache> {
-            let group = controller.groups.get(&group_index).unwrap();
+        let mut controller = Controller::new(0x1234, RewardConfig::default());
 
-            let mut map: HashMap<u64, usize> = HashMap::new();
+        let outputs = run_group_dkg(&mut controller, n, t);
 
-            for commit_cache in group.commit_cache.values() {
-                let count = map
-                    .entry(Controller::calculate_hash(&commit_cache.commit_result))
-                    .or_insert(0);
+        let signature_index = controller
+            .request(
+                String::from("user"),
+                String::from("seed"),
+                0,
+                DEFAULT_PRIORITY,
+            )
+            .unwrap();
 
-                *count += 1;
+        let message = controller.emit_signature_task().unwrap().message;
 
-                if *count >= group.threshold {
-                    return Some(commit_cache.clone());
-                }
-            }
+        let partials = outputs
+            .iter()
+            .map(|output| G1Scheme::partial_sign(&output.share, &message).unwrap())
+            .collect::<Vec<_>>();
 
-            None
+        for i in 0..2 {
+            assert_eq!(
+                controller.submit_partial(signature_index, format!("0x{}", i), partials[i].clone()),
+                Ok(false)
+            );
         }
 
-        if group.state {
-            // it's no good for a qualified node to miscommits here. So far we don't verify this commitment.
-            let member = group.members.get_mut(&id_address).unwrap();
+        // the partial signature of 0x1 doesn't count once it left the group
+        assert_eq!(
+            controller.node_deregister(String::from("0x1")),
+            DeregisterResult::Removed
+        );
+
+        assert_eq!(
+            controller.submit_partial(signature_index, String::from("0x2"), partials[2].clone()),
+            Ok(false)
+        );
+
+        assert_eq!(
+            controller.aggregate_partials(1, &[partials[0].clone(), partials[2].clone()]),
+            Err(ControllerError::NotEnoughPartials(t - 1, t))
+        );
+
+        assert!(controller.signature_tasks.get(signature_index).is_some());
+        assert!(controller.get_randomness(signature_index).is_err());
+
+        // another valid partial signature fulfills the task
+        assert_eq!(
+            controller.submit_partial(signature_index, String::from("0x3"), partials[3].clone()),
+            Ok(true)
+        );
+
+        let signature = G1Scheme::aggregate(
+            t,
+            &[
+                partials[0].clone(),
+                partials[2].clone(),
+                partials[3].clone(),
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(
+            controller.get_randomness(signature_index),
+            Ok(randomness_output(&signature))
+        );
+    }
 
-            member.partial_public_key = partial_public_key;
-        } else {
-            match get_identical_over_threshold_commitment(self, group_index) {
-                None => {}
-                Some(commit_cache) => {
-                    let group = self.groups.get_mut(&group_index).unwrap();
+    #[test]
+    fn verify_partials_reports_invalid_indices() {
+        let (t, n) = (3, 5);
+
+        let mut controller = Controller::new(0x1234, RewardConfig::default());
+
+        let outputs = run_group_dkg(&mut controller, n, t);
+
+        let message = b"message";
+
+        // the member 0x{i} has the index i + 1
+        let mut partials = outputs
+            .iter()
+            .enumerate()
+            .map(|(i, output)| {
+                (
+                    i + 1,
+                    G1Scheme::partial_sign(&output.share, message).unwrap(),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            controller.verify_partials(1, message, &partials),
+            Ok(vec![])
+        );
+
+        // a partial signature of another message, one of another member and an unknown index
+        partials[3].1 = G1Scheme::partial_sign(&outputs[4].share, message).unwrap();
+        partials[1].1 = G1Scheme::partial_sign(&outputs[1].share, b"other").unwrap();
+        partials.push((9, partials[0].1.clone()));
+
+        let invalid = controller.verify_partials(1, message, &partials).unwrap();
+
+        assert_eq!(invalid, vec![2, 4, 9]);
+
+        #[cfg(feature = "parallel")]
+        assert_eq!(
+            controller.verify_partials_parallel(1, message, &partials),
+            Ok(invalid)
+        );
+
+        assert_eq!(
+            controller.verify_partials(2, message, &partials),
+            Err(ControllerError::GroupNotFound(2))
+        );
+    }
 
-                    group.state = true;
+    #[test]
+    fn signature_ready_listener_fires_once_at_threshold() {
+        let (t, n) = (3, 5);
 
-                    group.size -= commit_cache.commit_result.disqualified_nodes.len();
+        let mut controller = Controller::new(0x1234, RewardConfig::default());
 
-                    group.public_key = commit_cache.commit_result.public_key.clone();
+        let outputs = run_group_dkg(&mut controller, n, t);
 
-                    commit_cache
-                        .commit_result
-                        .disqualified_nodes
-                        .iter()
-                        .for_each(|disqualified_id_address| {
-                            group.members.remove(disqualified_id_address);
-                        });
+        let events = Arc::new(Mutex::new(vec![]));
 
-                    for (id_address, cache) in group.commit_cache.iter_mut() {
-                        if !disqualified_nodes.contains(id_address) {
-                            let member = group.members.get_mut(id_address).unwrap();
+        let received = events.clone();
 
-                            member.partial_public_key = cache.partial_public_key.clone();
-                        }
-                    }
+        controller.on_signature_ready(move |event| received.lock().unwrap().push(event.clone()));
 
-                    // choose 3 committers randomly by last randomness output
+        let signature_index = controller
+            .request(
+                String::from("user"),
+                String::from("seed"),
+                0,
+                DEFAULT_PRIORITY,
+            )
+            .unwrap();
 
-                    let hash1 = Controller::calculate_hash(&self.last_output) as usize;
+        let message = controller.emit_signature_task().unwrap().message;
 
-                    let hash2 = Controller::calculate_hash(&hash1) as usize;
+        let partials = outputs
+            .iter()
+            .map(|output| G1Scheme::partial_sign(&output.share, &message).unwrap())
+            .collect::<Vec<_>>();
 
-                    let hash3 = Controller::calculate_hash(&hash2) as usize;
+        for (i, partial) in partials.iter().enumerate().take(t) {
+            assert_eq!(events.lock().unwrap().len(), 0);
 
-                    let mut index_member_map: HashMap<usize, String> = HashMap::new();
+            controller
+                .submit_partial(
+                    signature_index,
+                    String::from("0x") + &i.to_string(),
+                    partial.clone(),
+                )
+                .unwrap();
+        }
 
-                    group.members.iter().for_each(|(id_address, member)| {
-                        index_member_map.insert(member.index, id_address.clone());
-                    });
+        let signature = G1Scheme::aggregate(t, &partials[..t]).unwrap();
+
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![SignatureReadyEvent {
+                signature_index,
+                group_index: 1,
+                group_epoch: controller.get_group(1).unwrap().epoch,
+                signature: signature.clone(),
+            }]
+        );
+
+        // neither a late partial signature nor a confirmation fires it again
+        assert!(controller
+            .submit_partial(signature_index, String::from("0x3"), partials[3].clone())
+            .is_err());
+
+        let committers = controller.get_group(1).unwrap().committers.clone();
+
+        for committer in committers {
+            let _ = controller.submit_signature(
+                committer,
+                signature_index,
+                signature.clone(),
+                HashMap::new(),
+            );
+        }
 
-                    let mut qualified_indices = group
-                        .members
-                        .values()
-                        .map(|member| member.index)
-                        .collect::<Vec<_>>();
+        assert_eq!(events.lock().unwrap().len(), 1);
+    }
 
-                    let c1 = map_to_qualified_indices(
-                        hash1 % (qualified_indices.len() + 1),
-                        &qualified_indices,
-                    );
+    #[test]
+    fn group_finalized_listener_fires_once_at_threshold() {
+        let mut controller = Controller::new(0x1234, RewardConfig::default());
 
-                    qualified_indices.retain(|&x| x != c1);
+        register_nodes(&mut controller, 5);
 
-                    let c2 = map_to_qualified_indices(
-                        hash2 % (qualified_indices.len() + 1),
-                        &qualified_indices,
-                    );
+        let events = Arc::new(Mutex::new(vec![]));
 
-                    qualified_indices.retain(|&x| x != c2);
+        let received = events.clone();
 
-                    let c3 = map_to_qualified_indices(
-                        hash3 % (qualified_indices.len() + 1),
-                        &qualified_indices,
-                    );
+        controller.on_group_finalized(move |event| received.lock().unwrap().push(event.clone()));
 
-                    group
-                        .committers
-                        .push(index_member_map.get(&c1).unwrap().clone());
+        let group = controller.get_group(1).unwrap().clone();
 
-                    group
-                        .committers
-                        .push(index_member_map.get(&c2).unwrap().clone());
+        for i in 0..group.threshold {
+            assert_eq!(events.lock().unwrap().len(), 0);
 
-                    group
-                        .committers
-                        .push(index_member_map.get(&c3).unwrap().clone());
+            controller
+                .commit_dkg(
+                    String::from("0x") + &i.to_string(),
+                    1,
+                    group.epoch,
+                    fake_public_poly(&controller, 1, 1969),
+                    vec![],
+                    vec![],
+                )
+                .unwrap();
+        }
 
-                    fn map_to_qualified_indices(
-                        mut index: usize,
-                        qualified_indices: &[usize],
-                    ) -> usize {
-                        let max = qualified_indices.iter().max().unwrap();
+        let group = controller.get_group(1).unwrap().clone();
 
-                        while !qualified_indices.contains(&index) {
-                            index = (index + 1) % (max + 1);
-                        }
+        assert_eq!(group.state, GroupState::Active);
 
-                        index
-                    }
-                }
-            }
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![GroupFinalizedEvent {
+                group_index: 1,
+                epoch: group.epoch,
+                public_key: fake_public_key(1969),
+                members: (0..5)
+                    .map(|i| String::from("0x") + &i.to_string())
+                    .collect(),
+                committers: group.committers.clone(),
+            }]
+        );
+
+        // the late commits of an active group don't fire it again
+        for i in group.threshold..5 {
+            controller
+                .commit_dkg(
+                    String::from("0x") + &i.to_string(),
+                    1,
+                    group.epoch,
+                    fake_public_poly(&controller, 1, 1969),
+                    vec![],
+                    vec![],
+                )
+                .unwrap();
         }
 
-        true
+        assert_eq!(events.lock().unwrap().len(), 1);
     }
 
-    fn request(&mut self, message: String) -> bool {
-        let valid_group_indices = self.valid_group_indices();
+    #[test]
+    fn aggregation_needs_exactly_threshold_partials() {
+        let (t, n) = (3, 5);
 
-        if valid_group_indices.is_empty() {
-            return false;
-        }
-        // mock: payment for request
+        let mut controller = Controller::new(0x1234, RewardConfig::default());
 
-        let mut assignment_group_index = self.last_group_index;
+        let outputs = run_group_dkg(&mut controller, n, t);
 
-        loop {
-            assignment_group_index = (assignment_group_index + 1) % (self.groups.len() + 1);
+        let message = b"message";
 
-            if valid_group_indices.contains(&assignment_group_index) {
-                break;
-            }
-        }
+        let partials = outputs
+            .iter()
+            .map(|output| G1Scheme::partial_sign(&output.share, message).unwrap())
+            .collect::<Vec<_>>();
 
-        self.signature_count += 1;
+        // under-supply
+        assert_eq!(
+            controller.aggregate_partials(1, &partials[..t - 1]),
+            Err(ControllerError::NotEnoughPartials(t - 1, t))
+        );
 
-        let signature_task = SignatureTask {
-            index: self.signature_count,
+        // over-supply, along with a partial signature of another member
+        let mut partial_signatures = (0..n)
+            .map(|i| (format!("0x{}", i), partials[i].clone()))
+            .collect::<HashMap<_, _>>();
+
+        partial_signatures.insert(String::from("0x0"), partials[4].clone());
+
+        assert_eq!(
+            controller.aggregate_partials(1, &partials),
+            Err(ControllerError::ThresholdCountMismatch(1, n, t))
+        );
+
+        let trimmed = controller.trim_partials(1, message, &partial_signatures);
+
+        // the valid partial signatures of the lowest member indices are kept
+        assert_eq!(trimmed, partials[1..t + 1].to_vec());
+
+        let signature = controller.aggregate_partials(1, &trimmed).unwrap();
+
+        assert!(controller.scheme.verify(
+            &controller.get_group(1).unwrap().public_key,
             message,
-            group_index: assignment_group_index,
-            assignment_block_height: self.block_height,
+            &signature
+        ));
+    }
+
+    #[test]
+    fn fulfill_rewards_committer_and_contributors() {
+        let (t, n) = (3, 5);
+
+        let reward_config = RewardConfig {
+            signature_reward: 10,
+            committer_reward: 25,
         };
 
-        self.signature_task = Some(signature_task.clone());
-        // self.emit_signature_task(signature_task.clone());
+        let mut controller = Controller::new(0x1234, reward_config);
+
+        let outputs = run_group_dkg(&mut controller, n, t);
+
+        let committer = controller.get_group(1).unwrap().committers[0].clone();
+
+        for seed in ["first", "second"].iter() {
+            let signature_index = controller
+                .request(
+                    String::from("user"),
+                    String::from(*seed),
+                    100,
+                    DEFAULT_PRIORITY,
+                )
+                .unwrap();
+
+            let message = controller.emit_signature_task().unwrap().message;
+
+            let partials = outputs
+                .iter()
+                .map(|output| G1Scheme::partial_sign(&output.share, &message).unwrap())
+                .collect::<Vec<_>>();
+
+            let signature = G1Scheme::aggregate(t, &partials).unwrap();
+
+            let partial_signatures = partials
+                .into_iter()
+                .enumerate()
+                .map(|(i, partial)| (String::from("0x") + &i.to_string(), partial))
+                .collect::<HashMap<_, _>>();
+
+            controller
+                .submit_signature(
+                    committer.clone(),
+                    signature_index,
+                    signature,
+                    partial_signatures,
+                )
+                .unwrap();
+        }
 
-        self.pending_signature_tasks
-            .insert(signature_task.index, signature_task);
+        (0..n).for_each(|i| {
+            let id_address = String::from("0x") + &i.to_string();
 
-        self.last_group_index = assignment_group_index;
+            // the committer contributed a partial signature as well
+            let expected = if id_address == committer {
+                2 * (10 + 25)
+            } else {
+                2 * 10
+            };
 
-        true
+            assert_eq!(controller.get_node_reward(id_address), expected);
+        });
+
+        assert_eq!(controller.get_node_reward(String::from("0x9")), 0);
+
+        // each task paid 25 to the committer and 10 to each of the 5 contributors
+        assert_eq!(controller.get_fee_pool(), 2 * 100 - 2 * (25 + 5 * 10));
     }
 
-    fn fulfill(
-        &mut self,
-        id_address: String,
-        signature_index: usize,
-        signature: Vec<u8>,
-        partial_signatures: HashMap<String, Vec<u8>>,
-    ) -> bool {
-        if !self.pending_signature_tasks.contains_key(&signature_index) {
-            return false;
-        }
+    #[test]
+    fn member_public_shares_match_public_polynomial() {
+        let (t, n) = (3, 5);
 
-        let signature_task = self
-            .pending_signature_tasks
-            .get(&signature_index)
-            .unwrap()
-            .clone();
+        let mut controller = Controller::new(0x1234, RewardConfig::default());
 
-        let group = self
-            .groups
-            .get(&signature_task.group_index)
-            .unwrap()
-            .clone();
+        register_nodes(&mut controller, n);
 
-        if !group.committers.contains(&id_address) {
-            return false;
-        }
+        let epoch = controller.emit_dkg_task()[0].epoch;
 
-        let message = &signature_task.message;
+        let outputs = dkg_outputs::<bls12381::Curve, G1Scheme>(n, t, FIXTURE_SEED);
+
+        let public_poly = &outputs[0].public;
+
+        let serialized_poly = bincode::serialize(public_poly).unwrap();
 
-        let group_public_key: G1 = bincode::deserialize(&group.public_key).unwrap();
+        // the group is finalized once the threshold of members committed, none of them
+        // discloses the public key of its share
+        for i in 0..t {
+            assert_eq!(
+                controller.get_member_public_shares(1),
+                Err(ControllerError::GroupNotReady(1))
+            );
 
-        match SigScheme::verify(&group_public_key, &message.as_bytes(), &signature) {
-            Ok(()) => {}
-            Err(_err) => return false,
+            controller
+                .commit_dkg(
+                    String::from("0x") + &i.to_string(),
+                    1,
+                    epoch,
+                    serialized_poly.clone(),
+                    vec![],
+                    vec![],
+                )
+                .unwrap();
         }
 
-        let committer = self.nodes.get_mut(&id_address).unwrap();
+        let public_shares = controller.get_member_public_shares(1).unwrap();
 
-        let committer_address = committer.id_address.clone();
+        // node 0x{i} is the member of index i + 1, which holds the share of index i
+        assert_eq!(public_shares.len(), n);
 
-        let committer_reward = self.rewards.get_mut(&committer.reward_address).unwrap();
+        for (i, (idx, public_share)) in public_shares.into_iter().enumerate() {
+            assert_eq!(idx, i as Idx);
 
-        *committer_reward += COMMITTER_REWARD_PER_SIGNATURE;
+            let public_share: <bls12381::Curve as Curve>::Point =
+                bincode::deserialize(&public_share).unwrap();
 
-        partial_signatures.keys().for_each(|member_id_address| {
-            let node = self.nodes.get(member_id_address).unwrap();
+            assert_eq!(public_share, public_poly.eval(idx).value);
+        }
+    }
 
-            let member_reward = self.rewards.get_mut(&node.reward_address).unwrap();
+    #[test]
+    fn share_keys_are_evaluated_once_per_epoch() {
+        let (t, n) = (3, 5);
 
-            *member_reward += REWARD_PER_SIGNATURE;
-        });
+        let mut controller = Controller::new(0x1234, RewardConfig::default());
 
-        self.last_output = Controller::calculate_hash(&signature);
+        let outputs = run_group_dkg(&mut controller, n, t);
 
-        let signature_reward = SignatureReward {
-            signature_task,
-            committer: committer_address,
-            group,
-            partial_signatures,
-        };
+        let evaluated_poly = &controller.evaluated_polys[&1];
 
-        self.verifiable_signature_rewards
-            .insert(signature_index, signature_reward);
+        assert_eq!(evaluated_poly.len(), n);
+        assert_eq!(evaluated_poly.misses(), n);
 
-        self.pending_signature_tasks.remove(&signature_index);
+        // the public shares are read from the cache
+        for (idx, public_share) in controller.get_member_public_shares(1).unwrap() {
+            assert_eq!(
+                public_share,
+                bincode::serialize(&outputs[0].public.eval(idx).value).unwrap()
+            );
+        }
 
-        true
+        assert_eq!(controller.evaluated_polys[&1].misses(), n);
+
+        // a new epoch brings a new polynomial
+        controller.create_dkg_task(1);
+
+        assert!(!controller.evaluated_polys.contains_key(&1));
     }
 
-    fn challenge_reward(&mut self, id_address: String, signature_index: usize) -> bool {
-        if !self
-            .verifiable_signature_rewards
-            .contains_key(&signature_index)
-        {
-            return false;
-        }
+    #[tokio::test]
+    async fn regrouping_keeps_pubkey_history() {
+        let rng = &mut rand::thread_rng();
 
-        let signature_reward = self
-            .verifiable_signature_rewards
-            .get(&signature_index)
-            .unwrap();
+        let mut controller = Controller::new(0x1234, RewardConfig::default());
 
-        let group = &signature_reward.group;
+        let outputs = run_group_dkg(&mut controller, 5, 3);
 
-        let committer = self.nodes.get_mut(&signature_reward.committer).unwrap();
+        let first_epoch = controller.get_group(1).unwrap().epoch;
 
-        let committer_address = &committer.id_address.clone();
+        let first_key = controller.get_group(1).unwrap().public_key.clone();
 
-        let message = &signature_reward.signature_task.message;
+        // a signature produced during the first epoch
+        controller
+            .request(
+                String::from("user"),
+                String::from("seed"),
+                0,
+                DEFAULT_PRIORITY,
+            )
+            .unwrap();
 
-        // TODO need a BLS-Aggregation Verification instead of loop to save computational fee
-        for (member_id_address, partial_signature) in signature_reward.partial_signatures.iter() {
-            let public_key_as_bytes = &group
-                .members
-                .get(member_id_address)
-                .unwrap()
-                .partial_public_key;
+        let message = controller.emit_signature_task().unwrap().message;
+
+        let partials = outputs
+            .iter()
+            .map(|output| G1Scheme::partial_sign(&output.share, &message).unwrap())
+            .collect::<Vec<_>>();
 
-            let public_key = bincode::deserialize(public_key_as_bytes).unwrap();
+        let signature = G1Scheme::aggregate(3, &partials).unwrap();
 
-            // Note: partial_signature contains participant index
-            let res = SigScheme::verify(&public_key, message.as_bytes(), partial_signature);
+        // a new node joins the group, which has to run its DKG again
+        controller
+            .node_register(
+                String::from("0x5"),
+                id_public_key(5),
+                endpoint_of(5),
+                String::from("0x5"),
+                proof_of(5),
+            )
+            .unwrap();
 
-            match res {
-                Ok(()) => {}
-                Err(_err) => {
-                    committer.staking -= COMMITTER_PENALTY_PER_SIGNATURE;
+        let group = controller.get_group(1).unwrap();
 
-                    self.freeze_node(committer_address, 0);
+        assert_eq!(group.state, GroupState::Regrouping);
 
-                    if !self.rewards.contains_key(&id_address) {
-                        self.rewards.insert(id_address.clone(), 0);
-                    }
+        let (second_epoch, threshold) = (group.epoch, group.threshold);
 
-                    let challenger_reward = self.rewards.get_mut(&id_address).unwrap();
+        let (mut board, phase0s) =
+            setup::<bls12381::Curve, G1Scheme, _>(6, threshold, rng).unwrap();
 
-                    *challenger_reward += CHALLENGE_REWARD_PER_SIGNATURE;
+        let outputs = run_dkg::<bls12381::Curve, G1Scheme, _, _>(&mut board, phase0s, rng)
+            .await
+            .unwrap();
 
-                    self.verifiable_signature_rewards.remove(&signature_index);
+        let second_key = bincode::serialize(outputs[0].public.public_key()).unwrap();
+
+        let second_poly = bincode::serialize(&outputs[0].public).unwrap();
+
+        (0..6).for_each(|i| {
+            controller
+                .commit_dkg(
+                    String::from("0x") + &i.to_string(),
+                    1,
+                    second_epoch,
+                    second_poly.clone(),
+                    vec![],
+                    vec![],
+                )
+                .unwrap();
+        });
 
-                    return true;
-                }
-            }
-        }
+        assert_eq!(controller.get_group(1).unwrap().public_key, second_key);
 
-        self.verifiable_signature_rewards.remove(&signature_index);
+        assert_eq!(
+            controller.get_group_pubkey_at(1, first_epoch),
+            Ok(first_key.as_slice())
+        );
 
-        false
-    }
-}
+        assert_eq!(
+            controller.get_group_pubkey_at(1, second_epoch),
+            Ok(second_key.as_slice())
+        );
 
-impl Views for Controller {
-    fn get_last_output(&self) -> u64 {
-        self.last_output
-    }
+        assert_eq!(
+            controller.get_group_pubkey_at(1, 1),
+            Err(ControllerError::PublicKeyNotFound(1, 1))
+        );
 
-    fn get_node(&self, id_address: String) -> &Node {
-        self.nodes.get(&id_address).unwrap()
-    }
+        // the signature of the first epoch still verifies against the key of its epoch
+        let historical_key = controller.get_group_pubkey_at(1, first_epoch).unwrap();
 
-    fn get_group(&self, index: usize) -> &Group {
-        self.groups.get(&index).unwrap()
+        assert!(controller
+            .scheme
+            .verify(historical_key, &message, &signature));
     }
 
-    fn valid_group_indices(&self) -> Vec<usize> {
-        self.groups
-            .values()
-            .filter(|g| g.state)
-            .map(|g| g.index)
-            .collect::<Vec<_>>()
+    #[tokio::test]
+    async fn full_flow_with_g1_scheme() {
+        run_full_flow::<bls12381::Curve, G1Scheme>(SchemeKind::G1).await;
     }
 
-    fn pending_signature_tasks(&self) -> Vec<&SignatureTask> {
-        self.pending_signature_tasks.values().collect::<Vec<_>>()
+    #[tokio::test]
+    async fn full_flow_with_g2_scheme() {
+        run_full_flow::<bls12381::G2Curve, G2Scheme>(SchemeKind::G2).await;
     }
 
-    fn verifiable_signature_rewards(&self) -> Vec<&SignatureReward> {
-        self.verifiable_signature_rewards
-            .values()
-            .collect::<Vec<_>>()
+    #[tokio::test]
+    async fn full_flow_with_bn254_scheme() {
+        run_full_flow::<bn254::G2Curve, bn254::G2Scheme>(SchemeKind::Bn254).await;
     }
-}
-
-#[cfg(test)]
-pub mod tests {
 
     #[test]
     fn test_mut() {
@@ -0,0 +1,462 @@
+use crate::contract::{
+    Controller, ControllerError, DKGTask, GroupSnapshot, MockHelper, SignatureTask, Transactions,
+    Views,
+};
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::io::{BufReader, BufWriter, ErrorKind, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// The largest frame, in bytes, read from or written to a connection
+pub const MAX_FRAME_SIZE: u64 = 1 << 20;
+
+/// A call to the controller, sent by a client as a bincode frame
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum ControllerRequest {
+    NodeRegister {
+        id_address: String,
+        id_public_key: Vec<u8>,
+        rpc_endpoint: String,
+        reward_address: String,
+        proof_of_possession: Vec<u8>,
+    },
+    EmitDkgTask,
+    CommitDkg {
+        id_address: String,
+        group_index: usize,
+        group_epoch: usize,
+        public_poly: Vec<u8>,
+        partial_public_key: Vec<u8>,
+        disqualified_nodes: Vec<String>,
+    },
+    Request {
+        requester: String,
+        seed: String,
+        fee: u64,
+        priority: u8,
+    },
+    EmitSignatureTask,
+    // the task gets fulfilled once the threshold of partial signatures is reached
+    SubmitPartial {
+        signature_index: usize,
+        id_address: String,
+        partial_signature: Vec<u8>,
+    },
+    // a committer fulfills the task, or confirms the signature it was fulfilled with
+    SubmitSignature {
+        id_address: String,
+        signature_index: usize,
+        signature: Vec<u8>,
+        partial_signatures: HashMap<String, Vec<u8>>,
+    },
+    GetGroup {
+        index: usize,
+    },
+}
+
+/// The answer of the server to a `ControllerRequest`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum ControllerResponse {
+    Registered(usize),
+    DkgTasks(Vec<DKGTask>),
+    Committed,
+    Requested(usize),
+    SignatureTask(Option<SignatureTask>),
+    PartialSubmitted(bool),
+    SignatureSubmitted,
+    Group(GroupSnapshot),
+    // the controller rejected the call
+    Error(String),
+}
+
+/// Applies a request to the controller
+pub fn handle(controller: &mut Controller, request: ControllerRequest) -> ControllerResponse {
+    fn respond<T>(
+        res: Result<T, ControllerError>,
+        into: impl FnOnce(T) -> ControllerResponse,
+    ) -> ControllerResponse {
+        match res {
+            Ok(value) => into(value),
+            Err(err) => ControllerResponse::Error(err.to_string()),
+        }
+    }
+
+    match request {
+        ControllerRequest::NodeRegister {
+            id_address,
+            id_public_key,
+            rpc_endpoint,
+            reward_address,
+            proof_of_possession,
+        } => respond(
+            controller.node_register(
+                id_address,
+                id_public_key,
+                rpc_endpoint,
+                reward_address,
+                proof_of_possession,
+            ),
+            ControllerResponse::Registered,
+        ),
+        ControllerRequest::EmitDkgTask => {
+            ControllerResponse::DkgTasks(controller.emit_dkg_task().into_iter().cloned().collect())
+        }
+        ControllerRequest::CommitDkg {
+            id_address,
+            group_index,
+            group_epoch,
+            public_poly,
+            partial_public_key,
+            disqualified_nodes,
+        } => respond(
+            controller.commit_dkg(
+                id_address,
+                group_index,
+                group_epoch,
+                public_poly,
+                partial_public_key,
+                disqualified_nodes,
+            ),
+            |_| ControllerResponse::Committed,
+        ),
+        ControllerRequest::Request {
+            requester,
+            seed,
+            fee,
+            priority,
+        } => respond(
+            controller.request(requester, seed, fee, priority),
+            ControllerResponse::Requested,
+        ),
+        ControllerRequest::EmitSignatureTask => {
+            ControllerResponse::SignatureTask(controller.emit_signature_task())
+        }
+        ControllerRequest::SubmitPartial {
+            signature_index,
+            id_address,
+            partial_signature,
+        } => respond(
+            controller.submit_partial(signature_index, id_address, partial_signature),
+            ControllerResponse::PartialSubmitted,
+        ),
+        ControllerRequest::SubmitSignature {
+            id_address,
+            signature_index,
+            signature,
+            partial_signatures,
+        } => respond(
+            controller.submit_signature(id_address, signature_index, signature, partial_signatures),
+            |_| ControllerResponse::SignatureSubmitted,
+        ),
+        ControllerRequest::GetGroup { index } => respond(
+            controller.get_group_snapshot(index),
+            ControllerResponse::Group,
+        ),
+    }
+}
+
+/// Serves the controller to every client connecting to the listener, one thread per
+/// connection. Each connection sends any number of requests, each answered in order.
+pub fn serve(listener: TcpListener, controller: Arc<Mutex<Controller>>) -> std::io::Result<()> {
+    for stream in listener.incoming() {
+        let stream = stream?;
+
+        let controller = controller.clone();
+
+        thread::spawn(move || {
+            if let Err(err) = serve_connection(stream, &controller) {
+                warn!("event=connection_failed error=\"{}\"", err);
+            }
+        });
+    }
+
+    Ok(())
+}
+
+fn serve_connection(stream: TcpStream, controller: &Mutex<Controller>) -> bincode::Result<()> {
+    let mut reader = BufReader::new(stream.try_clone()?);
+
+    let mut writer = BufWriter::new(stream);
+
+    loop {
+        let request: ControllerRequest = match frame_config().deserialize_from(&mut reader) {
+            Ok(request) => request,
+            // the client closed the connection
+            Err(err) if is_eof(&err) => return Ok(()),
+            Err(err) => return Err(err),
+        };
+
+        // a handler panicking on another connection doesn't take the server down
+        let mut guard = controller.lock().unwrap_or_else(|err| {
+            warn!("event=controller_lock_poisoned");
+
+            err.into_inner()
+        });
+
+        let response = handle(&mut guard, request);
+
+        drop(guard);
+
+        frame_config().serialize_into(&mut writer, &response)?;
+
+        writer.flush()?;
+    }
+}
+
+// frames over `MAX_FRAME_SIZE` are rejected rather than allocated
+fn frame_config() -> bincode::Config {
+    let mut config = bincode::config();
+
+    config.limit(MAX_FRAME_SIZE);
+
+    config
+}
+
+fn is_eof(err: &bincode::Error) -> bool {
+    match err.as_ref() {
+        bincode::ErrorKind::Io(err) => err.kind() == ErrorKind::UnexpectedEof,
+        _ => false,
+    }
+}
+
+/// A connection to a controller server
+pub struct ControllerClient {
+    reader: BufReader<TcpStream>,
+    writer: BufWriter<TcpStream>,
+}
+
+impl ControllerClient {
+    pub fn connect<A: ToSocketAddrs>(address: A) -> std::io::Result<Self> {
+        let stream = TcpStream::connect(address)?;
+
+        Ok(Self {
+            reader: BufReader::new(stream.try_clone()?),
+            writer: BufWriter::new(stream),
+        })
+    }
+
+    /// Sends a request and waits for its response
+    pub fn call(&mut self, request: &ControllerRequest) -> bincode::Result<ControllerResponse> {
+        frame_config().serialize_into(&mut self.writer, request)?;
+
+        self.writer.flush()?;
+
+        frame_config().deserialize_from(&mut self.reader)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contract::{proof_of_possession, GroupState, RewardConfig, DEFAULT_PRIORITY};
+    use crate::fixtures::{active_group, FIXTURE_SEED};
+    use rand::{rngs::StdRng, SeedableRng};
+    use std::io::Read;
+    use std::net::SocketAddr;
+    use threshold_bls::schemes::bls12_381::G1Scheme;
+    use threshold_bls::sig::{Scheme, ThresholdScheme};
+
+    fn spawn_server() -> SocketAddr {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+
+        let address = listener.local_addr().unwrap();
+
+        let controller = Arc::new(Mutex::new(Controller::new(0x1234, RewardConfig::default())));
+
+        thread::spawn(move || serve(listener, controller));
+
+        address
+    }
+
+    fn register(client: &mut ControllerClient, i: u8) -> ControllerResponse {
+        let (private, public) = G1Scheme::keypair(&mut StdRng::seed_from_u64(i as u64));
+
+        let id_public_key = bincode::serialize(&public).unwrap();
+
+        client
+            .call(&ControllerRequest::NodeRegister {
+                id_address: format!("0x{}", i),
+                proof_of_possession: proof_of_possession::<G1Scheme>(&private, &id_public_key)
+                    .unwrap(),
+                id_public_key,
+                rpc_endpoint: format!("127.0.0.1:{}", 8000 + i as u16),
+                reward_address: format!("0x{}", i),
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn clients_register_and_read_the_group() {
+        let address = spawn_server();
+
+        let mut client = ControllerClient::connect(address).unwrap();
+
+        for i in 0..3 {
+            assert!(matches!(
+                register(&mut client, i),
+                ControllerResponse::Registered(index) if index == i as usize + 1
+            ));
+        }
+
+        // the controller rejects the call, the connection stays usable
+        assert!(matches!(
+            register(&mut client, 0),
+            ControllerResponse::Error(_)
+        ));
+
+        let group = match client
+            .call(&ControllerRequest::GetGroup { index: 1 })
+            .unwrap()
+        {
+            ControllerResponse::Group(group) => group,
+            response => panic!("unexpected response {:?}", response),
+        };
+
+        assert_eq!(group.size, 3);
+        assert_eq!(group.state, GroupState::WaitingForDkg);
+        assert_eq!(group.members[2].id_address, "0x2");
+
+        match client.call(&ControllerRequest::EmitDkgTask).unwrap() {
+            ControllerResponse::DkgTasks(tasks) => assert_eq!(tasks[0].epoch, group.epoch),
+            response => panic!("unexpected response {:?}", response),
+        }
+
+        // the state is shared between the connections
+        let mut other_client = ControllerClient::connect(address).unwrap();
+
+        assert!(matches!(
+            other_client
+                .call(&ControllerRequest::GetGroup { index: 1 })
+                .unwrap(),
+            ControllerResponse::Group(other_group) if other_group == group
+        ));
+    }
+
+    #[test]
+    fn oversized_frames_are_rejected() {
+        let address = spawn_server();
+
+        let mut stream = TcpStream::connect(address).unwrap();
+
+        // a `NodeRegister` whose id-address claims to be larger than any frame
+        let mut frame = 0u32.to_le_bytes().to_vec();
+
+        frame.extend_from_slice(&(MAX_FRAME_SIZE * 4).to_le_bytes());
+
+        stream.write_all(&frame).unwrap();
+
+        // the server drops the connection instead of waiting for the rest
+        let mut buf = vec![];
+
+        assert_eq!(stream.read_to_end(&mut buf).unwrap(), 0);
+
+        // and keeps serving the other clients
+        let mut client = ControllerClient::connect(address).unwrap();
+
+        assert!(matches!(
+            register(&mut client, 0),
+            ControllerResponse::Registered(1)
+        ));
+    }
+
+    #[test]
+    fn committers_confirm_the_signature() {
+        let (t, n) = (3, 5);
+
+        let (mut controller, outputs) = active_group(n, t, FIXTURE_SEED);
+
+        let signature_index = match handle(
+            &mut controller,
+            ControllerRequest::Request {
+                requester: String::from("0xuser"),
+                seed: String::from("seed"),
+                fee: 0,
+                priority: DEFAULT_PRIORITY,
+            },
+        ) {
+            ControllerResponse::Requested(signature_index) => signature_index,
+            response => panic!("unexpected response {:?}", response),
+        };
+
+        let message = match handle(&mut controller, ControllerRequest::EmitSignatureTask) {
+            ControllerResponse::SignatureTask(Some(task)) => task.message,
+            response => panic!("unexpected response {:?}", response),
+        };
+
+        let partial_signatures = outputs
+            .iter()
+            .enumerate()
+            .map(|(i, output)| {
+                (
+                    format!("0x{}", i),
+                    G1Scheme::partial_sign(&output.share, &message).unwrap(),
+                )
+            })
+            .collect::<HashMap<_, _>>();
+
+        // the threshold of partial signatures fulfills the task on behalf of the first
+        // committer
+        for i in 0..t {
+            let id_address = format!("0x{}", i);
+
+            assert!(matches!(
+                handle(
+                    &mut controller,
+                    ControllerRequest::SubmitPartial {
+                        signature_index,
+                        partial_signature: partial_signatures[&id_address].clone(),
+                        id_address,
+                    }
+                ),
+                ControllerResponse::PartialSubmitted(fulfilled) if fulfilled == (i == t - 1)
+            ));
+        }
+
+        let committers = controller.get_group(1).unwrap().committers.clone();
+
+        let partials = (0..t)
+            .map(|i| partial_signatures[&format!("0x{}", i)].clone())
+            .collect::<Vec<_>>();
+
+        let signature = G1Scheme::aggregate(t, &partials).unwrap();
+
+        // another committer confirms the signature
+        assert!(matches!(
+            handle(
+                &mut controller,
+                ControllerRequest::SubmitSignature {
+                    id_address: committers[1].clone(),
+                    signature_index,
+                    signature,
+                    partial_signatures: partial_signatures.clone(),
+                }
+            ),
+            ControllerResponse::SignatureSubmitted
+        ));
+
+        assert_eq!(
+            controller.get_signature_confirmations(signature_index),
+            Ok(&committers[1..2])
+        );
+
+        // and a third one disagrees with it
+        let response = handle(
+            &mut controller,
+            ControllerRequest::SubmitSignature {
+                id_address: committers[2].clone(),
+                signature_index,
+                signature: vec![0; 96],
+                partial_signatures,
+            },
+        );
+
+        assert!(matches!(
+            response,
+            ControllerResponse::Error(err)
+                if err == ControllerError::ConflictingSignature(signature_index).to_string()
+        ));
+    }
+}
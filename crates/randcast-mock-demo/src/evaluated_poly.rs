@@ -0,0 +1,152 @@
+use crate::contract::SchemeKind;
+use std::collections::HashMap;
+use threshold_bls::poly::Idx;
+
+/// Memoizes the evaluations of the serialized public polynomial of a group, i.e. the
+/// serialized public keys of the shares of its members, so that each of them is only
+/// computed once
+#[derive(Debug)]
+pub struct EvaluatedPoly {
+    scheme: SchemeKind,
+    public_poly: Vec<u8>,
+    evaluations: HashMap<Idx, Vec<u8>>,
+    hits: usize,
+    misses: usize,
+}
+
+impl EvaluatedPoly {
+    pub fn new(scheme: SchemeKind, public_poly: Vec<u8>) -> Self {
+        EvaluatedPoly {
+            scheme,
+            public_poly,
+            evaluations: HashMap::new(),
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    pub fn public_poly(&self) -> &[u8] {
+        &self.public_poly
+    }
+
+    /// Returns the serialized public key of the share of index `idx`, see
+    /// `SchemeKind::eval_public_poly`. None if the polynomial doesn't deserialize.
+    pub fn eval(&mut self, idx: Idx) -> Option<Vec<u8>> {
+        if let Some(public_share) = self.evaluations.get(&idx) {
+            self.hits += 1;
+
+            return Some(public_share.clone());
+        }
+
+        let public_share = self.scheme.eval_public_poly(&self.public_poly, idx)?;
+
+        self.misses += 1;
+
+        self.evaluations.insert(idx, public_share.clone());
+
+        Some(public_share)
+    }
+
+    /// Returns the cached public key of the share of index `idx` without computing it
+    pub fn get(&self, idx: Idx) -> Option<&[u8]> {
+        self.evaluations
+            .get(&idx)
+            .map(|public_share| public_share.as_slice())
+    }
+
+    /// Replaces the polynomial, e.g. once the group ran its DKG again in a new epoch. The
+    /// cached evaluations are dropped unless the polynomial stays the same.
+    pub fn update(&mut self, public_poly: &[u8]) {
+        if public_poly != self.public_poly.as_slice() {
+            self.public_poly = public_poly.to_vec();
+
+            self.evaluations.clear();
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.evaluations.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.evaluations.is_empty()
+    }
+
+    /// The number of evaluations read from the cache
+    pub fn hits(&self) -> usize {
+        self.hits
+    }
+
+    /// The number of evaluations which had to be computed
+    pub fn misses(&self) -> usize {
+        self.misses
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use threshold_bls::{
+        curve::bls12381,
+        group::Curve,
+        poly::{Poly, PublicPoly},
+    };
+
+    type C = bls12381::Curve;
+
+    fn random_public_poly(degree: usize) -> PublicPoly<C> {
+        Poly::<<C as Curve>::Scalar>::new(degree).commit::<<C as Curve>::Point>()
+    }
+
+    #[test]
+    fn cached_evaluations_match_the_polynomial() {
+        let n = 21;
+
+        let public = random_public_poly(10);
+
+        let mut evaluated =
+            EvaluatedPoly::new(SchemeKind::G1, bincode::serialize(&public).unwrap());
+
+        for _ in 0..3 {
+            for idx in 0..n {
+                assert_eq!(
+                    evaluated.eval(idx).unwrap(),
+                    bincode::serialize(&public.eval(idx).value).unwrap()
+                );
+            }
+        }
+
+        // every index was computed once, then read twice from the cache
+        assert_eq!(evaluated.len(), n as usize);
+        assert_eq!(evaluated.misses(), n as usize);
+        assert_eq!(evaluated.hits(), 2 * n as usize);
+
+        // the same polynomial keeps the cache
+        evaluated.update(&bincode::serialize(&public).unwrap());
+
+        assert_eq!(evaluated.len(), n as usize);
+
+        // a new polynomial drops it
+        let other = random_public_poly(10);
+
+        let serialized_other = bincode::serialize(&other).unwrap();
+
+        evaluated.update(&serialized_other);
+
+        assert!(evaluated.is_empty());
+        assert_eq!(evaluated.get(0), None);
+        assert_eq!(
+            evaluated.eval(0).unwrap(),
+            bincode::serialize(&other.eval(0).value).unwrap()
+        );
+        assert_eq!(evaluated.public_poly(), serialized_other.as_slice());
+    }
+
+    #[test]
+    fn malformed_polynomials_are_not_cached() {
+        let mut evaluated = EvaluatedPoly::new(SchemeKind::G1, vec![1, 2, 3]);
+
+        assert_eq!(evaluated.eval(0), None);
+        assert!(evaluated.is_empty());
+    }
+}
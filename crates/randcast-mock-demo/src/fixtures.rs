@@ -0,0 +1,209 @@
+use crate::actions::{run_dkg, setup, setup_with_private_keys};
+use crate::contract::{
+    proof_of_possession, Controller, Group, MockHelper, RewardConfig, Transactions, Views,
+    DEFAULT_PRIORITY,
+};
+use crate::verify::RandomnessProof;
+use dkg_core::primitives::types::DKGOutput;
+use futures::executor::block_on;
+use rand::{rngs::StdRng, SeedableRng};
+use threshold_bls::{
+    curve::bls12381::{self, PairingCurve as BLS12_381},
+    group::Curve,
+    poly::PublicPoly,
+    sig::{G1Scheme, Scheme, ThresholdScheme},
+};
+
+pub const FIXTURE_SEED: u64 = 1969;
+
+/// Runs a DKG among `n` fresh nodes with threshold `t` and returns the output of every
+/// node, i.e. the shares of a group which is ready to sign. The same seed always gives
+/// the same outputs.
+pub fn dkg_outputs<C, S>(n: usize, t: usize, seed: u64) -> Vec<DKGOutput<C>>
+where
+    C: Curve,
+    // We need to bind the Curve's Point and Scalars to the Scheme
+    S: Scheme<Public = <C as Curve>::Point, Private = <C as Curve>::Scalar>,
+{
+    let rng = &mut StdRng::seed_from_u64(seed);
+
+    let (mut board, phase0s) = setup::<C, S, _>(n, t, rng).unwrap();
+
+    // the in-memory board never has to wait, so the DKG can run on the current thread
+    block_on(run_dkg::<C, S, _, _>(&mut board, phase0s, rng)).unwrap()
+}
+
+/// Registers `n` nodes to a new controller on G1, runs the DKG of their group with
+/// threshold `t` and commits its result from every node. Node 0x{i} is the member of
+/// index i + 1 and holds the share of the output i.
+pub fn active_group(
+    n: usize,
+    t: usize,
+    seed: u64,
+) -> (Controller, Vec<DKGOutput<bls12381::Curve>>) {
+    let rng = &mut StdRng::seed_from_u64(seed);
+
+    let mut controller = Controller::new(seed, RewardConfig::default());
+
+    // all the nodes join the same group
+    controller.group_capacity = controller.group_capacity.max(n);
+
+    let (mut board, phase0s, private_keys) =
+        setup_with_private_keys::<bls12381::Curve, G1Scheme<BLS12_381>, _>(n, t, rng).unwrap();
+
+    for (i, (phase0, private_key)) in phase0s.iter().zip(private_keys.iter()).enumerate() {
+        let id_public_key = bincode::serialize(&phase0.info.public_key).unwrap();
+
+        let proof =
+            proof_of_possession::<G1Scheme<BLS12_381>>(private_key, &id_public_key).unwrap();
+
+        controller
+            .node_register(
+                format!("0x{}", i),
+                id_public_key,
+                format!("127.0.0.1:{}", 8000 + i),
+                format!("0x{}", i),
+                proof,
+            )
+            .unwrap();
+    }
+
+    let epoch = controller.emit_dkg_task()[0].epoch;
+
+    let outputs = block_on(run_dkg::<bls12381::Curve, G1Scheme<BLS12_381>, _, _>(
+        &mut board, phase0s, rng,
+    ))
+    .unwrap();
+
+    let public_poly = &outputs[0].public;
+
+    let serialized_poly = bincode::serialize(public_poly).unwrap();
+
+    for i in 0..n {
+        controller
+            .commit_dkg(
+                format!("0x{}", i),
+                1,
+                epoch,
+                serialized_poly.clone(),
+                bincode::serialize(&public_poly.eval(i as u32).value).unwrap(),
+                vec![],
+            )
+            .unwrap();
+    }
+
+    (controller, outputs)
+}
+
+/// Returns an active group of `n` members with threshold `t` along with the DKG output
+/// of every member and the public polynomial of the group, see `active_group`
+pub fn finalized_group(
+    n: usize,
+    t: usize,
+    seed: u64,
+) -> (
+    Group,
+    Vec<DKGOutput<bls12381::Curve>>,
+    PublicPoly<bls12381::Curve>,
+) {
+    let (controller, outputs) = active_group(n, t, seed);
+
+    let group = controller.get_group(1).unwrap().clone();
+
+    let public_poly = outputs[0].public.clone();
+
+    (group, outputs, public_poly)
+}
+
+/// Returns a controller whose group 1 of `n` members with threshold `t` is ready to take
+/// requests
+pub fn controller_with_active_group(n: usize, t: usize) -> Controller {
+    active_group(n, t, FIXTURE_SEED).0
+}
+
+impl Controller {
+    /// Runs a whole round on a new controller, the way the demo does: registers `n` nodes,
+    /// runs the DKG of their group with threshold `t`, commits its result, requests a
+    /// randomness and submits the partial signatures of the members until it is fulfilled.
+    /// Returns the proof of the output, which is the same for the same seed.
+    pub fn simulate_full_round(n: usize, t: usize, seed: u64) -> RandomnessProof {
+        let (mut controller, outputs) = active_group(n, t, seed);
+
+        let signature_index = controller
+            .request(
+                String::from("0xuser"),
+                format!("{:016x}", seed),
+                0,
+                DEFAULT_PRIORITY,
+            )
+            .unwrap();
+
+        let message = controller.emit_signature_task().unwrap().message;
+
+        for (i, output) in outputs.iter().enumerate() {
+            let partial = G1Scheme::<BLS12_381>::partial_sign(&output.share, &message).unwrap();
+
+            // the task is fulfilled as soon as the threshold is reached
+            if controller
+                .submit_partial(signature_index, format!("0x{}", i), partial)
+                .unwrap()
+            {
+                break;
+            }
+        }
+
+        controller.get_last_output_with_proof().unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contract::GroupState;
+
+    #[test]
+    fn fixture_outputs_share_one_public_key() {
+        let (t, n) = (3, 5);
+
+        let (group, outputs, public_poly) = finalized_group(n, t, 7);
+
+        assert_eq!(outputs.len(), n);
+        assert!(outputs.iter().all(|output| output.public == public_poly));
+
+        assert_eq!(group.state, GroupState::Active);
+        assert_eq!(group.size, n);
+        assert_eq!(
+            group.public_key,
+            bincode::serialize(public_poly.public_key()).unwrap()
+        );
+
+        // the fixtures are deterministic
+        let (_, _, other_public_poly) = finalized_group(n, t, 7);
+
+        assert_eq!(other_public_poly, public_poly);
+
+        let controller = controller_with_active_group(n, t);
+
+        assert_eq!(controller.valid_group_indices(), vec![1]);
+    }
+
+    #[test]
+    fn full_round_returns_a_valid_proof() {
+        let (t, n) = (3, 5);
+
+        let proof = Controller::simulate_full_round(n, t, 7);
+
+        let (group, _, _) = finalized_group(n, t, 7);
+
+        assert_eq!(proof.group_index, 1);
+        assert_eq!(proof.epoch, group.epoch);
+        assert_eq!(proof.group_pubkey, group.public_key);
+        assert_eq!(proof.randomness, proof.hash.randomness(&proof.signature));
+
+        proof.verify().unwrap();
+
+        // the round is deterministic
+        assert_eq!(Controller::simulate_full_round(n, t, 7), proof);
+        assert_ne!(Controller::simulate_full_round(n, t, 8), proof);
+    }
+}
@@ -2,4 +2,19 @@ pub mod actions;
 
 pub mod contract;
 
+pub mod controller_server;
+
+pub mod evaluated_poly;
+
+#[cfg(any(test, feature = "fixtures"))]
+pub mod fixtures;
+
+pub mod logging;
+
+pub mod node_client;
+
 pub mod test_helpers;
+
+pub mod transform;
+
+pub mod verify;
@@ -0,0 +1,151 @@
+use log::{LevelFilter, Log, Metadata, Record, SetLoggerError};
+
+/// Prints every record as a single line of `key=value` pairs, e.g.
+/// `level=info target=randcast_mock_demo::contract event=node_registered id_address=0x0`,
+/// so that the output of the demo can be parsed line by line
+pub struct LineLogger {
+    level: LevelFilter,
+}
+
+impl LineLogger {
+    pub fn new(level: LevelFilter) -> Self {
+        LineLogger { level }
+    }
+}
+
+impl Log for LineLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.level
+    }
+
+    fn log(&self, record: &Record) {
+        if self.enabled(record.metadata()) {
+            println!("{}", format_record(record));
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+/// Formats a record the way `LineLogger` prints it
+pub fn format_record(record: &Record) -> String {
+    format!(
+        "level={} target={} {}",
+        record.level().to_string().to_lowercase(),
+        record.target(),
+        record.args()
+    )
+}
+
+/// Installs a `LineLogger` as the global logger. The milestones of the demo are logged
+/// at the info level, `verbose` adds the details such as every partial signature.
+pub fn init(verbose: bool) -> Result<(), SetLoggerError> {
+    let level = if verbose {
+        LevelFilter::Debug
+    } else {
+        LevelFilter::Info
+    };
+
+    log::set_boxed_logger(Box::new(LineLogger::new(level)))?;
+
+    log::set_max_level(level);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contract::{Transactions, DEFAULT_PRIORITY};
+    use crate::fixtures::controller_with_active_group;
+    use std::sync::{Arc, Mutex};
+    use std::thread::{self, ThreadId};
+
+    // the logger is global, so the lines of the tests running on other threads are dropped
+    struct CapturingLogger {
+        thread: ThreadId,
+        lines: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl Log for CapturingLogger {
+        fn enabled(&self, _metadata: &Metadata) -> bool {
+            thread::current().id() == self.thread
+        }
+
+        fn log(&self, record: &Record) {
+            if self.enabled(record.metadata()) {
+                self.lines.lock().unwrap().push(format_record(record));
+            }
+        }
+
+        fn flush(&self) {}
+    }
+
+    fn event_of(line: &str) -> Option<&str> {
+        line.split(' ')
+            .find(|field| field.starts_with("event="))
+            .map(|field| &field["event=".len()..])
+    }
+
+    #[test]
+    fn milestones_are_logged_in_order() {
+        let lines = Arc::new(Mutex::new(vec![]));
+
+        log::set_boxed_logger(Box::new(CapturingLogger {
+            thread: thread::current().id(),
+            lines: lines.clone(),
+        }))
+        .unwrap();
+
+        log::set_max_level(LevelFilter::Info);
+
+        let mut controller = controller_with_active_group(5, 3);
+
+        controller
+            .request(
+                String::from("user"),
+                String::from("seed"),
+                0,
+                DEFAULT_PRIORITY,
+            )
+            .unwrap();
+
+        let lines = lines.lock().unwrap();
+
+        assert!(lines
+            .iter()
+            .all(|line| line.starts_with("level=info target=randcast_mock_demo::")));
+
+        let mut events = lines
+            .iter()
+            .filter_map(|line| event_of(line))
+            .collect::<Vec<_>>();
+
+        // consecutive repeats count once, e.g. the first 3 registrations or the phases of the DKG
+        events.dedup();
+
+        assert_eq!(
+            events,
+            vec![
+                "node_registered",
+                "dkg_task_created",
+                "node_registered",
+                "dkg_task_created",
+                "node_registered",
+                "dkg_task_created",
+                "dkg_phase_completed",
+                "dkg_committed",
+                "group_activated",
+                "dkg_committed",
+                "randomness_requested",
+            ]
+        );
+
+        let activation = lines
+            .iter()
+            .find(|line| event_of(line) == Some("group_activated"))
+            .unwrap();
+
+        assert!(activation.contains(" group_index=1 "));
+    }
+}
@@ -1,239 +1,207 @@
-use dkg_core::primitives::{
-    group::{Group, Node},
-    joint_feldman,
-    types::DKGOutput,
-};
-use dkg_core::{DKGPhase, Phase2Result};
-use rand::rngs::ThreadRng;
+use dkg_core::primitives::types::DKGOutput;
+use gumdrop::Options;
+use log::{debug, info, warn};
+use rand::{rngs::StdRng, SeedableRng};
+use randcast_mock_demo::actions::{run_dkg, setup_with_private_keys};
 use randcast_mock_demo::contract::*;
-use randcast_mock_demo::test_helpers::InMemoryBoard;
-use std::collections::HashMap;
 use threshold_bls::{
     curve::bls12381::{self, PairingCurve as BLS12_381},
+    curve::bn254::{self, PairingCurve as BN254},
     group::Curve,
-    poly::Idx,
-    sig::{G1Scheme, Scheme, SignatureScheme, ThresholdScheme},
+    sig::{G1Scheme, G2Scheme, Scheme, SignatureScheme, ThresholdScheme},
 };
 
+#[derive(Debug, Options, Clone)]
+struct DemoOpts {
+    help: bool,
+
+    #[options(
+        help = "the signature scheme, g1 or g2 (BLS12-381 public keys on G1 or G2), or bn254",
+        default = "g1"
+    )]
+    scheme: SchemeKind,
+
+    #[options(help = "seeds the RNG of the nodes, so that a run can be reproduced")]
+    rng_seed: Option<u64>,
+
+    #[options(help = "also logs the details, e.g. every partial signature")]
+    verbose: bool,
+}
+
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
+    let opts = DemoOpts::parse_args_default_or_exit();
+
+    randcast_mock_demo::logging::init(opts.verbose)?;
+
+    match opts.scheme {
+        SchemeKind::G1 => run::<bls12381::Curve, G1Scheme<BLS12_381>>(opts).await,
+        SchemeKind::G2 => run::<bls12381::G2Curve, G2Scheme<BLS12_381>>(opts).await,
+        SchemeKind::Bn254 => run::<bn254::G2Curve, G2Scheme<BN254>>(opts).await,
+    }
+}
+
+async fn run<C, S>(opts: DemoOpts) -> anyhow::Result<()>
+where
+    C: Curve,
+    // We need to bind the Curve's Point and Scalars to the Scheme
+    S: Scheme<Public = <C as Curve>::Point, Private = <C as Curve>::Scalar>
+        + ThresholdScheme
+        + SignatureScheme,
+{
     let initial_entropy = 0x8762_4875_6548_6346;
 
-    println!(
-        "controller is deploying... initial entropy: {}",
+    info!(
+        "event=controller_deploying initial_entropy={}",
         initial_entropy
     );
 
-    let mut controller = Controller::new(initial_entropy);
+    let mut controller = Controller::new(initial_entropy, RewardConfig::default());
+
+    info!("event=scheme_selected scheme={:?}", opts.scheme);
+
+    controller.scheme = opts.scheme;
 
     let (t, n) = (3, 5);
 
-    println!("nodes setup... t: {} n: {}", t, n);
+    info!("event=nodes_setup threshold={} size={}", t, n);
 
-    let rng = &mut rand::thread_rng();
+    let rng = &mut match opts.rng_seed {
+        Some(rng_seed) => StdRng::seed_from_u64(rng_seed),
+        None => StdRng::from_entropy(),
+    };
 
-    let (mut board, phase0s) = setup::<bls12381::Curve, G1Scheme<BLS12_381>, _>(n, t, rng);
+    let (mut board, phase0s, private_keys) = setup_with_private_keys::<C, S, _>(n, t, rng)?;
 
-    println!("nodes are registering to controller...");
+    for (i, (phase0, private_key)) in phase0s.iter().zip(private_keys.iter()).enumerate() {
+        let id_public_key = bincode::serialize(&phase0.info.public_key).unwrap();
+
+        let proof = proof_of_possession::<S>(private_key, &id_public_key).unwrap();
 
-    phase0s.iter().enumerate().for_each(|(i, phase0)| {
         controller.node_register(
             String::from("0x") + &i.to_string(),
-            bincode::serialize(&phase0.info.public_key).unwrap(),
-            String::from(""),
+            id_public_key,
+            format!("127.0.0.1:{}", 8000 + i),
             String::from("0x") + &i.to_string(),
-        );
-    });
-
-    println!("DKG task is emitting...");
+            proof,
+        )?;
+    }
 
-    let dkg_task = controller.emit_dkg_task();
+    let dkg_task = controller.emit_dkg_task()[0];
 
     let group_index = dkg_task.group_index;
 
     let group_epoch = dkg_task.epoch;
 
     // executes the DKG state machine and ensures that the keys are generated correctly
-    let outputs = run_dkg::<bls12381::Curve, G1Scheme<BLS12_381>>(&mut board, phase0s, rng).await;
+    let mut outputs = match run_dkg::<C, S, _, _>(&mut board, phase0s, rng).await {
+        Ok(outputs) => outputs,
+        Err(err) => {
+            warn!("event=dkg_failed error=\"{}\"", err);
+            return Err(err.into());
+        }
+    };
 
     // get the public key (we have already checked that all outputs' pubkeys are the same)
     let public_poly = outputs[0].public.clone();
 
     let pubkey = public_poly.public_key();
 
-    println!("DKG result is committing...");
-
     (0..n).for_each(|i| {
         let res = controller.commit_dkg(
             String::from("0x") + &i.to_string(),
             group_index,
             group_epoch,
-            bincode::serialize(&pubkey).unwrap(),
+            bincode::serialize(&public_poly).unwrap(),
             bincode::serialize(&public_poly.eval(i as u32).value).unwrap(),
             vec![],
         );
-        println!("{}-res: {}", i, res);
+        debug!(
+            "event=commit_result id_address=0x{} result=\"{}\"",
+            i,
+            format_res(&res)
+        );
     });
 
-    let group = controller.get_group(1);
-
-    println!("group state: {}", group.state);
+    // let msg = rand::random::<[u8; 32]>().to_vec();
 
-    println!("group committers: {:?}", group.committers);
+    let seed = String::from("ujehwsndfgljkhrlkg");
 
-    // let msg = rand::random::<[u8; 32]>().to_vec();
+    let request_res = controller.request(String::from("0xuser"), seed, 1000, DEFAULT_PRIORITY);
 
-    let msg = String::from("ujehwsndfgljkhrlkg");
+    debug!(
+        "event=request_result result=\"{}\"",
+        format_res(&request_res)
+    );
 
-    println!("An user is requesting a randomness... msg seed: {}", msg);
+    let signature_task = controller.emit_signature_task().unwrap();
 
-    let request_res = controller.request(msg.clone());
+    let signature_index = signature_task.index;
 
-    println!("request_res: {}", request_res);
+    // the message is chained with the last randomness output
+    let msg = signature_task.message.clone();
 
-    println!("A signature task is emitting...");
+    // node 0 persists its output and restarts, reloading its share from the saved bytes
+    let saved_output = outputs[0].to_bytes()?;
 
-    let signature_task = controller.emit_signature_task();
+    info!(
+        "event=node_restarting id_address=0x0 saved_bytes={}",
+        saved_output.len()
+    );
 
-    let signature_index = signature_task.index;
+    outputs[0] = DKGOutput::from_bytes(&saved_output)?;
 
     // generates a partial sig with each share from the dkg
     let partial_sigs = outputs
         .iter()
-        .map(|output| G1Scheme::<BLS12_381>::partial_sign(&output.share, &msg.as_bytes()).unwrap())
+        .map(|output| S::partial_sign(&output.share, &msg).unwrap())
         .collect::<Vec<_>>();
 
-    // committer verify the partial threshold signatures first
-    partial_sigs.iter().for_each(|partial_sig| {
-        G1Scheme::<BLS12_381>::partial_verify(&public_poly, &msg.as_bytes(), &partial_sig).unwrap();
-    });
-
-    // then aggregates them
-    let sig = G1Scheme::<BLS12_381>::aggregate(t, &partial_sigs).unwrap();
-
-    // committer verify the threshold signature first
-    G1Scheme::<BLS12_381>::verify(&pubkey, &msg.as_bytes(), &sig).unwrap();
-
-    println!("Committers are committing result of the signature task...");
-
-    (0..n).for_each(|i| {
-        // the participant list to be rewarded by this signature task
-        let mut partial_signatures: HashMap<String, Vec<u8>> = HashMap::new();
-
-        partial_sigs
-            .iter()
-            .enumerate()
-            .for_each(|(i, partial_sig)| {
-                partial_signatures.insert(String::from("0x") + &i.to_string(), partial_sig.clone());
-            });
+    // then aggregates them, the committer verifies the threshold signature first
+    match S::aggregate(t, &partial_sigs) {
+        Ok(sig) => S::verify(&pubkey, &msg, &sig).unwrap(),
+        // the controller keeps the task pending until enough partial signatures are in
+        Err(err) => warn!("event=aggregation_deferred error=\"{}\"", err),
+    }
 
-        let res = controller.fulfill(
-            String::from("0x") + &i.to_string(),
+    for (i, partial_sig) in partial_sigs.into_iter().enumerate() {
+        let res = controller.submit_partial(
             signature_index,
-            sig.clone(),
-            partial_signatures,
+            String::from("0x") + &i.to_string(),
+            partial_sig,
         );
 
-        println!("{}-res: {}", i, res);
-    });
+        debug!(
+            "event=partial_result id_address=0x{} result=\"{}\"",
+            i,
+            format_res(&res)
+        );
 
-    let randomness_output = controller.get_last_output();
+        // the task is fulfilled as soon as the threshold is reached
+        if let Ok(true) = res {
+            break;
+        }
+    }
 
-    println!("randomness output: {}", randomness_output);
+    info!(
+        "event=randomness_output signature_index={} confirmations={} fee_pool={} output={}",
+        signature_index,
+        controller
+            .get_signature_confirmations(signature_index)
+            .unwrap()
+            .join(","),
+        controller.get_fee_pool(),
+        controller.get_last_output()
+    );
 
-    println!("finish.");
+    info!("event=finished");
 
     Ok(())
 }
 
-async fn run_dkg<C, S>(
-    board: &mut InMemoryBoard<C>,
-    phase0s: Vec<joint_feldman::DKG<C>>,
-    rng: &mut ThreadRng,
-) -> Vec<DKGOutput<C>>
-where
-    C: Curve,
-    // We need to bind the Curve's Point and Scalars to the Scheme
-    S: Scheme<Public = <C as Curve>::Point, Private = <C as Curve>::Scalar>,
-{
-    // Phase 1: Publishes shares
-    let mut phase1s = Vec::new();
-    for phase0 in phase0s {
-        phase1s.push(phase0.run(board, rng).await.unwrap());
+fn format_res<T>(res: &Result<T, ControllerError>) -> String {
+    match res {
+        Ok(_) => String::from("ok"),
+        Err(err) => err.to_string(),
     }
-
-    // Get the shares from the board
-    let shares = board.shares.clone();
-
-    // Phase2
-    let mut phase2s = Vec::new();
-    for phase1 in phase1s {
-        phase2s.push(phase1.run(board, &shares).await.unwrap());
-    }
-
-    // Get the responses from the board
-    let responses = board.responses.clone();
-
-    let mut results = Vec::new();
-    for phase2 in phase2s {
-        results.push(phase2.run(board, &responses).await.unwrap());
-    }
-
-    // The distributed public key must be the same
-    let outputs = results
-        .into_iter()
-        .map(|res| match res {
-            Phase2Result::Output(out) => out,
-            Phase2Result::GoToPhase3(_) => unreachable!("should not get here"),
-        })
-        .collect::<Vec<_>>();
-    assert!(is_all_same(outputs.iter().map(|output| &output.public)));
-
-    outputs
-}
-
-fn setup<C, S, R: rand::RngCore>(
-    n: usize,
-    t: usize,
-    rng: &mut R,
-) -> (InMemoryBoard<C>, Vec<joint_feldman::DKG<C>>)
-where
-    C: Curve,
-    // We need to bind the Curve's Point and Scalars to the Scheme
-    S: Scheme<Public = <C as Curve>::Point, Private = <C as Curve>::Scalar>,
-{
-    // generate a keypair per participant
-    let keypairs = (0..n).map(|_| S::keypair(rng)).collect::<Vec<_>>();
-    // keypairs
-    //     .iter()
-    //     .for_each(|(private, public)| println!("{} {}", private, public));
-
-    let nodes = keypairs
-        .iter()
-        .enumerate()
-        .map(|(i, (_, public))| {
-            // println!("{}", i);
-            Node::<C>::new(i as Idx, public.clone())
-        })
-        .collect::<Vec<_>>();
-
-    // This is setup phase during which publickeys and indexes must be exchanged
-    // across participants
-    let group = Group::new(nodes, t).unwrap();
-
-    // Create the Phase 0 for each participant
-    let phase0s = keypairs
-        .iter()
-        .map(|(private, _)| joint_feldman::DKG::new(private.clone(), group.clone()).unwrap())
-        .collect::<Vec<_>>();
-
-    // Create the board
-    let board = InMemoryBoard::<C>::new();
-
-    (board, phase0s)
-}
-
-fn is_all_same<T: PartialEq>(mut arr: impl Iterator<Item = T>) -> bool {
-    let first = arr.next().unwrap();
-    arr.all(|item| item == first)
 }
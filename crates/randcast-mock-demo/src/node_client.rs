@@ -0,0 +1,637 @@
+use crate::contract::{
+    proof_of_possession, Controller, ControllerError, DKGTask, MockHelper, SignatureTask,
+    Transactions, Views,
+};
+use crate::test_helpers::ReadableBoard;
+use async_trait::async_trait;
+use dkg_core::{
+    board::BoardPublisher,
+    primitives::{
+        group::{Group, Node},
+        joint_feldman,
+        types::{BundledJustification, BundledResponses, BundledShares, DKGOutput},
+        DKGError,
+    },
+    DKGPhase, NodeError, Phase2Result,
+};
+use rand::{rngs::StdRng, RngCore, SeedableRng};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use thiserror::Error;
+use threshold_bls::{
+    group::Curve,
+    poly::Idx,
+    sig::{Scheme, SignatureScheme, ThresholdScheme},
+};
+use tokio::sync::{broadcast, mpsc, Mutex};
+use tokio::time::{delay_for, timeout};
+
+/// The interval at which a node polls the controller while waiting for its DKG task
+const POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// The in-process endpoint through which the nodes reach the controller
+pub type ControllerEndpoint = Arc<Mutex<Controller>>;
+
+/// A bundle published on the board during one of the DKG phases
+#[derive(Clone)]
+pub enum BoardMessage<C: Curve> {
+    Shares(BundledShares<C>),
+    Responses(BundledResponses),
+    Justifications(BundledJustification<C>),
+}
+
+/// A board shared by in-process nodes over a broadcast channel. Every handle receives
+/// the bundles published by all the handles subscribed to the board, its own included.
+pub struct ChannelBoard<C: Curve> {
+    sender: broadcast::Sender<BoardMessage<C>>,
+    receiver: broadcast::Receiver<BoardMessage<C>>,
+    shares: Vec<BundledShares<C>>,
+    responses: Vec<BundledResponses>,
+    justifs: Vec<BundledJustification<C>>,
+}
+
+impl<C: Curve> ChannelBoard<C> {
+    /// Creates a board which buffers up to `capacity` unread bundles per handle
+    pub fn new(capacity: usize) -> Self {
+        let (sender, receiver) = broadcast::channel(capacity);
+
+        Self {
+            sender,
+            receiver,
+            shares: vec![],
+            responses: vec![],
+            justifs: vec![],
+        }
+    }
+
+    /// Returns a new handle to the board, which receives every bundle published from now on
+    pub fn subscribe(&self) -> Self {
+        Self {
+            sender: self.sender.clone(),
+            receiver: self.sender.subscribe(),
+            shares: vec![],
+            responses: vec![],
+            justifs: vec![],
+        }
+    }
+
+    /// Receives the published bundles until `done` holds or `wait` elapses. Returns
+    /// whether `done` was satisfied.
+    pub async fn receive_until(&mut self, done: impl Fn(&Self) -> bool, wait: Duration) -> bool {
+        let deadline = Instant::now() + wait;
+
+        while !done(self) {
+            let remaining = match deadline.checked_duration_since(Instant::now()) {
+                Some(remaining) => remaining,
+                None => return false,
+            };
+
+            match timeout(remaining, self.receiver.recv()).await {
+                Ok(Ok(message)) => self.store(message),
+                // the skipped bundles are lost, the DKG will complain about them
+                Ok(Err(broadcast::RecvError::Lagged(_))) => continue,
+                Ok(Err(broadcast::RecvError::Closed)) | Err(_) => return done(self),
+            }
+        }
+
+        true
+    }
+
+    fn store(&mut self, message: BoardMessage<C>) {
+        match message {
+            BoardMessage::Shares(bundle) => self.shares.push(bundle),
+            BoardMessage::Responses(bundle) => self.responses.push(bundle),
+            BoardMessage::Justifications(bundle) => self.justifs.push(bundle),
+        }
+    }
+
+    fn publish(&self, message: BoardMessage<C>) -> Result<(), ()> {
+        self.sender.send(message).map(|_| ()).map_err(|_| ())
+    }
+}
+
+#[async_trait(?Send)]
+impl<C: Curve> BoardPublisher<C> for ChannelBoard<C> {
+    type Error = ();
+
+    async fn publish_shares(&mut self, bundle: BundledShares<C>) -> Result<(), Self::Error>
+    where
+        C: 'async_trait,
+    {
+        self.publish(BoardMessage::Shares(bundle))
+    }
+
+    async fn publish_responses(&mut self, bundle: BundledResponses) -> Result<(), Self::Error>
+    where
+        C: 'async_trait,
+    {
+        self.publish(BoardMessage::Responses(bundle))
+    }
+
+    async fn publish_justifications(
+        &mut self,
+        bundle: BundledJustification<C>,
+    ) -> Result<(), Self::Error>
+    where
+        C: 'async_trait,
+    {
+        self.publish(BoardMessage::Justifications(bundle))
+    }
+}
+
+impl<C: Curve> ReadableBoard<C> for ChannelBoard<C> {
+    fn shares(&self) -> Vec<BundledShares<C>> {
+        self.shares.clone()
+    }
+
+    fn responses(&self) -> Vec<BundledResponses> {
+        self.responses.clone()
+    }
+
+    fn justifications(&self) -> Vec<BundledJustification<C>> {
+        self.justifs.clone()
+    }
+}
+
+/// A partial signature produced by a node for a signature task
+#[derive(Debug, Clone)]
+pub struct PartialSignature {
+    pub id_address: String,
+    pub signature_index: usize,
+    pub partial_signature: Vec<u8>,
+}
+
+/// Memoizes the partial signatures of a node, keyed by the group epoch and the SHA-256
+/// digest of the message, so that a message signed again e.g. after a restart doesn't
+/// cost another BLS operation. The entries of a previous epoch are dropped, since they
+/// were signed with a share which has been replaced.
+#[derive(Debug, Default)]
+pub struct PartialSigCache {
+    entries: HashMap<(usize, Vec<u8>), Vec<u8>>,
+    hits: usize,
+    misses: usize,
+}
+
+impl PartialSigCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the cached partial signature of the message, or calls `sign` and caches
+    /// its result
+    pub fn get_or_sign<E>(
+        &mut self,
+        group_epoch: usize,
+        message: &[u8],
+        sign: impl FnOnce() -> Result<Vec<u8>, E>,
+    ) -> Result<Vec<u8>, E> {
+        // a new epoch means a new share
+        self.entries.retain(|(epoch, _), _| *epoch == group_epoch);
+
+        let key = (group_epoch, Sha256::digest(message).to_vec());
+
+        if let Some(partial_signature) = self.entries.get(&key) {
+            self.hits += 1;
+
+            return Ok(partial_signature.clone());
+        }
+
+        let partial_signature = sign()?;
+
+        self.misses += 1;
+
+        self.entries.insert(key, partial_signature.clone());
+
+        Ok(partial_signature)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The number of partial signatures read from the cache
+    pub fn hits(&self) -> usize {
+        self.hits
+    }
+
+    /// The number of partial signatures which had to be computed
+    pub fn misses(&self) -> usize {
+        self.misses
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum NodeClientError {
+    #[error("no DKG task of {0} members was assigned to {1}")]
+    NoDKGTask(usize, String),
+    #[error("{0} has not finished its DKG yet")]
+    DKGNotFinished(String),
+    #[error("could not partially sign: {0}")]
+    SigningError(String),
+    #[error(transparent)]
+    ControllerError(#[from] ControllerError),
+    #[error(transparent)]
+    DKGError(#[from] DKGError),
+    #[error(transparent)]
+    NodeError(#[from] NodeError),
+    #[error(transparent)]
+    BincodeError(#[from] bincode::Error),
+}
+
+/// A single participant of the randomness network. It registers to the controller, runs
+/// its own DKG state machine over the shared board and then partially signs the
+/// signature tasks assigned to its group.
+pub struct NodeClient<C: Curve, S> {
+    id_address: String,
+    rpc_endpoint: SocketAddr,
+    private_key: C::Scalar,
+    public_key: C::Point,
+    controller: ControllerEndpoint,
+    board: ChannelBoard<C>,
+    phase_timeout: Duration,
+    // seeded from the RNG the node was created with, draws the randomness of its DKGs
+    rng: StdRng,
+    // the group index, the epoch and the output of the last DKG this node took part in
+    group: Option<(usize, usize, DKGOutput<C>)>,
+    partial_sig_cache: std::sync::Mutex<PartialSigCache>,
+    scheme: PhantomData<S>,
+}
+
+impl<C, S> NodeClient<C, S>
+where
+    C: Curve,
+    // We need to bind the Curve's Point and Scalars to the Scheme
+    S: Scheme<Public = <C as Curve>::Point, Private = <C as Curve>::Scalar>
+        + ThresholdScheme
+        + SignatureScheme,
+{
+    /// Creates a node with a fresh keypair drawn from `rng`. Every phase of its DKG waits
+    /// for the bundles of the other members for at most `phase_timeout`.
+    pub fn new<R: RngCore>(
+        id_address: String,
+        rpc_endpoint: SocketAddr,
+        controller: ControllerEndpoint,
+        board: ChannelBoard<C>,
+        phase_timeout: Duration,
+        rng: &mut R,
+    ) -> Self {
+        let (private_key, public_key) = S::keypair(rng);
+
+        let mut seed = <StdRng as SeedableRng>::Seed::default();
+
+        rng.fill_bytes(&mut seed);
+
+        Self {
+            id_address,
+            rpc_endpoint,
+            private_key,
+            public_key,
+            controller,
+            board,
+            phase_timeout,
+            rng: StdRng::from_seed(seed),
+            group: None,
+            partial_sig_cache: std::sync::Mutex::new(PartialSigCache::new()),
+            scheme: PhantomData,
+        }
+    }
+
+    pub fn id_address(&self) -> &str {
+        &self.id_address
+    }
+
+    pub fn rpc_endpoint(&self) -> SocketAddr {
+        self.rpc_endpoint
+    }
+
+    pub fn public_key(&self) -> &C::Point {
+        &self.public_key
+    }
+
+    /// Returns the output of the last DKG this node took part in
+    pub fn output(&self) -> Option<&DKGOutput<C>> {
+        self.group.as_ref().map(|(_, _, output)| output)
+    }
+
+    /// Registers the node to the controller with its DKG public key, along with the proof
+    /// that it holds the private key. Returns the index the node was assigned in its group.
+    pub async fn register(&self) -> Result<usize, NodeClientError> {
+        let id_public_key = bincode::serialize(&self.public_key)?;
+
+        let proof = proof_of_possession::<S>(&self.private_key, &id_public_key)
+            .map_err(|err| NodeClientError::SigningError(err.to_string()))?;
+
+        let index = self.controller.lock().await.node_register(
+            self.id_address.clone(),
+            id_public_key,
+            self.rpc_endpoint.to_string(),
+            self.id_address.clone(),
+            proof,
+        )?;
+
+        Ok(index)
+    }
+
+    /// Waits for a DKG task which includes this node and has `group_size` members, since
+    /// the task of a group gets superseded as long as nodes keep joining it.
+    pub async fn wait_for_dkg_task(
+        &self,
+        group_size: usize,
+        wait: Duration,
+    ) -> Result<DKGTask, NodeClientError> {
+        let deadline = Instant::now() + wait;
+
+        loop {
+            let task = self
+                .controller
+                .lock()
+                .await
+                .emit_dkg_task()
+                .into_iter()
+                .find(|task| task.index_of(&self.id_address).is_some())
+                .filter(|task| task.size == group_size)
+                .cloned();
+
+            if let Some(task) = task {
+                return Ok(task);
+            }
+
+            if Instant::now() >= deadline {
+                return Err(NodeClientError::NoDKGTask(
+                    group_size,
+                    self.id_address.clone(),
+                ));
+            }
+
+            delay_for(POLL_INTERVAL).await;
+        }
+    }
+
+    /// Runs the DKG of `task` over the board and commits the result to the controller.
+    /// Returns the index of the share of the node along with the committed public key.
+    pub async fn run_dkg(&mut self, task: &DKGTask) -> Result<(Idx, C::Point), NodeClientError> {
+        let group = self.group_of(task).await?;
+
+        // the member of index i holds the share of index i - 1
+        let index = task
+            .index_of(&self.id_address)
+            .ok_or_else(|| NodeClientError::NoDKGTask(task.size, self.id_address.clone()))?
+            as Idx
+            - 1;
+
+        let output = self.run_dkg_phases(group).await?;
+
+        let public_key = output.public.public_key().clone();
+
+        self.controller.lock().await.commit_dkg(
+            self.id_address.clone(),
+            task.group_index,
+            task.epoch,
+            bincode::serialize(&output.public)?,
+            bincode::serialize(&output.public.eval(index).value)?,
+            vec![],
+        )?;
+
+        self.group = Some((task.group_index, task.epoch, output));
+
+        Ok((index, public_key))
+    }
+
+    /// Partially signs every task assigned to the group of this node and sends the
+    /// partial signatures to the committers, until the task stream is closed.
+    pub async fn sign_tasks(
+        &self,
+        mut tasks: broadcast::Receiver<SignatureTask>,
+        partials: mpsc::UnboundedSender<PartialSignature>,
+    ) -> Result<(), NodeClientError> {
+        let (group_index, group_epoch, output) = self
+            .group
+            .as_ref()
+            .ok_or_else(|| NodeClientError::DKGNotFinished(self.id_address.clone()))?;
+
+        loop {
+            let task = match tasks.recv().await {
+                Ok(task) => task,
+                Err(broadcast::RecvError::Lagged(_)) => continue,
+                Err(broadcast::RecvError::Closed) => return Ok(()),
+            };
+
+            if task.group_index != *group_index {
+                continue;
+            }
+
+            let partial_signature = self
+                .partial_sig_cache
+                .lock()
+                .unwrap()
+                .get_or_sign(*group_epoch, &task.message, || {
+                    S::partial_sign(&output.share, &task.message)
+                })
+                .map_err(|err| NodeClientError::SigningError(err.to_string()))?;
+
+            // the committers may have already gone away once they have enough partials
+            let _ = partials.send(PartialSignature {
+                id_address: self.id_address.clone(),
+                signature_index: task.index,
+                partial_signature,
+            });
+        }
+    }
+
+    /// Builds the DKG group of the task from the public keys registered to the controller
+    async fn group_of(&self, task: &DKGTask) -> Result<Group<C>, NodeClientError> {
+        let controller = self.controller.lock().await;
+
+        let mut nodes = Vec::new();
+        for (index, id_address) in task.members.iter() {
+            let node = controller.get_node(id_address.clone());
+            let public_key = bincode::deserialize(&node.id_public_key)?;
+            nodes.push(Node::<C>::new(*index as Idx - 1, public_key));
+        }
+
+        Ok(Group::new(nodes, task.threshold)?)
+    }
+
+    async fn run_dkg_phases(&mut self, group: Group<C>) -> Result<DKGOutput<C>, NodeClientError> {
+        let n = group.len();
+
+        let phase0 = joint_feldman::DKG::new_rand(self.private_key.clone(), group, &mut self.rng)?;
+
+        // Phase 1: Publishes shares and waits for the shares of every member
+        let phase1 = phase0.run(&mut self.board, &mut self.rng).await?;
+
+        self.board
+            .receive_until(|board| board.shares.len() == n, self.phase_timeout)
+            .await;
+        let shares = self.board.shares();
+
+        // Phase 2: Only the complaints get published, so wait for the whole phase
+        let phase2 = phase1.run(&mut self.board, &shares).await?;
+
+        self.board
+            .receive_until(|_| false, self.phase_timeout)
+            .await;
+        let responses = self.board.responses();
+
+        // Phase 3 is only needed if there were complaints
+        match phase2.run(&mut self.board, &responses).await? {
+            Phase2Result::Output(output) => Ok(output),
+            Phase2Result::GoToPhase3(phase3) => {
+                self.board
+                    .receive_until(|_| false, self.phase_timeout)
+                    .await;
+                let justifications = self.board.justifications();
+
+                Ok(phase3.run(&mut self.board, &justifications).await?)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::contract::{GroupState, DEFAULT_PRIORITY};
+    use futures::future::join_all;
+    use threshold_bls::{
+        curve::bls12381::{self, PairingCurve as BLS12_381},
+        sig::{G1Scheme, Share, SignatureScheme},
+    };
+
+    type C = bls12381::Curve;
+    type S = G1Scheme<BLS12_381>;
+
+    #[tokio::test]
+    async fn node_clients_run_dkg_and_sign() {
+        let n = 5;
+        let controller = Arc::new(Mutex::new(Controller::new(0x1234, Default::default())));
+        let board = ChannelBoard::<C>::new(64);
+        let rng = &mut rand::thread_rng();
+
+        let mut nodes = (0..n)
+            .map(|i| {
+                NodeClient::<C, S>::new(
+                    format!("0x{}", i),
+                    SocketAddr::from(([127, 0, 0, 1], 8000 + i as u16)),
+                    controller.clone(),
+                    board.subscribe(),
+                    Duration::from_millis(50),
+                    rng,
+                )
+            })
+            .collect::<Vec<_>>();
+
+        for node in nodes.iter() {
+            node.register().await.unwrap();
+        }
+
+        let results = join_all(nodes.iter_mut().map(|node| async move {
+            let task = node
+                .wait_for_dkg_task(n, Duration::from_secs(1))
+                .await
+                .unwrap();
+            node.run_dkg(&task).await.unwrap()
+        }))
+        .await;
+
+        // every node committed the same public key and the group got ready
+        let public_key = results[0].1;
+        assert!(results.iter().all(|(_, key)| *key == public_key));
+        let group_index = controller.lock().await.valid_group_indices()[0];
+        assert_eq!(
+            controller
+                .lock()
+                .await
+                .get_group(group_index)
+                .unwrap()
+                .state,
+            GroupState::Active
+        );
+
+        let (tasks_sender, _) = broadcast::channel(16);
+        let (partials_sender, mut partials_receiver) = mpsc::unbounded_channel();
+
+        let signing = join_all(
+            nodes
+                .iter()
+                .map(|node| node.sign_tasks(tasks_sender.subscribe(), partials_sender.clone())),
+        );
+
+        let requesting = async {
+            let mut controller = controller.lock().await;
+            controller
+                .request(
+                    String::from("user"),
+                    String::from("seed"),
+                    0,
+                    DEFAULT_PRIORITY,
+                )
+                .unwrap();
+            let task = controller.emit_signature_task().unwrap();
+            // every node is listening for the task
+            assert_eq!(tasks_sender.send(task.clone()).ok(), Some(n));
+            drop(tasks_sender);
+
+            let mut partials = Vec::new();
+            for _ in 0..n {
+                partials.push(partials_receiver.recv().await.unwrap());
+            }
+            (task, partials)
+        };
+
+        let (results, (task, partials)) = futures::join!(signing, requesting);
+        assert!(results.iter().all(Result::is_ok));
+
+        // the partial signatures aggregate into a valid signature of the task
+        let partials = partials
+            .into_iter()
+            .map(|partial| partial.partial_signature)
+            .collect::<Vec<_>>();
+        let sig = S::aggregate(3, &partials).unwrap();
+        S::verify(&public_key, &task.message, &sig).unwrap();
+    }
+
+    #[test]
+    fn partial_signatures_are_cached_per_epoch() {
+        let (private, _) = S::keypair(&mut rand::thread_rng());
+        let share = Share { index: 1, private };
+
+        let mut cache = PartialSigCache::new();
+        let mut signed = 0;
+        let mut sign = |message: &[u8]| {
+            signed += 1;
+            S::partial_sign(&share, message)
+        };
+
+        let first = cache
+            .get_or_sign(1, b"message", || sign(b"message"))
+            .unwrap();
+        let second = cache
+            .get_or_sign(1, b"message", || sign(b"message"))
+            .unwrap();
+
+        // the second signature is a cache hit
+        assert_eq!(first, second);
+        assert_eq!((cache.hits(), cache.misses()), (1, 1));
+
+        cache.get_or_sign(1, b"other", || sign(b"other")).unwrap();
+        assert_eq!(cache.len(), 2);
+
+        // a new epoch drops the signatures of the previous share
+        cache
+            .get_or_sign(2, b"message", || sign(b"message"))
+            .unwrap();
+        assert_eq!(cache.len(), 1);
+        assert_eq!((cache.hits(), cache.misses()), (1, 3));
+
+        assert_eq!(signed, 3);
+    }
+}
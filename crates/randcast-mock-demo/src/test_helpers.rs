@@ -1,7 +1,17 @@
 use async_trait::async_trait;
 use dkg_core::board::BoardPublisher;
-use dkg_core::primitives::types::{BundledJustification, BundledResponses, BundledShares};
-use threshold_bls::group::Curve;
+use dkg_core::primitives::{
+    types::{BundledJustification, BundledResponses, BundledShares},
+    Status,
+};
+use rand::seq::SliceRandom;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use threshold_bls::{
+    ecies,
+    group::Curve,
+    poly::{Idx, PrivatePoly},
+};
 
 /// An in-memory board used for testing
 #[derive(Default)]
@@ -20,6 +30,36 @@ impl<C: Curve> InMemoryBoard<C> {
             justifs: vec![],
         }
     }
+
+    /// Drops every published bundle, e.g. to run another DKG on the same board
+    pub fn reset(&mut self) {
+        self.shares.clear();
+        self.responses.clear();
+        self.justifs.clear();
+    }
+
+    /// Copies the published bundles, so that the board can be rolled back to them
+    pub fn snapshot(&self) -> BoardSnapshot<C> {
+        BoardSnapshot {
+            shares: self.shares.clone(),
+            responses: self.responses.clone(),
+            justifs: self.justifs.clone(),
+        }
+    }
+
+    /// Replaces the published bundles with those of a snapshot
+    pub fn restore(&mut self, snapshot: BoardSnapshot<C>) {
+        self.shares = snapshot.shares;
+        self.responses = snapshot.responses;
+        self.justifs = snapshot.justifs;
+    }
+}
+
+/// The bundles an `InMemoryBoard` held when its snapshot was taken
+pub struct BoardSnapshot<C: Curve> {
+    pub shares: Vec<BundledShares<C>>,
+    pub responses: Vec<BundledResponses>,
+    pub justifs: Vec<BundledJustification<C>>,
 }
 
 #[async_trait(?Send)]
@@ -53,3 +93,343 @@ impl<C: Curve> BoardPublisher<C> for InMemoryBoard<C> {
         Ok(())
     }
 }
+
+/// A board whose published bundles can be read back by the participants
+pub trait ReadableBoard<C: Curve>: BoardPublisher<C> {
+    fn shares(&self) -> Vec<BundledShares<C>>;
+
+    fn responses(&self) -> Vec<BundledResponses>;
+
+    fn justifications(&self) -> Vec<BundledJustification<C>>;
+
+    /// Returns the shares as delivered to the participant of index `share_idx`. They are
+    /// the published ones, unless a dealer shows another commitment to some participants.
+    fn shares_seen_by(&self, _share_idx: Idx) -> Vec<BundledShares<C>> {
+        self.shares()
+    }
+
+    /// Returns the responses complaining about a share, i.e. the responses without the
+    /// acknowledgments, which are what Phase 3 needs to be justified
+    fn complaints(&self) -> Vec<BundledResponses> {
+        self.responses()
+            .into_iter()
+            .filter_map(|mut bundle| {
+                bundle
+                    .responses
+                    .retain(|response| response.status == Status::Complaint);
+
+                if bundle.responses.is_empty() {
+                    None
+                } else {
+                    Some(bundle)
+                }
+            })
+            .collect()
+    }
+    /// Decrypts the share a dealer published for the participant of index `share_idx`.
+    /// The shares are ECIES ciphertexts under the public key of their recipient, so this
+    /// returns None unless `private_key` is the key of that participant.
+    fn decrypt_share(
+        &self,
+        dealer_idx: Idx,
+        share_idx: Idx,
+        private_key: &C::Scalar,
+    ) -> Option<C::Scalar> {
+        let bundle = self
+            .shares()
+            .into_iter()
+            .find(|bundle| bundle.dealer_idx == dealer_idx)?;
+
+        let share = bundle
+            .shares
+            .iter()
+            .find(|share| share.share_idx == share_idx)?;
+
+        let plaintext = ecies::decrypt::<C>(private_key, &share.secret).ok()?;
+
+        bincode::deserialize(&plaintext).ok()
+    }
+}
+
+impl<C: Curve> ReadableBoard<C> for InMemoryBoard<C> {
+    fn shares(&self) -> Vec<BundledShares<C>> {
+        self.shares.clone()
+    }
+
+    fn responses(&self) -> Vec<BundledResponses> {
+        self.responses.clone()
+    }
+
+    fn justifications(&self) -> Vec<BundledJustification<C>> {
+        self.justifs.clone()
+    }
+}
+
+/// The attack a `ByzantineBoard` performs on the shares of the malicious nodes
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ByzantinePolicy {
+    // the shares are never published
+    DropShare,
+    // every share is encrypted to another participant
+    CorruptShare,
+    // the shares are published along with a random public polynomial
+    WrongCommitment,
+    // the shares are published along with the real public polynomial, but the
+    // participants of odd index are shown a random one
+    Equivocate,
+    // only the share of the given participant is corrupted and it never gets justified
+    DefraudShare(Idx),
+}
+
+/// An in-memory board which tampers with the shares published by the malicious nodes
+pub struct ByzantineBoard<C: Curve> {
+    pub board: InMemoryBoard<C>,
+    pub malicious: Vec<Idx>,
+    pub policy: ByzantinePolicy,
+    // the bundles shown instead of the published ones by the equivocating dealers
+    forged: Vec<BundledShares<C>>,
+}
+
+impl<C: Curve> ByzantineBoard<C> {
+    pub fn new(malicious: Vec<Idx>, policy: ByzantinePolicy) -> Self {
+        Self {
+            board: InMemoryBoard::new(),
+            malicious,
+            policy,
+            forged: vec![],
+        }
+    }
+}
+
+#[async_trait(?Send)]
+impl<C: Curve> BoardPublisher<C> for ByzantineBoard<C> {
+    type Error = ();
+
+    async fn publish_shares(&mut self, mut bundle: BundledShares<C>) -> Result<(), Self::Error>
+    where
+        C: 'async_trait,
+    {
+        if self.malicious.contains(&bundle.dealer_idx) {
+            match self.policy {
+                ByzantinePolicy::DropShare => return Ok(()),
+                ByzantinePolicy::CorruptShare => {
+                    let mut secrets = bundle
+                        .shares
+                        .iter()
+                        .map(|share| share.secret.clone())
+                        .collect::<Vec<_>>();
+
+                    secrets.rotate_left(1);
+
+                    bundle
+                        .shares
+                        .iter_mut()
+                        .zip(secrets)
+                        .for_each(|(share, secret)| share.secret = secret);
+                }
+                ByzantinePolicy::WrongCommitment => {
+                    bundle.public = PrivatePoly::<C>::new(bundle.public.degree()).commit();
+                }
+                ByzantinePolicy::Equivocate => {
+                    let mut forged = bundle.clone();
+
+                    forged.public = PrivatePoly::<C>::new(bundle.public.degree()).commit();
+
+                    self.forged.push(forged);
+                }
+                ByzantinePolicy::DefraudShare(victim) => {
+                    // the victim gets a copy of a share encrypted to somebody else
+                    let other = bundle
+                        .shares
+                        .iter()
+                        .find(|share| share.share_idx != victim)
+                        .map(|share| share.secret.clone());
+
+                    if let (Some(share), Some(other)) = (
+                        bundle
+                            .shares
+                            .iter_mut()
+                            .find(|share| share.share_idx == victim),
+                        other,
+                    ) {
+                        share.secret = other;
+                    }
+                }
+            }
+        }
+
+        self.board.publish_shares(bundle).await
+    }
+
+    async fn publish_responses(&mut self, bundle: BundledResponses) -> Result<(), Self::Error>
+    where
+        C: 'async_trait,
+    {
+        self.board.publish_responses(bundle).await
+    }
+
+    async fn publish_justifications(
+        &mut self,
+        bundle: BundledJustification<C>,
+    ) -> Result<(), Self::Error>
+    where
+        C: 'async_trait,
+    {
+        if let ByzantinePolicy::DefraudShare(_) = self.policy {
+            if self.malicious.contains(&bundle.dealer_idx) {
+                return Ok(());
+            }
+        }
+
+        self.board.publish_justifications(bundle).await
+    }
+}
+
+impl<C: Curve> ReadableBoard<C> for ByzantineBoard<C> {
+    fn shares(&self) -> Vec<BundledShares<C>> {
+        self.board.shares()
+    }
+
+    fn shares_seen_by(&self, share_idx: Idx) -> Vec<BundledShares<C>> {
+        if share_idx % 2 == 0 {
+            return self.shares();
+        }
+
+        self.shares()
+            .into_iter()
+            .map(|bundle| {
+                self.forged
+                    .iter()
+                    .find(|forged| forged.dealer_idx == bundle.dealer_idx)
+                    .cloned()
+                    .unwrap_or(bundle)
+            })
+            .collect()
+    }
+
+    fn responses(&self) -> Vec<BundledResponses> {
+        self.board.responses()
+    }
+
+    fn justifications(&self) -> Vec<BundledJustification<C>> {
+        self.board.justifications()
+    }
+}
+
+/// An in-memory board which delays the delivery of the shares and responses published by
+/// each node, and returns the delivered bundles in a random order
+pub struct AsyncDelayBoard<C: Curve> {
+    delays: HashMap<Idx, Duration>,
+    shares: Vec<(Instant, BundledShares<C>)>,
+    responses: Vec<(Instant, BundledResponses)>,
+    justifs: Vec<BundledJustification<C>>,
+}
+
+impl<C: Curve> AsyncDelayBoard<C> {
+    /// Nodes without a configured delay get their bundles delivered immediately
+    pub fn new(delays: HashMap<Idx, Duration>) -> Self {
+        Self {
+            delays,
+            shares: vec![],
+            responses: vec![],
+            justifs: vec![],
+        }
+    }
+
+    fn delivery_time(&self, idx: Idx) -> Instant {
+        Instant::now() + self.delays.get(&idx).copied().unwrap_or_default()
+    }
+
+    /// Waits until every published bundle is delivered or the timeout elapses. Returns
+    /// false if some bundles are still in flight.
+    pub async fn wait_for_delivery(&self, timeout: Duration) -> bool {
+        let deadline = Instant::now() + timeout;
+
+        let last_delivery = self
+            .shares
+            .iter()
+            .map(|(delivery_time, _)| *delivery_time)
+            .chain(
+                self.responses
+                    .iter()
+                    .map(|(delivery_time, _)| *delivery_time),
+            )
+            .max();
+
+        let now = Instant::now();
+
+        match last_delivery {
+            Some(last_delivery) if last_delivery > deadline => {
+                tokio::time::delay_for(deadline.saturating_duration_since(now)).await;
+                false
+            }
+            Some(last_delivery) if last_delivery > now => {
+                tokio::time::delay_for(last_delivery.saturating_duration_since(now)).await;
+                true
+            }
+            _ => true,
+        }
+    }
+}
+
+fn delivered<T: Clone>(bundles: &[(Instant, T)]) -> Vec<T> {
+    let now = Instant::now();
+
+    let mut delivered = bundles
+        .iter()
+        .filter(|(delivery_time, _)| *delivery_time <= now)
+        .map(|(_, bundle)| bundle.clone())
+        .collect::<Vec<_>>();
+
+    delivered.shuffle(&mut rand::thread_rng());
+
+    delivered
+}
+
+#[async_trait(?Send)]
+impl<C: Curve> BoardPublisher<C> for AsyncDelayBoard<C> {
+    type Error = ();
+
+    async fn publish_shares(&mut self, bundle: BundledShares<C>) -> Result<(), Self::Error>
+    where
+        C: 'async_trait,
+    {
+        let delivery_time = self.delivery_time(bundle.dealer_idx);
+        self.shares.push((delivery_time, bundle));
+        Ok(())
+    }
+
+    async fn publish_responses(&mut self, bundle: BundledResponses) -> Result<(), Self::Error>
+    where
+        C: 'async_trait,
+    {
+        let delivery_time = self.delivery_time(bundle.share_idx);
+        self.responses.push((delivery_time, bundle));
+        Ok(())
+    }
+
+    async fn publish_justifications(
+        &mut self,
+        bundle: BundledJustification<C>,
+    ) -> Result<(), Self::Error>
+    where
+        C: 'async_trait,
+    {
+        self.justifs.push(bundle);
+        Ok(())
+    }
+}
+
+impl<C: Curve> ReadableBoard<C> for AsyncDelayBoard<C> {
+    fn shares(&self) -> Vec<BundledShares<C>> {
+        delivered(&self.shares)
+    }
+
+    fn responses(&self) -> Vec<BundledResponses> {
+        delivered(&self.responses)
+    }
+
+    fn justifications(&self) -> Vec<BundledJustification<C>> {
+        self.justifs.clone()
+    }
+}
@@ -0,0 +1,216 @@
+use sha2::{Digest, Sha256};
+use std::convert::TryInto;
+
+/// Turns the 32 bytes of entropy derived from a signature into the form a consumer
+/// needs, e.g. an integer in a range
+pub trait RandomnessTransform {
+    type Output;
+
+    fn apply(&self, entropy: &[u8; 32]) -> Self::Output;
+}
+
+/// An endless stream of uniform integers expanded from the entropy, as the SHA-256
+/// digests of the entropy followed by a counter
+struct EntropyStream {
+    entropy: [u8; 32],
+    counter: u64,
+}
+
+impl EntropyStream {
+    fn new(entropy: &[u8; 32]) -> Self {
+        EntropyStream {
+            entropy: *entropy,
+            counter: 0,
+        }
+    }
+
+    fn next_u128(&mut self) -> u128 {
+        let mut hasher = Sha256::new();
+
+        hasher.input(&self.entropy);
+
+        hasher.input(&self.counter.to_be_bytes());
+
+        self.counter += 1;
+
+        u128::from_be_bytes(hasher.result()[..16].try_into().unwrap())
+    }
+
+    /// Draws a uniform integer in `0..span` by rejection sampling, so that the lower
+    /// values are not favored as with a plain modulo. A `span` of 0 stands for 2^128.
+    fn next_below(&mut self, span: u128) -> u128 {
+        if span == 0 {
+            return self.next_u128();
+        }
+
+        // 2^128 mod span, the draws below it would be biased
+        let threshold = span.wrapping_neg() % span;
+
+        loop {
+            let draw = self.next_u128();
+
+            if draw >= threshold {
+                return draw % span;
+            }
+        }
+    }
+}
+
+/// Reduces the entropy to a uniform integer in `min..=max`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RangeTransform {
+    min: u128,
+    max: u128,
+}
+
+impl RangeTransform {
+    /// Panics if `min` is greater than `max`
+    pub fn new(min: u128, max: u128) -> Self {
+        assert!(min <= max, "empty range {}..={}", min, max);
+
+        RangeTransform { min, max }
+    }
+}
+
+impl RandomnessTransform for RangeTransform {
+    type Output = u128;
+
+    fn apply(&self, entropy: &[u8; 32]) -> u128 {
+        // wraps to 0 for the whole range of u128
+        let span = (self.max - self.min).wrapping_add(1);
+
+        self.min + EntropyStream::new(entropy).next_below(span)
+    }
+}
+
+/// Derives a uniform permutation of `0..n` from the entropy, with a Fisher-Yates shuffle
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ShuffleTransform {
+    n: usize,
+}
+
+impl ShuffleTransform {
+    pub fn new(n: usize) -> Self {
+        ShuffleTransform { n }
+    }
+}
+
+impl RandomnessTransform for ShuffleTransform {
+    type Output = Vec<usize>;
+
+    fn apply(&self, entropy: &[u8; 32]) -> Vec<usize> {
+        let mut stream = EntropyStream::new(entropy);
+
+        let mut permutation = (0..self.n).collect::<Vec<_>>();
+
+        for i in (1..self.n).rev() {
+            let j = stream.next_below(i as u128 + 1) as usize;
+
+            permutation.swap(i, j);
+        }
+
+        permutation
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entropy_of(seed: u64) -> [u8; 32] {
+        let mut entropy = [0; 32];
+
+        entropy.copy_from_slice(&Sha256::digest(&seed.to_be_bytes()));
+
+        entropy
+    }
+
+    #[test]
+    fn range_stays_within_bounds() {
+        let ranges = [
+            (0, 0),
+            (5, 5),
+            (10, 20),
+            (0, u128::MAX),
+            (u128::MAX - 1, u128::MAX),
+        ];
+
+        for (min, max) in ranges.iter() {
+            let transform = RangeTransform::new(*min, *max);
+
+            for seed in 0..200 {
+                let value = transform.apply(&entropy_of(seed));
+
+                assert!(*min <= value && value <= *max);
+            }
+        }
+
+        // the same entropy always gives the same value
+        let transform = RangeTransform::new(1, 6);
+
+        assert_eq!(
+            transform.apply(&entropy_of(1)),
+            transform.apply(&entropy_of(1))
+        );
+    }
+
+    #[test]
+    fn range_is_unbiased() {
+        // 2^128 is not a multiple of 3, a plain modulo would favor the lower values
+        let transform = RangeTransform::new(1, 3);
+
+        let samples = 30_000;
+
+        let mut counts = [0usize; 3];
+
+        for seed in 0..samples {
+            counts[transform.apply(&entropy_of(seed)) as usize - 1] += 1;
+        }
+
+        for count in counts.iter() {
+            let expected = samples as usize / 3;
+
+            assert!(
+                (*count as f64 - expected as f64).abs() < expected as f64 * 0.05,
+                "{:?}",
+                counts
+            );
+        }
+    }
+
+    #[test]
+    fn shuffle_is_a_uniform_permutation() {
+        let transform = ShuffleTransform::new(3);
+
+        let samples = 30_000;
+
+        // the number of times each of the 6 permutations of 3 elements comes up
+        let mut counts = std::collections::HashMap::new();
+
+        for seed in 0..samples {
+            let permutation = transform.apply(&entropy_of(seed));
+
+            let mut sorted = permutation.clone();
+
+            sorted.sort();
+
+            assert_eq!(sorted, vec![0, 1, 2]);
+
+            *counts.entry(permutation).or_insert(0usize) += 1;
+        }
+
+        assert_eq!(counts.len(), 6);
+
+        for count in counts.values() {
+            let expected = samples as usize / 6;
+
+            assert!(
+                (*count as f64 - expected as f64).abs() < expected as f64 * 0.1,
+                "{:?}",
+                counts
+            );
+        }
+
+        assert!(ShuffleTransform::new(0).apply(&entropy_of(0)).is_empty());
+    }
+}
@@ -0,0 +1,246 @@
+use crate::contract::{PlainRequestHasher, RequestHasher};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::convert::TryInto;
+use thiserror::Error;
+use threshold_bls::curve::bls12381::PairingCurve as BLS12_381;
+use threshold_bls::sig::{BLSError, G1Scheme, Scheme, SignatureScheme};
+use tiny_keccak::{Hasher, Keccak};
+
+type S = G1Scheme<BLS12_381>;
+
+#[derive(Debug, Error)]
+pub enum VerifyError {
+    #[error("could not deserialize the group public key: {0}")]
+    InvalidPublicKey(#[from] bincode::Error),
+    #[error(transparent)]
+    InvalidSignature(#[from] BLSError),
+}
+
+#[derive(Debug, Error)]
+pub enum ProofError {
+    #[error("the message was not derived from the seed")]
+    MessageMismatch,
+    #[error(transparent)]
+    InvalidSignature(#[from] VerifyError),
+    #[error("the randomness is not the digest of the signature")]
+    RandomnessMismatch,
+}
+
+/// Everything a verifier needs to check a randomness output on its own
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RandomnessProof {
+    // the index of the signature task
+    pub index: usize,
+    pub requester: String,
+    // the seed of the request chained with the last output, see `chain_seed`
+    pub seed: Vec<u8>,
+    // the nonce of the requester which went into the message
+    pub nonce: u64,
+    // the digest of the signature
+    pub randomness: [u8; 32],
+    // the hash which derived the randomness, verifiers have to use the same
+    pub hash: RandomnessHash,
+    pub message: Vec<u8>,
+    pub signature: Vec<u8>,
+    // the public key of the group at the epoch it signed in
+    pub group_pubkey: Vec<u8>,
+    pub group_index: usize,
+    pub epoch: usize,
+}
+
+impl RandomnessProof {
+    /// Checks the proof of a controller which derives the messages with the default
+    /// `PlainRequestHasher`, see `verify_with`
+    pub fn verify(&self) -> Result<(), ProofError> {
+        self.verify_with(&PlainRequestHasher)
+    }
+
+    /// Checks that the message was derived from the seed by `hasher`, that the group signed
+    /// it and that the randomness is the digest of the signature
+    pub fn verify_with<H: RequestHasher + ?Sized>(&self, hasher: &H) -> Result<(), ProofError> {
+        if hasher.hash(&self.seed, &self.requester, self.nonce) != self.message {
+            return Err(ProofError::MessageMismatch);
+        }
+
+        verify_randomness(&self.group_pubkey, &self.message, &self.signature)?;
+
+        if self.hash.randomness(&self.signature) != self.randomness {
+            return Err(ProofError::RandomnessMismatch);
+        }
+
+        Ok(())
+    }
+}
+
+/// Checks that `signature` is the signature of the group over `message`. The public key is
+/// the bincode serialization committed to the controller, on G1 of BLS12-381.
+pub fn verify_randomness(
+    group_pubkey_bytes: &[u8],
+    message: &[u8],
+    signature: &[u8],
+) -> Result<(), VerifyError> {
+    let public_key: <S as Scheme>::Public = bincode::deserialize(group_pubkey_bytes)?;
+
+    S::verify(&public_key, message, signature)?;
+
+    Ok(())
+}
+
+/// The hash which derives the randomness of a signature task from its signature. A
+/// verifier has to use the same one as the controller, which is part of every
+/// `RandomnessProof`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RandomnessHash {
+    Sha256,
+    // what an EVM contract can compute cheaply
+    Keccak256,
+}
+
+impl Default for RandomnessHash {
+    fn default() -> Self {
+        RandomnessHash::Sha256
+    }
+}
+
+impl RandomnessHash {
+    /// Derives the randomness as the digest of the serialized aggregated signature,
+    /// exactly as submitted to `fulfill`
+    pub fn randomness(&self, sig: &[u8]) -> [u8; 32] {
+        let mut randomness = [0; 32];
+
+        match self {
+            RandomnessHash::Sha256 => randomness.copy_from_slice(&Sha256::digest(sig)),
+            RandomnessHash::Keccak256 => {
+                let mut keccak = Keccak::v256();
+
+                keccak.update(sig);
+
+                keccak.finalize(&mut randomness);
+            }
+        }
+
+        randomness
+    }
+
+    /// The first 8 bytes of the randomness, read as a big-endian integer
+    pub fn output(&self, sig: &[u8]) -> u64 {
+        let randomness = self.randomness(sig);
+
+        u64::from_be_bytes(randomness[..8].try_into().unwrap())
+    }
+}
+
+/// Derives the randomness of a signature task as the SHA-256 digest of the serialized
+/// aggregated signature, exactly as submitted to `fulfill`.
+pub fn randomness_from_signature(sig: &[u8]) -> [u8; 32] {
+    RandomnessHash::Sha256.randomness(sig)
+}
+
+/// The randomness output of the controller: the first 8 bytes of
+/// `randomness_from_signature`, read as a big-endian integer.
+pub fn randomness_output(sig: &[u8]) -> u64 {
+    RandomnessHash::Sha256.output(sig)
+}
+
+/// The address of a group, as an EVM contract derives it: the last 20 bytes of the
+/// keccak256 digest of the serialized group public key.
+pub fn group_address_from_pubkey(pubkey_bytes: &[u8]) -> [u8; 20] {
+    let mut digest = [0; 32];
+
+    let mut keccak = Keccak::v256();
+
+    keccak.update(pubkey_bytes);
+
+    keccak.finalize(&mut digest);
+
+    let mut address = [0; 20];
+
+    address.copy_from_slice(&digest[12..]);
+
+    address
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn signed(message: &[u8]) -> (Vec<u8>, Vec<u8>) {
+        let (private, public) = S::keypair(&mut rand::thread_rng());
+
+        let signature = S::sign(&private, message).unwrap();
+
+        (bincode::serialize(&public).unwrap(), signature)
+    }
+
+    #[test]
+    fn verifies_valid_signature() {
+        let message = b"randomness";
+
+        let (public_key, signature) = signed(message);
+
+        verify_randomness(&public_key, message, &signature).unwrap();
+
+        assert_eq!(
+            randomness_output(&signature).to_be_bytes()[..],
+            randomness_from_signature(&signature)[..8]
+        );
+    }
+
+    #[test]
+    fn randomness_depends_on_the_hash() {
+        let (_, signature) = signed(b"randomness");
+
+        let sha256 = RandomnessHash::Sha256.randomness(&signature);
+
+        let keccak256 = RandomnessHash::Keccak256.randomness(&signature);
+
+        assert_ne!(sha256, keccak256);
+
+        assert_eq!(sha256, randomness_from_signature(&signature));
+        assert_eq!(
+            RandomnessHash::Keccak256.output(&signature).to_be_bytes()[..],
+            keccak256[..8]
+        );
+
+        // the keccak256 digest of the empty input, as EVM contracts compute it
+        assert_eq!(
+            hex::encode(RandomnessHash::Keccak256.randomness(b"")),
+            "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"
+        );
+    }
+
+    #[test]
+    fn rejects_tampered_signature() {
+        let message = b"randomness";
+
+        let (public_key, signature) = signed(message);
+
+        // a valid signature of another message
+        let (_, other_signature) = signed(b"other");
+
+        assert!(matches!(
+            verify_randomness(&public_key, message, &other_signature),
+            Err(VerifyError::InvalidSignature(BLSError::InvalidSig))
+        ));
+
+        let mut tampered = signature.clone();
+
+        tampered[0] ^= 1;
+
+        assert!(matches!(
+            verify_randomness(&public_key, message, &tampered),
+            Err(VerifyError::InvalidSignature(_))
+        ));
+
+        assert!(matches!(
+            verify_randomness(&public_key[1..], message, &signature),
+            Err(VerifyError::InvalidPublicKey(_))
+        ));
+
+        assert!(matches!(
+            verify_randomness(&public_key, b"tampered", &signature),
+            Err(VerifyError::InvalidSignature(BLSError::InvalidSig))
+        ));
+    }
+}
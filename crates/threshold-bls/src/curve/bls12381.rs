@@ -1,6 +1,6 @@
 use crate::group::{self, Element, PairingCurve as PC, Point, Scalar as Sc};
 use ff::{Field, PrimeField};
-use groupy::CurveProjective;
+use groupy::{CurveAffine, CurveProjective, EncodedPoint};
 use paired::bls12_381::{Bls12, Fq12, Fr, FrRepr, G1 as PG1, G2 as PG2};
 use paired::Engine;
 use rand_core::RngCore;
@@ -178,9 +178,38 @@ impl PC for PairingCurve {
     }
 }
 
+/// Encodes a BLS12-381 point as its compressed affine coordinates, half the size of the
+/// uncompressed encoding. This is the form the serde implementation produces as well, so a
+/// compressed signature is a valid serialized signature. There is no such helper for BN254,
+/// whose serde implementation writes the compressed form directly.
+pub fn compress<P: CurveProjective>(point: &P) -> Vec<u8> {
+    point.into_affine().into_compressed().as_ref().to_vec()
+}
+
+/// Decodes a compressed BLS12-381 point, failing unless it lies on the curve and in its subgroup
+pub fn decompress<P: CurveProjective>(bytes: &[u8]) -> Result<P, BellmanError> {
+    let mut encoded = <P::Affine as CurveAffine>::Compressed::empty();
+
+    if bytes.len() != encoded.as_ref().len() {
+        return Err(BellmanError::InvalidLength(
+            bytes.len(),
+            encoded.as_ref().len(),
+        ));
+    }
+
+    encoded.as_mut().copy_from_slice(bytes);
+
+    Ok(encoded.into_affine()?.into_projective())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{
+        poly::{Eval, Idx, Poly},
+        schemes::bls12_381::G1Scheme,
+        sig::{Share, SignatureScheme, ThresholdScheme},
+    };
     use rand::prelude::*;
 
     use serde::{de::DeserializeOwned, Serialize};
@@ -218,4 +247,82 @@ mod tests {
         let mut ii = Scalar::new();
         ii.set_int(4);
     }
+
+    #[test]
+    fn points_serialize_compressed() {
+        let rng = &mut thread_rng();
+
+        let g1 = G1::rand(rng);
+        let ser = bincode::serialize(&g1).unwrap();
+        // the compressed form is half the size of the uncompressed one
+        assert_eq!(ser.len(), 48);
+        assert_eq!(
+            2 * ser.len(),
+            g1.into_affine().into_uncompressed().as_ref().len()
+        );
+        assert_eq!(bincode::deserialize::<G1>(&ser).unwrap(), g1);
+
+        let g2 = G2::rand(rng);
+        let ser = bincode::serialize(&g2).unwrap();
+        assert_eq!(ser.len(), 96);
+        assert_eq!(
+            2 * ser.len(),
+            g2.into_affine().into_uncompressed().as_ref().len()
+        );
+        assert_eq!(bincode::deserialize::<G2>(&ser).unwrap(), g2);
+    }
+
+    #[test]
+    fn compressed_signatures_round_trip_and_verify() {
+        let (t, n) = (3, 5);
+        let msg = vec![1, 9, 6, 9];
+
+        let private = Poly::<Scalar>::new(t - 1);
+        let public = private.commit::<G1>();
+
+        // the partial signatures carry their point compressed, as the nodes gossip them
+        let partials = (0..n)
+            .map(|i| {
+                let eval = private.eval(i as Idx);
+                let share = Share {
+                    index: eval.index,
+                    private: eval.value,
+                };
+
+                let partial = G1Scheme::partial_sign(&share, &msg).unwrap();
+                let partial: Eval<Vec<u8>> = bincode::deserialize(&partial).unwrap();
+                let point = bincode::deserialize::<G2>(&partial.value).unwrap();
+
+                let compressed = compress(&point);
+                assert_eq!(
+                    2 * compressed.len(),
+                    point.into_affine().into_uncompressed().as_ref().len()
+                );
+                assert_eq!(decompress::<G2>(&compressed).unwrap(), point);
+
+                bincode::serialize(&Eval {
+                    value: compressed,
+                    index: partial.index,
+                })
+                .unwrap()
+            })
+            .collect::<Vec<_>>();
+
+        for partial in &partials {
+            G1Scheme::partial_verify(&public, &msg, partial).unwrap();
+        }
+
+        let signature = G1Scheme::aggregate(t, &partials).unwrap();
+        let point = decompress::<G2>(&signature).unwrap();
+        assert_eq!(compress(&point), signature);
+
+        let decompressed = bincode::serialize(&point).unwrap();
+        G1Scheme::verify(public.public_key(), &msg, &decompressed).unwrap();
+
+        // a truncated point or one without the compression flag is rejected
+        assert!(decompress::<G2>(&signature[1..]).is_err());
+        let mut garbled = signature.clone();
+        garbled[0] ^= 0x80;
+        assert!(decompress::<G2>(&garbled).is_err());
+    }
 }
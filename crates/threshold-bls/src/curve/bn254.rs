@@ -0,0 +1,287 @@
+use super::zexe_serde::{deserialize_field, deserialize_group, serialize_field, serialize_group};
+use crate::group::{self, Element, PairingCurve as PC, Point, Scalar as Sc};
+use algebra::{
+    bn254 as zexe,
+    curves::{PairingEngine, ProjectiveCurve},
+    fields::Field,
+    prelude::{One, UniformRand, Zero},
+};
+use bls_crypto::{
+    hash_to_curve::{try_and_increment::TryAndIncrement, HashToCurve},
+    hashers::DirectHasher,
+    BLSError, SIG_DOMAIN,
+};
+use rand_core::RngCore;
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt,
+    ops::{AddAssign, MulAssign, Neg, SubAssign},
+};
+
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum BN254Error {
+    #[error("{0}")]
+    SerializationError(#[from] algebra::SerializationError),
+    #[error("{0}")]
+    BLSError(#[from] BLSError),
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Deserialize, Serialize)]
+pub struct Scalar(
+    #[serde(deserialize_with = "deserialize_field")]
+    #[serde(serialize_with = "serialize_field")]
+    <zexe::Bn254 as PairingEngine>::Fr,
+);
+
+type ZG1 = <zexe::Bn254 as PairingEngine>::G1Projective;
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct G1(
+    #[serde(deserialize_with = "deserialize_group")]
+    #[serde(serialize_with = "serialize_group")]
+    ZG1,
+);
+
+type ZG2 = <zexe::Bn254 as PairingEngine>::G2Projective;
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct G2(
+    #[serde(deserialize_with = "deserialize_group")]
+    #[serde(serialize_with = "serialize_group")]
+    ZG2,
+);
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct GT(
+    #[serde(deserialize_with = "deserialize_field")]
+    #[serde(serialize_with = "serialize_field")]
+    <zexe::Bn254 as PairingEngine>::Fqk,
+);
+
+impl Element for Scalar {
+    type RHS = Scalar;
+
+    fn new() -> Self {
+        Self(Zero::zero())
+    }
+
+    fn one() -> Self {
+        Self(One::one())
+    }
+
+    fn add(&mut self, s2: &Self) {
+        self.0.add_assign(s2.0);
+    }
+
+    fn mul(&mut self, mul: &Scalar) {
+        self.0.mul_assign(mul.0)
+    }
+
+    fn rand<R: RngCore>(rng: &mut R) -> Self {
+        Self(zexe::Fr::rand(rng))
+    }
+}
+
+impl Sc for Scalar {
+    fn set_int(&mut self, i: u64) {
+        *self = Self(zexe::Fr::from(i))
+    }
+
+    fn inverse(&self) -> Option<Self> {
+        Some(Self(Field::inverse(&self.0)?))
+    }
+
+    fn negate(&mut self) {
+        *self = Self(self.0.neg())
+    }
+
+    fn sub(&mut self, other: &Self) {
+        self.0.sub_assign(other.0);
+    }
+}
+
+impl fmt::Display for Scalar {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{{{:?}}}", self.0)
+    }
+}
+
+/// G1 points can be multiplied by Fr elements
+impl Element for G1 {
+    type RHS = Scalar;
+
+    fn new() -> Self {
+        Self(Zero::zero())
+    }
+
+    fn one() -> Self {
+        Self(ZG1::prime_subgroup_generator())
+    }
+
+    fn rand<R: RngCore>(rng: &mut R) -> Self {
+        Self(ZG1::rand(rng))
+    }
+
+    fn add(&mut self, s2: &Self) {
+        self.0.add_assign(s2.0);
+    }
+
+    fn mul(&mut self, mul: &Scalar) {
+        self.0.mul_assign(mul.0)
+    }
+}
+
+/// Implementation of Point using G1 from BN254
+impl Point for G1 {
+    type Error = BN254Error;
+
+    fn map(&mut self, data: &[u8]) -> Result<(), BN254Error> {
+        let hasher = TryAndIncrement::new(&DirectHasher);
+
+        let hash = hasher.hash(SIG_DOMAIN, data, &[])?;
+
+        *self = Self(hash);
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for G1 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{{{:?}}}", self.0)
+    }
+}
+
+/// G2 points can be multiplied by Fr elements
+impl Element for G2 {
+    type RHS = Scalar;
+
+    fn new() -> Self {
+        Self(Zero::zero())
+    }
+
+    fn one() -> Self {
+        Self(ZG2::prime_subgroup_generator())
+    }
+
+    fn rand<R: RngCore>(mut rng: &mut R) -> Self {
+        Self(ZG2::rand(&mut rng))
+    }
+
+    fn add(&mut self, s2: &Self) {
+        self.0.add_assign(s2.0);
+    }
+
+    fn mul(&mut self, mul: &Scalar) {
+        self.0.mul_assign(mul.0)
+    }
+}
+
+/// Implementation of Point using G2 from BN254
+impl Point for G2 {
+    type Error = BN254Error;
+
+    fn map(&mut self, data: &[u8]) -> Result<(), BN254Error> {
+        let hasher = TryAndIncrement::new(&DirectHasher);
+
+        let hash = hasher.hash(SIG_DOMAIN, data, &[])?;
+        *self = Self(hash);
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for G2 {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{{{:?}}}", self.0)
+    }
+}
+
+impl Element for GT {
+    type RHS = GT;
+
+    fn new() -> Self {
+        Self(Zero::zero())
+    }
+    fn one() -> Self {
+        Self(One::one())
+    }
+    fn add(&mut self, s2: &Self) {
+        self.0.add_assign(s2.0);
+    }
+    fn mul(&mut self, mul: &GT) {
+        self.0.mul_assign(mul.0)
+    }
+    fn rand<R: RngCore>(rng: &mut R) -> Self {
+        Self(zexe::Fq12::rand(rng))
+    }
+}
+
+impl fmt::Display for GT {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{{{:?}}}", self.0)
+    }
+}
+
+pub type G1Curve = group::G1Curve<PairingCurve>;
+pub type G2Curve = group::G2Curve<PairingCurve>;
+
+#[derive(Clone, Debug)]
+pub struct PairingCurve;
+
+impl PC for PairingCurve {
+    type Scalar = Scalar;
+    type G1 = G1;
+    type G2 = G2;
+    type GT = GT;
+
+    fn pair(a: &Self::G1, b: &Self::G2) -> Self::GT {
+        GT(<zexe::Bn254 as PairingEngine>::pairing(a.0, b.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::{de::DeserializeOwned, Serialize};
+    use static_assertions::assert_impl_all;
+
+    assert_impl_all!(G1: Serialize, DeserializeOwned, Clone);
+    assert_impl_all!(G2: Serialize, DeserializeOwned, Clone);
+    assert_impl_all!(GT: Serialize, DeserializeOwned, Clone);
+    assert_impl_all!(Scalar: Serialize, DeserializeOwned, Clone);
+
+    #[test]
+    fn serialize_group() {
+        serialize_group_test::<G1>(32);
+        serialize_group_test::<G2>(64);
+    }
+
+    fn serialize_group_test<E: Element>(size: usize) {
+        let rng = &mut rand::thread_rng();
+        let sig = E::rand(rng);
+        let ser = bincode::serialize(&sig).unwrap();
+        assert_eq!(ser.len(), size);
+
+        let de: E = bincode::deserialize(&ser).unwrap();
+        assert_eq!(de, sig);
+    }
+
+    #[test]
+    fn serialize_field() {
+        serialize_field_test::<GT>(384);
+        serialize_field_test::<Scalar>(32);
+    }
+
+    fn serialize_field_test<E: Element>(size: usize) {
+        let rng = &mut rand::thread_rng();
+        let sig = E::rand(rng);
+        let ser = bincode::serialize(&sig).unwrap();
+        assert_eq!(ser.len(), size);
+
+        let de: E = bincode::deserialize(&ser).unwrap();
+        assert_eq!(de, sig);
+    }
+}
@@ -6,6 +6,13 @@ pub mod bls12381;
 #[cfg(feature = "bls12_377")]
 pub mod zexe;
 
+/// Wrappers around the BN254 curve from [zexe](https://github.com/scipr-lab/zexe/tree/master/algebra/src/bn254)
+#[cfg(feature = "bn254")]
+pub mod bn254;
+
+#[cfg(any(feature = "bls12_377", feature = "bn254"))]
+mod zexe_serde;
+
 use thiserror::Error;
 
 /// Error which unifies all curve specific errors from different libraries
@@ -18,4 +25,8 @@ pub enum CurveError {
     #[cfg(feature = "bls12_381")]
     #[error("Bellman Error: {0}")]
     BLS12_381(bls12381::BellmanError),
+
+    #[cfg(feature = "bn254")]
+    #[error("BN254 Error: {0}")]
+    BN254(bn254::BN254Error),
 }
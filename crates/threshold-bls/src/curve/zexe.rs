@@ -1,10 +1,10 @@
+use super::zexe_serde::{deserialize_field, deserialize_group, serialize_field, serialize_group};
 use crate::group::{self, Element, PairingCurve as PC, Point, Scalar as Sc};
 use algebra::{
     bls12_377 as zexe,
-    curves::{AffineCurve, PairingEngine, ProjectiveCurve},
+    curves::{PairingEngine, ProjectiveCurve},
     fields::Field,
     prelude::{One, UniformRand, Zero},
-    CanonicalDeserialize, CanonicalSerialize, ConstantSerializedSize,
 };
 use bls_crypto::{
     hash_to_curve::{try_and_increment::TryAndIncrement, HashToCurve},
@@ -12,14 +12,9 @@ use bls_crypto::{
     BLSError, SIG_DOMAIN,
 };
 use rand_core::RngCore;
-use serde::{
-    de::{Error as DeserializeError, SeqAccess, Visitor},
-    ser::{Error as SerializationError, SerializeTuple},
-    Deserialize, Deserializer, Serialize, Serializer,
-};
+use serde::{Deserialize, Serialize};
 use std::{
     fmt,
-    marker::PhantomData,
     ops::{AddAssign, MulAssign, Neg, SubAssign},
 };
 
@@ -249,124 +244,6 @@ impl PC for PairingCurve {
     }
 }
 
-// Serde implementations (ideally, these should be upstreamed to Zexe)
-
-fn deserialize_field<'de, D, C>(deserializer: D) -> Result<C, D::Error>
-where
-    D: Deserializer<'de>,
-    C: CanonicalDeserialize + ConstantSerializedSize,
-{
-    struct FieldVisitor<C>(PhantomData<C>);
-
-    impl<'de, C> Visitor<'de> for FieldVisitor<C>
-    where
-        C: CanonicalDeserialize + ConstantSerializedSize,
-    {
-        type Value = C;
-
-        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-            formatter.write_str("a valid group element")
-        }
-
-        fn visit_seq<S>(self, mut seq: S) -> Result<C, S::Error>
-        where
-            S: SeqAccess<'de>,
-        {
-            let len = C::SERIALIZED_SIZE;
-            let bytes: Vec<u8> = (0..len)
-                .map(|_| {
-                    seq.next_element()?
-                        .ok_or_else(|| DeserializeError::custom("could not read bytes"))
-                })
-                .collect::<Result<Vec<_>, _>>()?;
-
-            let res = C::deserialize(&mut &bytes[..]).map_err(DeserializeError::custom)?;
-            Ok(res)
-        }
-    }
-
-    let visitor = FieldVisitor(PhantomData);
-    deserializer.deserialize_tuple(C::SERIALIZED_SIZE, visitor)
-}
-
-fn serialize_field<S, C>(c: &C, s: S) -> Result<S::Ok, S::Error>
-where
-    S: Serializer,
-    C: CanonicalSerialize,
-{
-    let len = c.serialized_size();
-    let mut bytes = Vec::with_capacity(len);
-    c.serialize(&mut bytes)
-        .map_err(SerializationError::custom)?;
-
-    let mut tup = s.serialize_tuple(len)?;
-    for byte in &bytes {
-        tup.serialize_element(byte)?;
-    }
-    tup.end()
-}
-
-fn deserialize_group<'de, D, C>(deserializer: D) -> Result<C, D::Error>
-where
-    D: Deserializer<'de>,
-    C: ProjectiveCurve,
-    C::Affine: CanonicalDeserialize + ConstantSerializedSize,
-{
-    struct GroupVisitor<C>(PhantomData<C>);
-
-    impl<'de, C> Visitor<'de> for GroupVisitor<C>
-    where
-        C: ProjectiveCurve,
-        C::Affine: CanonicalDeserialize + ConstantSerializedSize,
-    {
-        type Value = C;
-
-        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-            formatter.write_str("a valid group element")
-        }
-
-        fn visit_seq<S>(self, mut seq: S) -> Result<C, S::Error>
-        where
-            S: SeqAccess<'de>,
-        {
-            let len = C::Affine::SERIALIZED_SIZE;
-            let bytes: Vec<u8> = (0..len)
-                .map(|_| {
-                    seq.next_element()?
-                        .ok_or_else(|| DeserializeError::custom("could not read bytes"))
-                })
-                .collect::<Result<Vec<_>, _>>()?;
-
-            let affine =
-                C::Affine::deserialize(&mut &bytes[..]).map_err(DeserializeError::custom)?;
-            Ok(affine.into_projective())
-        }
-    }
-
-    let visitor = GroupVisitor(PhantomData);
-    deserializer.deserialize_tuple(C::Affine::SERIALIZED_SIZE, visitor)
-}
-
-fn serialize_group<S, C>(c: &C, s: S) -> Result<S::Ok, S::Error>
-where
-    S: Serializer,
-    C: ProjectiveCurve,
-    C::Affine: CanonicalSerialize,
-{
-    let affine = c.into_affine();
-    let len = affine.serialized_size();
-    let mut bytes = Vec::with_capacity(len);
-    affine
-        .serialize(&mut bytes)
-        .map_err(SerializationError::custom)?;
-
-    let mut tup = s.serialize_tuple(len)?;
-    for byte in &bytes {
-        tup.serialize_element(byte)?;
-    }
-    tup.end()
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -166,6 +166,13 @@
 //! threshold-bls = { version = "0.1" }
 //! ```
 //!
+//! BN254, which the EVM pairing precompiles operate on, is available behind the opt-in
+//! `bn254` feature:
+//!
+//! ```toml
+//! threshold-bls = { version = "0.1", default-features = false, features = ["bn254"] }
+//! ```
+//!
 //! [poly]: ./poly/index.html
 //! [bls12_377]: ./curve/zexe/index.html
 //!
@@ -217,4 +224,16 @@ pub mod schemes {
         /// Public Keys on G2, Signatures on G1
         pub type G2Scheme = super::G2Scheme<PairingCurve>;
     }
+
+    #[cfg(feature = "bn254")]
+    /// BN254 Schemes, matching the pairing precompiles of the EVM
+    pub mod bn254 {
+        use crate::curve::bn254::PairingCurve;
+        pub use crate::curve::bn254::{G1Curve, G2Curve};
+
+        /// Public Keys on G1, Signatures on G2
+        pub type G1Scheme = super::G1Scheme<PairingCurve>;
+        /// Public Keys on G2, Signatures on G1
+        pub type G2Scheme = super::G2Scheme<PairingCurve>;
+    }
 }
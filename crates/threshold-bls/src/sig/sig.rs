@@ -1,7 +1,7 @@
 pub use super::tbls::Share; // import and re-export it for easier access
 use crate::{
     group::{Element, Point, Scalar},
-    poly::Poly,
+    poly::{Idx, Poly},
 };
 use rand_core::RngCore;
 use serde::{de::DeserializeOwned, Serialize};
@@ -154,6 +154,17 @@ pub trait ThresholdScheme: Scheme {
         partial: &[u8],
     ) -> Result<(), Self::Error>;
 
+    /// Verifies many partial signatures on the same message at once, each given
+    /// along with the index of its signer. A random linear combination of the partials
+    /// is checked against the same combination of the public shares, so that a valid
+    /// batch costs a single verification. If the batch fails, every partial is verified
+    /// on its own and the indices of the invalid ones are returned.
+    fn batch_partial_verify(
+        public: &Poly<Self::Public>,
+        msg: &[u8],
+        partials: &[(Idx, Partial)],
+    ) -> Result<(), Vec<Idx>>;
+
     /// Aggregates all partials signature together. Note that this method does
     /// not verify if the partial signatures are correct or not; it only
     /// aggregates them.
@@ -1,5 +1,6 @@
 //! Threshold Signatures implementation for any type which implements
 //! [`SignatureScheme`](../trait.SignatureScheme.html)
+use crate::group::Element;
 use crate::poly::{Eval, Idx, Poly, PolyError};
 use crate::sig::{Partial, SignatureScheme, ThresholdScheme};
 use serde::{Deserialize, Serialize};
@@ -63,6 +64,70 @@ impl<I: SignatureScheme> ThresholdScheme for I {
         Self::verify(&public_i.value, msg, &partial.value).map_err(ThresholdError::SignatureError)
     }
 
+    fn batch_partial_verify(
+        public: &Poly<Self::Public>,
+        msg: &[u8],
+        partials: &[(Idx, Partial)],
+    ) -> Result<(), Vec<Idx>> {
+        let rng = &mut rand::thread_rng();
+
+        let mut combined_sig = Self::Signature::new();
+        let mut combined_public = Self::Public::new();
+        let mut decoded = true;
+
+        for (index, partial) in partials {
+            let sig = bincode::deserialize::<Eval<Vec<u8>>>(partial)
+                .ok()
+                .filter(|eval| eval.index == *index)
+                .and_then(|eval| bincode::deserialize::<Self::Signature>(&eval.value).ok());
+
+            let mut sig = match sig {
+                Some(sig) => sig,
+                None => {
+                    decoded = false;
+                    break;
+                }
+            };
+
+            // the random coefficients keep invalid partials from cancelling each other out
+            let coefficient = Self::Private::rand(rng);
+
+            sig.mul(&coefficient);
+            combined_sig.add(&sig);
+
+            let mut public_i = public.eval(*index).value;
+            public_i.mul(&coefficient);
+            combined_public.add(&public_i);
+        }
+
+        if decoded {
+            let combined_sig = bincode::serialize(&combined_sig).expect("could not serialize");
+
+            if Self::verify(&combined_public, msg, &combined_sig).is_ok() {
+                return Ok(());
+            }
+        }
+
+        // find the culprits
+        let invalid = partials
+            .iter()
+            .filter(|(index, partial)| {
+                let matches_index = bincode::deserialize::<Eval<Vec<u8>>>(partial)
+                    .map(|eval| eval.index == *index)
+                    .unwrap_or(false);
+
+                !matches_index || Self::partial_verify(public, msg, partial).is_err()
+            })
+            .map(|(index, _)| *index)
+            .collect::<Vec<_>>();
+
+        if invalid.is_empty() {
+            Ok(())
+        } else {
+            Err(invalid)
+        }
+    }
+
     fn aggregate(
         threshold: usize,
         partials: &[Partial],
@@ -145,6 +210,48 @@ mod tests {
         T::verify(public.public_key(), &msg, &final_sig).unwrap();
     }
 
+    fn test_batch_partial_verify<T: ThresholdScheme + SignatureScheme>(creator: ShareCreator<T>) {
+        let (shares, public) = creator(7, 4);
+        let msg = vec![1, 9, 6, 9];
+
+        let mut partials: Vec<_> = shares
+            .iter()
+            .map(|s| (s.index, T::partial_sign(s, &msg).unwrap()))
+            .collect();
+
+        assert!(T::batch_partial_verify(&public, &msg, &partials).is_ok());
+        assert!(T::batch_partial_verify(&public, &msg, &[]).is_ok());
+
+        // a partial over another message is the only one reported
+        partials[2].1 = T::partial_sign(&shares[2], &[6, 9]).unwrap();
+
+        assert_eq!(
+            T::batch_partial_verify(&public, &msg, &partials),
+            Err(vec![shares[2].index])
+        );
+
+        // so is a partial claiming the index of another signer
+        partials[2].1 = T::partial_sign(&shares[2], &msg).unwrap();
+        partials[5].0 = shares[4].index;
+
+        assert_eq!(
+            T::batch_partial_verify(&public, &msg, &partials),
+            Err(vec![shares[4].index])
+        );
+    }
+
+    #[test]
+    fn batch_partial_verify_g1() {
+        type S = G1Scheme<PCurve>;
+        test_batch_partial_verify::<S>(shares::<S>);
+    }
+
+    #[test]
+    fn batch_partial_verify_g2() {
+        type S = G2Scheme<PCurve>;
+        test_batch_partial_verify::<S>(shares::<S>);
+    }
+
     #[test]
     fn threshold_g1() {
         type S = G1Scheme<PCurve>;